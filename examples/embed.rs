@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use rover::{Engine, IntoValue, Result, Value};
+
+fn run() -> Result<()> {
+	let mut engine = Engine::new();
+
+	// rust functions are called from mars like any other
+	engine.register("hypot", |a: f64, b: f64| (a * a + b * b).sqrt())?;
+	engine.register("parse", |text: String| text.trim().parse::<f64>().map_err(|err| err.to_string()))?;
+	engine.set("limits", vec![1, 2, 3])?;
+
+	let total: f64 = engine.eval("hypot(3, 4) + parse(' 10 ')")?;
+	println!("total = {}", total);
+
+	engine.eval::<()>("
+		function scale(list, factor) {
+			let out = [];
+			for x in list { out.push(x * factor); }
+			return out;
+		}
+	")?;
+	let scaled: Vec<i64> = engine.call("scale", vec![engine.get("limits")?, 10.into_value()])?;
+	println!("scaled = {:?}", scaled);
+
+	let mut point = HashMap::new();
+	point.insert("x".to_owned(), 1.5);
+	point.insert("y".to_owned(), -2.0);
+	engine.set("point", point)?;
+	let point: HashMap<String, f64> = engine.eval("{ x = point.x * 2, y = point.y * 2 }")?;
+	println!("point = ({}, {})", point["x"], point["y"]);
+
+	// mars functions can be kept and called later
+	engine.eval::<()>("let square = function(n) => n * n")?;
+	let callback: Box<dyn Value> = engine.get("square")?;
+	let squared: f64 = engine.apply(callback.as_ref(), vec![7.into_value()])?;
+	println!("squared = {}", squared);
+
+	if let Err(err) = engine.eval::<f64>("parse('not a number')") {
+		println!("error: {}", err);
+	}
+	Ok(())
+}
+
+fn main() {
+	if let Err(err) = run() {
+		eprintln!("{}", err);
+	}
+}
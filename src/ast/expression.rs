@@ -5,7 +5,7 @@ use crate::utils::{result::*, source_pos::*};
 
 use self::ExprType::*;
 
-use super::{identifier::Identifier, Block};
+use super::{identifier::Identifier, pattern::Pattern, types::Type, Block};
 
 #[derive(Debug, Clone)]
 pub enum BinaryOperator {
@@ -21,7 +21,9 @@ pub enum UnaryOperator {
 
 #[derive(Debug, Clone)]
 pub enum LogicOperator {
-	And, Or
+	And, Or,
+	// gives the right side when the left one is none or an error
+	Coalesce,
 }
 
 #[derive(Debug, Clone)]
@@ -33,7 +35,9 @@ pub enum LiteralData {
 	// The following variants aren't "really" literals, maybe we should move them to another expression type
 	Template(Vec<Expression>),
 	List(Vec<Expression>),
-	Object(HashMap<String, Expression>, HashSet<Identifier>),
+	Map(Vec<(Expression, Expression)>),
+	// spread objects are copied in, along with the fields, in the order they were written
+	Object(HashMap<String, Expression>, HashSet<Identifier>, Vec<Expression>),
 	Error(Box<Expression>),
 }
 
@@ -45,14 +49,28 @@ pub struct LogicData { pub lhs: Box<Expression>, pub op: LogicOperator, pub rhs:
 pub struct BinaryData { pub lhs: Box<Expression>, pub op: BinaryOperator, pub rhs: Box<Expression> }
 #[derive(Debug, Clone)]
 pub struct UnaryData { pub op: UnaryOperator, pub expr: Box<Expression> }
+// an optional call, index or field, written with `?.`, gives none instead when its head is none
 #[derive(Debug, Clone)]
-pub struct CallData { pub calee: Box<Expression>, pub args: Vec<Expression> }
+pub struct CallData { pub calee: Box<Expression>, pub args: Vec<Expression>, pub named: Vec<NamedArg>, pub optional: bool }
+// either bound can be left out, which only makes sense when slicing
 #[derive(Debug, Clone)]
-pub struct IndexData { pub head: Box<Expression>, pub index: Box<Expression> }
+pub struct RangeData { pub start: Option<Box<Expression>>, pub end: Option<Box<Expression>>, pub inclusive: bool, pub step: Option<Box<Expression>> }
 #[derive(Debug, Clone)]
-pub struct FieldData { pub head: Box<Expression>, pub field: String }
+pub struct NamedArg { pub name: String, pub pos: SourcePos, pub expr: Expression }
 #[derive(Debug, Clone)]
-pub struct LambdaData { pub params: Vec<Identifier>, pub body: Block }
+pub struct IndexData { pub head: Box<Expression>, pub index: Box<Expression>, pub optional: bool }
+#[derive(Debug, Clone)]
+pub struct FieldData { pub head: Box<Expression>, pub field: String, pub optional: bool }
+// only the last parameter can be a rest one, collecting the arguments left over in a list.
+// a destructured parameter gets a hidden name, and its pattern is bound from it when the call starts
+#[derive(Debug, Clone)]
+pub struct Param { pub name: Identifier, pub typ: Option<Type>, pub default: Option<Expression>, pub rest: bool, pub pattern: Option<Pattern> }
+#[derive(Debug, Clone)]
+pub struct LambdaData { pub params: Vec<Param>, pub ret: Option<Type>, pub body: Block }
+#[derive(Debug, Clone)]
+pub struct MatchArm { pub pattern: Pattern, pub guard: Option<Expression>, pub body: Expression }
+#[derive(Debug, Clone)]
+pub struct MatchData { pub value: Box<Expression>, pub arms: Vec<MatchArm> }
 
 #[derive(Debug, Clone)]
 pub enum ExprType {
@@ -68,9 +86,23 @@ pub enum ExprType {
 	Variable(Identifier),
 	Lambda(LambdaData),
 	DoExpr(Block),
+	Match(MatchData),
+	// only found among the items of a list, the members of an object and the arguments of a call
+	Spread(Box<Expression>),
+	Range(RangeData),
 	SelfRef,
 }
 
+impl Param {
+	// how errors refer to the parameter, hidden names only tell its position
+	pub fn label(&self) -> String {
+		match self.name.name.strip_prefix('$') {
+			Some(index) => (index.parse::<usize>().unwrap_or_default() + 1).to_string(),
+			None => format!("'{}'", self.name),
+		}
+	}
+}
+
 impl ExprType {
 	pub fn to_expr(self, pos: SourcePos) -> Expression {
 		Expression { typ: self, pos }
@@ -105,6 +137,9 @@ impl Expression {
 			SelfRef => visitor.self_ref(self.pos),
 			DoExpr(block) => visitor.do_expr(block, self.pos),
     	Binding(data) => visitor.bind_expr(data, self.pos),
+			Match(data) => visitor.match_expr(data, self.pos),
+			Spread(expr) => visitor.spread(expr, self.pos),
+			Range(data) => visitor.range(data, self.pos),
 		}
 	}
 }
@@ -123,4 +158,7 @@ pub trait ExprVisitor<T> {
 	fn self_ref(&mut self, pos: SourcePos) -> Result<T>;
 	fn do_expr(&mut self, block: Block, pos: SourcePos) -> Result<T>;
 	fn bind_expr(&mut self, data: BindData, pos: SourcePos) -> Result<T>;
+	fn match_expr(&mut self, data: MatchData, pos: SourcePos) -> Result<T>;
+	fn spread(&mut self, expr: Box<Expression>, pos: SourcePos) -> Result<T>;
+	fn range(&mut self, data: RangeData, pos: SourcePos) -> Result<T>;
 }
\ No newline at end of file
@@ -1,24 +1,41 @@
 
 use std::{cell::RefCell, fmt::Display, rc::Rc, hash::Hash};
 
-use crate::utils::wrap::Wrap;
+use crate::utils::{source_pos::SourcePos, wrap::Wrap};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// where a name lives at runtime, module level names are looked up by id, local ones by scope depth and slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+	Global,
+	Local(usize, usize),
+}
+
+#[derive(Debug, Clone)]
 pub struct Identifier {
 	pub name: String,
 	pub id: Rc<RefCell<usize>>,
+	pub location: Rc<RefCell<Location>>,
+	// where the name was written, only used by tooling
+	pub pos: SourcePos,
 }
 
 impl Identifier {
 
-	pub fn new(name: String) -> Self {
-		Self { name, id: usize::default().wrap() }
+	pub fn new(name: String, pos: SourcePos) -> Self {
+		Self { name, id: usize::default().wrap(), location: Location::Global.wrap(), pos }
+	}
+
+	// names that aren't written anywhere, like the ones of native attributes
+	pub fn native(name: &str, id: usize) -> Self {
+		Self { name: name.to_owned(), id: id.wrap(), location: Location::Global.wrap(), pos: SourcePos::new(0, 0) }
 	}
 
 	pub fn same(src: &Identifier) -> Self {
 		Self {
 			name: src.get_name(),
-			id: src.id.clone()
+			id: src.id.clone(),
+			location: src.location.clone(),
+			pos: src.pos,
 		}
 	}
 
@@ -27,11 +44,24 @@ impl Identifier {
 	}
 
 	pub fn get_id(&self) -> usize {
-		self.id.borrow().clone()
+		*self.id.borrow()
+	}
+
+	pub fn get_location(&self) -> Location {
+		*self.location.borrow()
 	}
 
 }
 
+// the same name can be written in many places, so positions don't take part in comparisons
+impl PartialEq for Identifier {
+	fn eq(&self, other: &Self) -> bool {
+		self.name == other.name && self.id == other.id && self.location == other.location
+	}
+}
+
+impl Eq for Identifier {}
+
 impl Hash for Identifier {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
 		self.name.hash(state);
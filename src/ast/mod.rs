@@ -1,8 +1,10 @@
 
 pub mod expression;
+pub mod pattern;
 pub mod statement;
 pub mod identifier;
 pub mod module;
+pub mod types;
 
 use self::statement::Statement;
 
@@ -11,6 +11,12 @@ pub struct Module {
 	pub main_id: Rc<RefCell<Option<usize>>>,
 }
 
+impl Default for Module {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl Module {
 
 	pub fn new() -> Self {
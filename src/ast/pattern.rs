@@ -0,0 +1,41 @@
+
+use crate::utils::source_pos::SourcePos;
+
+use super::{identifier::Identifier, expression::LiteralData};
+
+#[derive(Debug, Clone)]
+pub enum PatternType {
+	Wildcard,
+	Literal(LiteralData),
+	Binding(Identifier),
+	List(Vec<Pattern>),
+	// only allowed as the last item of a list pattern, it takes every item left
+	Rest(Box<Pattern>),
+	Object(Vec<(String, Pattern)>, Vec<Identifier>),
+	Attribute(Identifier),
+}
+
+impl PatternType {
+	pub fn to_pattern(self, pos: SourcePos) -> Pattern {
+		Pattern { typ: self, pos }
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+	pub typ: PatternType,
+	pub pos: SourcePos,
+}
+
+impl Pattern {
+	// every name bound by the pattern, in the order they appear
+	pub fn bindings(&self) -> Vec<Identifier> {
+		match &self.typ {
+			PatternType::Binding(name) => vec![name.clone()],
+			PatternType::List(patterns) => patterns.iter().flat_map(|pattern| pattern.bindings()).collect(),
+			PatternType::Rest(pattern) => pattern.bindings(),
+			PatternType::Object(fields, _) => fields.iter().flat_map(|(_, pattern)| pattern.bindings()).collect(),
+			_ => vec![],
+		}
+	}
+}
@@ -3,27 +3,33 @@ use std::collections::{HashMap, HashSet};
 
 use crate::utils::{result::Result, source_pos::SourcePos};
 
-use super::{identifier::Identifier, expression::Expression, Block};
+use super::{identifier::Identifier, expression::{Expression, Param}, pattern::Pattern, types::Type, Block};
 
 use self::StmtType::*;
 
 #[derive(Debug, Clone)]
-pub struct DeclarationData { pub constant: bool, pub name: Identifier, pub expr: Box<Expression> }
+pub struct DeclarationData { pub constant: bool, pub name: Identifier, pub typ: Option<Type>, pub expr: Box<Expression> }
 #[derive(Debug, Clone)]
-pub struct FunctionData { pub name: Identifier, pub params: Vec<Identifier>, pub body: Block }
+pub struct DestructureData { pub constant: bool, pub pattern: Pattern, pub expr: Box<Expression> }
+#[derive(Debug, Clone)]
+pub struct FunctionData { pub name: Identifier, pub params: Vec<Param>, pub ret: Option<Type>, pub body: Block }
 #[derive(Debug, Clone)]
 pub struct AttrDeclarationData { pub name: Identifier, pub fields: HashMap<String, Expression>, pub methods: Vec<FunctionData>, pub attributes: HashSet<Identifier> }
 #[derive(Debug, Clone)]
 pub struct AssignData { pub head: Box<Expression>, pub l_pos: SourcePos, pub expr: Box<Expression> }
 #[derive(Debug, Clone)]
+pub struct ImportData { pub name: Identifier, pub path: String }
+#[derive(Debug, Clone)]
 pub struct IfData { pub cond: Box<Expression>, pub then_block: Block, pub else_block: Block }
 
 #[derive(Debug, Clone)]
 pub enum StmtType {
 	Expr(Box<Expression>),
 	Declaration(DeclarationData),
+	Destructure(DestructureData),
 	FuncDeclaration(FunctionData),
 	AttrDeclaration(AttrDeclarationData),
+	Import(ImportData),
 	Assignment(AssignData),
 	If(IfData),
 	Loop(Block),
@@ -55,8 +61,10 @@ impl Statement {
 		match self.typ {
 			Expr(expr) => visitor.expr(expr, self.pos),
 			Declaration(data) => visitor.declaration(data, self.pos),
+			Destructure(data) => visitor.destructure(data, self.pos),
 			FuncDeclaration(data) => visitor.func_declaration(data, self.pos),
 			AttrDeclaration(data) => visitor.attr_declaration(data, self.pos),
+			Import(data) => visitor.import(data, self.pos),
 			Assignment(data) => { let l_pos = data.l_pos; visitor.assignment(data, l_pos) },
 			If(data) => visitor.if_stmt(data, self.pos),
 			Loop(block) => visitor.loop_stmt(block, self.pos),
@@ -71,8 +79,10 @@ impl Statement {
 pub trait StmtVisitor<T> {
 	fn expr(&mut self, expr: Box<Expression>, pos: SourcePos) -> Result<T>;
 	fn declaration(&mut self, data: DeclarationData, pos: SourcePos) -> Result<T>;
+	fn destructure(&mut self, data: DestructureData, pos: SourcePos) -> Result<T>;
 	fn func_declaration(&mut self, data: FunctionData, pos: SourcePos) -> Result<T>;
 	fn attr_declaration(&mut self, data: AttrDeclarationData, pos: SourcePos) -> Result<T>;
+	fn import(&mut self, data: ImportData, pos: SourcePos) -> Result<T>;
 	fn assignment(&mut self, data: AssignData, pos: SourcePos) -> Result<T>;
 	fn if_stmt(&mut self, data: IfData, pos: SourcePos) -> Result<T>;
 	fn loop_stmt(&mut self, block: Block, pos: SourcePos) -> Result<T>;
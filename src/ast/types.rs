@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+// the static type of a value as written in annotations, Any matches every value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+	Any,
+	None,
+	Num,
+	Str,
+	Bool,
+	List,
+	Map,
+	Object,
+	Function,
+	Error,
+	Iterator,
+	Range,
+	Attribute,
+	Named(String),
+	Optional(Box<Type>),
+}
+
+impl Type {
+	pub fn from_name(name: &str) -> Self {
+		match name {
+			"any" => Type::Any,
+			"none" => Type::None,
+			"number" => Type::Num,
+			"string" => Type::Str,
+			"bool" => Type::Bool,
+			"list" => Type::List,
+			"map" => Type::Map,
+			"object" => Type::Object,
+			"function" => Type::Function,
+			"error" => Type::Error,
+			"iterator" => Type::Iterator,
+			"range" => Type::Range,
+			"attribute" => Type::Attribute,
+			_ => Type::Named(name.to_owned()),
+		}
+	}
+
+	pub fn optional(self) -> Self {
+		match self {
+			Type::Any | Type::None | Type::Optional(_) => self,
+			typ => Type::Optional(Box::new(typ)),
+		}
+	}
+
+	// whether some value could have both types, only a definite mismatch is an error
+	pub fn compatible(&self, other: &Type) -> bool {
+		match (self, other) {
+			(Type::Any, _) | (_, Type::Any) => true,
+			(Type::Optional(_), Type::None) | (Type::None, Type::Optional(_)) => true,
+			(Type::Optional(a), b) | (b, Type::Optional(a)) => a.compatible(b),
+			// an object can have several attributes, so named types only rule out non objects
+			(Type::Named(_) | Type::Object, Type::Named(_) | Type::Object) => true,
+			(a, b) => a == b,
+		}
+	}
+
+	// the union of both types when it can still be expressed, otherwise Any
+	pub fn join(self, other: Type) -> Self {
+		match (self, other) {
+			(a, b) if a == b => a,
+			(Type::None, b) | (b, Type::None) => b.optional(),
+			(Type::Optional(a), b) | (b, Type::Optional(a)) if *a == b => Type::Optional(a),
+			_ => Type::Any,
+		}
+	}
+}
+
+impl Display for Type {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Type::Any => write!(f, "any"),
+			Type::None => write!(f, "none"),
+			Type::Num => write!(f, "number"),
+			Type::Str => write!(f, "string"),
+			Type::Bool => write!(f, "bool"),
+			Type::List => write!(f, "list"),
+			Type::Map => write!(f, "map"),
+			Type::Object => write!(f, "object"),
+			Type::Function => write!(f, "function"),
+			Type::Error => write!(f, "error"),
+			Type::Iterator => write!(f, "iterator"),
+			Type::Range => write!(f, "range"),
+			Type::Attribute => write!(f, "attribute"),
+			Type::Named(name) => write!(f, "{}", name),
+			Type::Optional(typ) => write!(f, "{}?", typ),
+		}
+	}
+}
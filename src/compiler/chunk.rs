@@ -0,0 +1,135 @@
+use std::rc::Rc;
+
+use crate::{ast::{expression::BinaryOperator, identifier::Identifier, pattern::Pattern}, interpreter::value::{Value, primitives::callable::Params}, utils::source_pos::SourcePos};
+
+// where a closure finds each of its captured variables when it is created
+#[derive(Debug, Clone, Copy)]
+pub enum Capture {
+	Local(usize),
+	Upvalue(usize),
+}
+
+#[derive(Debug, Clone)]
+pub enum Op {
+	Constant(usize),
+	None, True, False,
+	Pop,
+
+	DefineLocal(usize), GetLocal(usize), SetLocal(usize),
+	GetUpvalue(usize), SetUpvalue(usize),
+	DefineGlobal(usize), GetGlobal(usize), SetGlobal(usize),
+	GetSelf,
+
+	Binary(BinaryOperator, SourcePos, SourcePos),
+	Not, Neg(SourcePos), Pos(SourcePos),
+	ToBool,
+
+	Jump(usize), JumpIfFalse(usize), JumpIfTrue(usize),
+	// skips the default of a parameter that the call passed
+	JumpIfPassed(usize, usize),
+	// both leave the value on the stack when they jump, the second one pops it otherwise
+	JumpIfNone(usize), JumpIfPresent(usize),
+	EnterLoop, ExitLoop, Unwind,
+
+	Call(usize),
+	Index(SourcePos, SourcePos),
+	SetIndex(SourcePos, SourcePos),
+	GetField(usize),
+	SetField(usize, SourcePos),
+	Bind(SourcePos),
+	Match(usize),
+	Destructure(usize),
+
+	Template(usize),
+	List(usize),
+	SpreadList(usize),
+	Map(usize),
+	Object(usize),
+	Range(usize),
+	Error,
+	Closure(usize),
+	Attribute(usize),
+	Import(usize),
+
+	Return,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallSite { pub argc: usize, pub calee_pos: SourcePos, pub arg_pos: Vec<SourcePos>, pub spreads: Vec<Option<SourcePos>>, pub named: Vec<(String, SourcePos)> }
+// a member without a key is a spread, along with the position of the spread value
+#[derive(Debug, Clone)]
+pub struct ObjectShape { pub members: Vec<(Option<String>, SourcePos)>, pub attributes: Vec<usize> }
+#[derive(Debug, Clone)]
+pub struct AttrShape { pub name: Identifier, pub methods: Vec<String>, pub fields: Vec<String>, pub attributes: Vec<usize> }
+// where each bound of a range literal was written, the ones left out aren't on the stack
+#[derive(Debug, Clone)]
+pub struct RangeShape { pub start: Option<SourcePos>, pub end: Option<SourcePos>, pub step: Option<SourcePos>, pub inclusive: bool }
+// the slots are where each name bound by the pattern is stored, by its id
+#[derive(Debug, Clone)]
+pub struct MatchShape { pub pattern: Pattern, pub slots: Vec<(usize, usize)> }
+
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+	pub code: Vec<Op>,
+	pub positions: Vec<SourcePos>,
+	pub constants: Vec<Box<dyn Value>>,
+	pub names: Vec<String>,
+	pub protos: Vec<Rc<Proto>>,
+	pub calls: Vec<CallSite>,
+	pub templates: Vec<Vec<SourcePos>>,
+	// the position of each key in a map literal
+	pub maps: Vec<Vec<SourcePos>>,
+	// the position of the value of each spread item in a list literal
+	pub spreads: Vec<Vec<Option<SourcePos>>>,
+	pub objects: Vec<ObjectShape>,
+	pub ranges: Vec<RangeShape>,
+	pub attributes: Vec<AttrShape>,
+	pub matches: Vec<MatchShape>,
+}
+
+impl Chunk {
+
+	pub fn emit(&mut self, op: Op, pos: SourcePos) -> usize {
+		self.code.push(op);
+		self.positions.push(pos);
+		self.code.len() - 1
+	}
+
+	pub fn patch(&mut self, at: usize, target: usize) {
+		self.code[at] = match self.code[at] {
+			Op::Jump(_) => Op::Jump(target),
+			Op::JumpIfFalse(_) => Op::JumpIfFalse(target),
+			Op::JumpIfTrue(_) => Op::JumpIfTrue(target),
+			Op::JumpIfPassed(param, _) => Op::JumpIfPassed(param, target),
+			Op::JumpIfNone(_) => Op::JumpIfNone(target),
+			Op::JumpIfPresent(_) => Op::JumpIfPresent(target),
+			_ => panic!("Tried to patch an instruction that isn't a jump"),
+		}
+	}
+
+	pub fn name(&mut self, name: &str) -> usize {
+		match self.names.iter().position(|n| n == name) {
+			Some(i) => i,
+			None => {
+				self.names.push(name.to_owned());
+				self.names.len() - 1
+			}
+		}
+	}
+
+	pub fn constant(&mut self, value: Box<dyn Value>) -> usize {
+		self.constants.push(value);
+		self.constants.len() - 1
+	}
+
+}
+
+// a compiled function, closures pair it with the cells of the variables it captures
+#[derive(Debug, Clone, Default)]
+pub struct Proto {
+	pub name: Option<String>,
+	pub params: Params,
+	pub slots: usize,
+	pub captures: Vec<Capture>,
+	pub chunk: Chunk,
+}
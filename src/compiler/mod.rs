@@ -0,0 +1,676 @@
+pub mod chunk;
+
+use std::rc::Rc;
+
+use crate::{ast::{identifier::Identifier, expression::*, pattern::Pattern, statement::*, Block, module::Module}, interpreter::value::primitives::{callable::Params, number::Number, string::Str}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+use self::chunk::{AttrShape, CallSite, Capture, Chunk, MatchShape, ObjectShape, Op, Proto, RangeShape};
+
+// slots that don't belong to a named variable, like the result of a do expression
+const HIDDEN: usize = usize::MAX;
+
+struct LoopState {
+	start: usize,
+	breaks: Vec<usize>,
+}
+
+#[derive(Default)]
+struct FunctionState {
+	name: Option<String>,
+	params: Params,
+	chunk: Chunk,
+	locals: Vec<(usize, usize)>,
+	scopes: Vec<usize>,
+	max_slots: usize,
+	upvalues: Vec<(usize, Capture)>,
+	loops: Vec<LoopState>,
+	// the slot that receives the value of expression statements, used to give do expressions a value
+	eval_target: Option<usize>,
+}
+
+impl FunctionState {
+
+	fn new(name: Option<String>) -> Self {
+		Self { name, ..Default::default() }
+	}
+
+	fn local(&self, id: usize) -> Option<usize> {
+		self.locals.iter().rev().find(|(local, _)| *local == id).map(|(_, slot)| *slot)
+	}
+
+	fn into_proto(self) -> Proto {
+		Proto {
+			name: self.name,
+			params: self.params,
+			slots: self.max_slots,
+			captures: self.upvalues.into_iter().map(|(_, capture)| capture).collect(),
+			chunk: self.chunk,
+		}
+	}
+
+}
+
+pub struct Compiler {
+	functions: Vec<FunctionState>,
+}
+
+impl Default for Compiler {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Compiler {
+
+	pub fn new() -> Self {
+		Self { functions: Vec::new() }
+	}
+
+	// module declarations are compiled to a function that defines each of them in the environment
+	pub fn compile_module(&mut self, module: Module) -> Result<Proto> {
+		self.functions.push(FunctionState::new(None));
+
+		let (imports, decls): (Vec<_>, Vec<_>) = module.env.into_iter().partition(|(_, stmt)| matches!(stmt.typ, StmtType::Import(_)));
+		for (id, stmt) in imports.into_iter().chain(decls) {
+			let pos = stmt.pos;
+			stmt.accept(self)?;
+			self.emit(Op::DefineGlobal(id.get_id()), pos);
+		}
+
+		self.finish(SourcePos::new(1, 1)).wrap()
+	}
+
+	pub fn compile_block(&mut self, block: Block) -> Result<Proto> {
+		self.functions.push(FunctionState::new(None));
+		self.block(block)?;
+		self.finish(SourcePos::new(1, 1)).wrap()
+	}
+
+	fn current(&mut self) -> &mut FunctionState {
+		self.functions.last_mut().expect("Compiler should always be inside a function")
+	}
+
+	fn chunk(&mut self) -> &mut Chunk {
+		&mut self.current().chunk
+	}
+
+	fn emit(&mut self, op: Op, pos: SourcePos) -> usize {
+		self.chunk().emit(op, pos)
+	}
+
+	fn here(&mut self) -> usize {
+		self.chunk().code.len()
+	}
+
+	fn patch_here(&mut self, at: usize) {
+		let here = self.here();
+		self.chunk().patch(at, here);
+	}
+
+	fn finish(&mut self, pos: SourcePos) -> Proto {
+		self.emit(Op::None, pos);
+		self.emit(Op::Return, pos);
+		self.functions.pop().unwrap().into_proto()
+	}
+
+	fn push_scope(&mut self) {
+		let len = self.current().locals.len();
+		self.current().scopes.push(len);
+	}
+
+	fn pop_scope(&mut self) {
+		let len = self.current().scopes.pop().unwrap();
+		self.current().locals.truncate(len);
+	}
+
+	fn declare(&mut self, id: usize) -> usize {
+		let state = self.current();
+		let slot = state.locals.len();
+		state.locals.push((id, slot));
+		state.max_slots = state.max_slots.max(slot + 1);
+		slot
+	}
+
+	fn upvalue(&mut self, level: usize, id: usize) -> Option<usize> {
+		if level == 0 { return None; }
+
+		let capture = match self.functions[level - 1].local(id) {
+			Some(slot) => Capture::Local(slot),
+			None => Capture::Upvalue(self.upvalue(level - 1, id)?),
+		};
+
+		let upvalues = &mut self.functions[level].upvalues;
+		match upvalues.iter().position(|(upvalue, _)| *upvalue == id) {
+			Some(i) => Some(i),
+			None => {
+				upvalues.push((id, capture));
+				Some(upvalues.len() - 1)
+			}
+		}
+	}
+
+	fn get_var(&mut self, id: usize, pos: SourcePos) {
+		let level = self.functions.len() - 1;
+		let op = if let Some(slot) = self.current().local(id) {
+			Op::GetLocal(slot)
+		} else if let Some(i) = self.upvalue(level, id) {
+			Op::GetUpvalue(i)
+		} else {
+			Op::GetGlobal(id)
+		};
+		self.emit(op, pos);
+	}
+
+	fn set_var(&mut self, id: usize, pos: SourcePos) {
+		let level = self.functions.len() - 1;
+		let op = if let Some(slot) = self.current().local(id) {
+			Op::SetLocal(slot)
+		} else if let Some(i) = self.upvalue(level, id) {
+			Op::SetUpvalue(i)
+		} else {
+			Op::SetGlobal(id)
+		};
+		self.emit(op, pos);
+	}
+
+	fn block(&mut self, block: Block) -> Result<()> {
+		self.push_scope();
+		for stmt in block {
+			stmt.accept(self)?;
+		}
+		self.pop_scope();
+		Ok(())
+	}
+
+	fn function(&mut self, name: Option<String>, params: Vec<Param>, body: Block, pos: SourcePos) -> Result<()> {
+		let mut state = FunctionState::new(name);
+		state.params = Params::new(&params);
+		self.functions.push(state);
+
+		// the arguments fill the first slots, the ones left out get their default before the body runs
+		// destructured ones are bound right after, before the defaults of the parameters that follow
+		let slots = params.iter().map(|param| self.declare(param.name.get_id())).collect::<Vec<_>>();
+		for (i, param) in params.into_iter().enumerate() {
+			if let Some(default) = param.default {
+				let skip = self.emit(Op::JumpIfPassed(i, 0), pos);
+				default.accept(self)?;
+				self.emit(Op::DefineLocal(slots[i]), pos);
+				self.patch_here(skip);
+			}
+			if let Some(pattern) = param.pattern {
+				let pattern_pos = pattern.pos;
+				let shape = self.pattern(pattern);
+				self.emit(Op::GetLocal(slots[i]), pos);
+				self.emit(Op::Destructure(shape), pattern_pos);
+			}
+		}
+		self.block(body)?;
+
+		let proto = self.finish(pos);
+		let chunk = self.chunk();
+		chunk.protos.push(Rc::new(proto));
+		let i = chunk.protos.len() - 1;
+		self.emit(Op::Closure(i), pos);
+		Ok(())
+	}
+
+	// declares the names a pattern binds, the shape keeps the slot of each one
+	fn pattern(&mut self, pattern: Pattern) -> usize {
+		let slots = pattern.bindings().into_iter().map(|name| {
+			let id = name.get_id();
+			(id, self.declare(id))
+		}).collect();
+		self.chunk().matches.push(MatchShape { pattern, slots });
+		self.chunk().matches.len() - 1
+	}
+
+	// compiles the items of a list or the args of a call, keeping where the value of each spread is
+	fn items(&mut self, exprs: Vec<Expression>) -> Result<Vec<Option<SourcePos>>> {
+		let mut spreads = Vec::new();
+		for expr in exprs {
+			match expr.typ {
+				ExprType::Spread(expr) => {
+					spreads.push(Some(expr.pos));
+					expr.accept(self)?;
+				}
+				_ => {
+					spreads.push(None);
+					expr.accept(self)?;
+				}
+			}
+		}
+		Ok(spreads)
+	}
+
+	// compiles a chain of calls, indexes and fields, where an optional link whose head is none
+	// jumps to the end of the chain leaving that none as its value
+	fn chain(&mut self, link: impl FnOnce(&mut Compiler, &mut Vec<usize>) -> Result<()>) -> Result<()> {
+		let mut skips = Vec::new();
+		link(self, &mut skips)?;
+		for skip in skips { self.patch_here(skip); }
+		Ok(())
+	}
+
+	fn link(&mut self, expr: Expression, skips: &mut Vec<usize>) -> Result<()> {
+		match expr.typ {
+			ExprType::Call(data) => self.call_link(data, expr.pos, skips),
+			ExprType::Index(data) => self.index_link(data, expr.pos, skips),
+			ExprType::FieldGet(data) => self.field_link(data, expr.pos, skips),
+			_ => expr.accept(self),
+		}
+	}
+
+	fn call_link(&mut self, data: CallData, pos: SourcePos, skips: &mut Vec<usize>) -> Result<()> {
+		let calee_pos = data.calee.pos;
+		self.link(*data.calee, skips)?;
+		if data.optional { skips.push(self.emit(Op::JumpIfNone(0), pos)); }
+		let arg_pos = data.args.iter().map(|arg| arg.pos).collect();
+		let argc = data.args.len();
+		let spreads = self.items(data.args)?;
+		let mut named = Vec::new();
+		for arg in data.named {
+			arg.expr.accept(self)?;
+			named.push((arg.name, arg.pos));
+		}
+		self.chunk().calls.push(CallSite { argc, calee_pos, arg_pos, spreads, named });
+		let i = self.chunk().calls.len() - 1;
+		self.emit(Op::Call(i), pos);
+		Ok(())
+	}
+
+	fn index_link(&mut self, data: IndexData, pos: SourcePos, skips: &mut Vec<usize>) -> Result<()> {
+		let (head_pos, index_pos) = (data.head.pos, data.index.pos);
+		self.link(*data.head, skips)?;
+		if data.optional { skips.push(self.emit(Op::JumpIfNone(0), pos)); }
+		data.index.accept(self)?;
+		self.emit(Op::Index(head_pos, index_pos), pos);
+		Ok(())
+	}
+
+	fn field_link(&mut self, data: FieldData, pos: SourcePos, skips: &mut Vec<usize>) -> Result<()> {
+		self.link(*data.head, skips)?;
+		if data.optional { skips.push(self.emit(Op::JumpIfNone(0), pos)); }
+		let i = self.chunk().name(&data.field);
+		self.emit(Op::GetField(i), pos);
+		Ok(())
+	}
+
+	fn attributes(attrs: impl IntoIterator<Item = Identifier>) -> Vec<usize> {
+		attrs.into_iter().map(|attr| attr.get_id()).collect()
+	}
+
+}
+
+impl ExprVisitor<()> for Compiler {
+
+	fn literal(&mut self, data: LiteralData, pos: SourcePos) -> Result<()> {
+		match data {
+			LiteralData::None => { self.emit(Op::None, pos); },
+			LiteralData::Bool(true) => { self.emit(Op::True, pos); },
+			LiteralData::Bool(false) => { self.emit(Op::False, pos); },
+			LiteralData::Num(n) => {
+				let i = self.chunk().constant(Number::new(n));
+				self.emit(Op::Constant(i), pos);
+			}
+			LiteralData::Str(s) => {
+				let i = self.chunk().constant(Str::new(s));
+				self.emit(Op::Constant(i), pos);
+			}
+			LiteralData::Template(exprs) => {
+				let positions = exprs.iter().map(|expr| expr.pos).collect();
+				for expr in exprs { expr.accept(self)?; }
+				self.chunk().templates.push(positions);
+				let i = self.chunk().templates.len() - 1;
+				self.emit(Op::Template(i), pos);
+			}
+			LiteralData::List(exprs) => {
+				let len = exprs.len();
+				let spreads = self.items(exprs)?;
+				if spreads.iter().any(Option::is_some) {
+					self.chunk().spreads.push(spreads);
+					let i = self.chunk().spreads.len() - 1;
+					self.emit(Op::SpreadList(i), pos);
+				} else {
+					self.emit(Op::List(len), pos);
+				}
+			}
+			LiteralData::Map(entries) => {
+				let positions = entries.iter().map(|(key, _)| key.pos).collect();
+				for (key, val) in entries {
+					key.accept(self)?;
+					val.accept(self)?;
+				}
+				self.chunk().maps.push(positions);
+				let i = self.chunk().maps.len() - 1;
+				self.emit(Op::Map(i), pos);
+			}
+			LiteralData::Object(map, attrs, spreads) => {
+				let mut exprs = map.into_iter().map(|(key, expr)| (Some(key), expr)).chain(spreads.into_iter().map(|expr| (None, expr))).collect::<Vec<_>>();
+				exprs.sort_by_key(|(_, expr)| (expr.pos.lin, expr.pos.col));
+				let mut members = Vec::new();
+				for (key, expr) in exprs {
+					let expr = match expr.typ {
+						ExprType::Spread(expr) => *expr,
+						_ => expr,
+					};
+					members.push((key, expr.pos));
+					expr.accept(self)?;
+				}
+				self.chunk().objects.push(ObjectShape { members, attributes: Compiler::attributes(attrs) });
+				let i = self.chunk().objects.len() - 1;
+				self.emit(Op::Object(i), pos);
+			}
+			LiteralData::Error(expr) => {
+				expr.accept(self)?;
+				self.emit(Op::Error, pos);
+			}
+		}
+		Ok(())
+	}
+
+	fn binary(&mut self, data: BinaryData, pos: SourcePos) -> Result<()> {
+		let (l_pos, r_pos) = (data.lhs.pos, data.rhs.pos);
+		data.lhs.accept(self)?;
+		data.rhs.accept(self)?;
+		self.emit(Op::Binary(data.op, l_pos, r_pos), pos);
+		Ok(())
+	}
+
+	fn unary(&mut self, data: UnaryData, pos: SourcePos) -> Result<()> {
+		let expr_pos = data.expr.pos;
+		data.expr.accept(self)?;
+		self.emit(match data.op {
+			UnaryOperator::Not => Op::Not,
+			UnaryOperator::Neg => Op::Neg(expr_pos),
+			UnaryOperator::Pos => Op::Pos(expr_pos),
+		}, pos);
+		Ok(())
+	}
+
+	fn logic(&mut self, data: LogicData, pos: SourcePos) -> Result<()> {
+		data.lhs.accept(self)?;
+		let short = match data.op {
+			LogicOperator::And => self.emit(Op::JumpIfFalse(0), pos),
+			LogicOperator::Or => self.emit(Op::JumpIfTrue(0), pos),
+			LogicOperator::Coalesce => {
+				let present = self.emit(Op::JumpIfPresent(0), pos);
+				data.rhs.accept(self)?;
+				self.patch_here(present);
+				return Ok(());
+			}
+		};
+		data.rhs.accept(self)?;
+		self.emit(Op::ToBool, pos);
+		let end = self.emit(Op::Jump(0), pos);
+		self.patch_here(short);
+		self.emit(match data.op {
+			LogicOperator::And => Op::False,
+			_ => Op::True,
+		}, pos);
+		self.patch_here(end);
+		Ok(())
+	}
+
+	fn grouping(&mut self, data: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		data.accept(self)
+	}
+
+	fn variable(&mut self, data: Identifier, pos: SourcePos) -> Result<()> {
+		self.get_var(data.get_id(), pos);
+		Ok(())
+	}
+
+	fn lambda(&mut self, data: LambdaData, pos: SourcePos) -> Result<()> {
+		self.function(None, data.params, data.body, pos)
+	}
+
+	fn call(&mut self, data: CallData, pos: SourcePos) -> Result<()> {
+		self.chain(|compiler, skips| compiler.call_link(data, pos, skips))
+	}
+
+	fn range(&mut self, data: RangeData, pos: SourcePos) -> Result<()> {
+		let mut bound = |expr: Option<Box<Expression>>| -> Result<Option<SourcePos>> {
+			match expr {
+				Some(expr) => { let expr_pos = expr.pos; expr.accept(self)?; Ok(Some(expr_pos)) }
+				None => Ok(None),
+			}
+		};
+		let (start, end, step) = (bound(data.start)?, bound(data.end)?, bound(data.step)?);
+		self.chunk().ranges.push(RangeShape { start, end, step, inclusive: data.inclusive });
+		let i = self.chunk().ranges.len() - 1;
+		self.emit(Op::Range(i), pos);
+		Ok(())
+	}
+
+	fn spread(&mut self, _expr: Box<Expression>, pos: SourcePos) -> Result<()> {
+		ErrorList::comp("Spread syntax can only be used in lists, objects and calls".to_owned(), pos).err()
+	}
+
+	fn index(&mut self, data: IndexData, pos: SourcePos) -> Result<()> {
+		self.chain(|compiler, skips| compiler.index_link(data, pos, skips))
+	}
+
+	fn field(&mut self, data: FieldData, pos: SourcePos) -> Result<()> {
+		self.chain(|compiler, skips| compiler.field_link(data, pos, skips))
+	}
+
+	fn self_ref(&mut self, pos: SourcePos) -> Result<()> {
+		self.emit(Op::GetSelf, pos);
+		Ok(())
+	}
+
+	fn do_expr(&mut self, block: Block, pos: SourcePos) -> Result<()> {
+		self.emit(Op::None, pos);
+		let slot = self.declare(HIDDEN);
+		self.emit(Op::DefineLocal(slot), pos);
+
+		let prev = self.current().eval_target.replace(slot);
+		let res = self.block(block);
+		self.current().eval_target = prev;
+		res?;
+
+		self.emit(Op::GetLocal(slot), pos);
+		Ok(())
+	}
+
+	fn bind_expr(&mut self, data: BindData, pos: SourcePos) -> Result<()> {
+		let method_pos = data.method.pos;
+		data.expr.accept(self)?;
+		data.method.accept(self)?;
+		self.emit(Op::Bind(method_pos), pos);
+		Ok(())
+	}
+
+	fn match_expr(&mut self, data: MatchData, pos: SourcePos) -> Result<()> {
+		data.value.accept(self)?;
+		let subject = self.declare(HIDDEN);
+		self.emit(Op::DefineLocal(subject), pos);
+
+		let mut ends = Vec::new();
+		for arm in data.arms {
+			self.push_scope();
+
+			let pattern_pos = arm.pattern.pos;
+			let i = self.pattern(arm.pattern);
+
+			self.emit(Op::GetLocal(subject), pos);
+			self.emit(Op::Match(i), pattern_pos);
+			let mut fails = vec![self.emit(Op::JumpIfFalse(0), pos)];
+			if let Some(guard) = arm.guard {
+				let guard_pos = guard.pos;
+				guard.accept(self)?;
+				fails.push(self.emit(Op::JumpIfFalse(0), guard_pos));
+			}
+			arm.body.accept(self)?;
+			ends.push(self.emit(Op::Jump(0), pos));
+			for jump in fails { self.patch_here(jump); }
+
+			self.pop_scope();
+		}
+
+		// no arm matched
+		self.emit(Op::None, pos);
+		for jump in ends { self.patch_here(jump); }
+		Ok(())
+	}
+
+}
+
+impl StmtVisitor<()> for Compiler {
+
+	fn expr(&mut self, expr: Box<Expression>, pos: SourcePos) -> Result<()> {
+		expr.accept(self)?;
+		match self.current().eval_target {
+			Some(slot) => self.emit(Op::SetLocal(slot), pos),
+			None => self.emit(Op::Pop, pos),
+		};
+		Ok(())
+	}
+
+	fn declaration(&mut self, data: DeclarationData, pos: SourcePos) -> Result<()> {
+		match data.expr.typ {
+			// these can refer to the name they're being declared to, so it must exist before evaluating them
+			ExprType::Lambda(_) | ExprType::Literal(LiteralData::Object(..)) => {
+				self.emit(Op::None, pos);
+				let slot = self.declare(data.name.get_id());
+				self.emit(Op::DefineLocal(slot), pos);
+				data.expr.accept(self)?;
+				self.emit(Op::SetLocal(slot), pos);
+			}
+			_ => {
+				data.expr.accept(self)?;
+				let slot = self.declare(data.name.get_id());
+				self.emit(Op::DefineLocal(slot), pos);
+			}
+		}
+		Ok(())
+	}
+
+	fn destructure(&mut self, data: DestructureData, _pos: SourcePos) -> Result<()> {
+		data.expr.accept(self)?;
+		let pattern_pos = data.pattern.pos;
+		let shape = self.pattern(data.pattern);
+		self.emit(Op::Destructure(shape), pattern_pos);
+		Ok(())
+	}
+
+	fn func_declaration(&mut self, data: FunctionData, pos: SourcePos) -> Result<()> {
+		self.function(data.name.get_name().wrap(), data.params, data.body, pos)
+	}
+
+	fn attr_declaration(&mut self, data: AttrDeclarationData, pos: SourcePos) -> Result<()> {
+		let mut methods = Vec::new();
+		for method in data.methods {
+			let name = method.name.get_name();
+			self.function(name.clone().wrap(), method.params, method.body, pos)?;
+			methods.push(name);
+		}
+
+		let mut fields = Vec::new();
+		for (key, expr) in data.fields {
+			expr.accept(self)?;
+			fields.push(key);
+		}
+
+		let shape = AttrShape { name: data.name, methods, fields, attributes: Compiler::attributes(data.attributes) };
+		self.chunk().attributes.push(shape);
+		let i = self.chunk().attributes.len() - 1;
+		self.emit(Op::Attribute(i), pos);
+		Ok(())
+	}
+
+	fn import(&mut self, data: ImportData, pos: SourcePos) -> Result<()> {
+		let i = self.chunk().name(&data.path);
+		self.emit(Op::Import(i), pos);
+		Ok(())
+	}
+
+	fn assignment(&mut self, data: AssignData, pos: SourcePos) -> Result<()> {
+		data.expr.accept(self)?;
+		match data.head.typ {
+			ExprType::Variable(name) => self.set_var(name.get_id(), pos),
+			ExprType::Index(IndexData { head, index, .. }) => {
+				let (h_pos, i_pos) = (head.pos, index.pos);
+				head.accept(self)?;
+				index.accept(self)?;
+				self.emit(Op::SetIndex(h_pos, i_pos), pos);
+			}
+			ExprType::FieldGet(FieldData { head, field, .. }) => {
+				let h_pos = head.pos;
+				head.accept(self)?;
+				let i = self.chunk().name(&field);
+				self.emit(Op::SetField(i, h_pos), pos);
+			}
+			_ => unreachable!("the parser only allows variables, indexes and fields as assignment targets"),
+		}
+		Ok(())
+	}
+
+	fn if_stmt(&mut self, data: IfData, pos: SourcePos) -> Result<()> {
+		data.cond.accept(self)?;
+		let else_jump = self.emit(Op::JumpIfFalse(0), pos);
+		self.block(data.then_block)?;
+		let end_jump = self.emit(Op::Jump(0), pos);
+		self.patch_here(else_jump);
+		self.block(data.else_block)?;
+		self.patch_here(end_jump);
+		Ok(())
+	}
+
+	fn loop_stmt(&mut self, block: Block, pos: SourcePos) -> Result<()> {
+		self.emit(Op::EnterLoop, pos);
+		let start = self.here();
+		self.current().loops.push(LoopState { start, breaks: Vec::new() });
+
+		// loops don't give a value to the block they're in
+		let prev = self.current().eval_target.take();
+		let res = self.block(block);
+		self.current().eval_target = prev;
+		res?;
+
+		self.emit(Op::Jump(start), pos);
+		let state = self.current().loops.pop().unwrap();
+		for jump in state.breaks { self.patch_here(jump); }
+		self.emit(Op::ExitLoop, pos);
+		Ok(())
+	}
+
+	fn break_stmt(&mut self, pos: SourcePos) -> Result<()> {
+		if self.current().loops.is_empty() {
+			// breaking out of a function that was declared inside a loop just leaves the function
+			self.emit(Op::None, pos);
+			self.emit(Op::Return, pos);
+			return Ok(());
+		}
+		self.emit(Op::Unwind, pos);
+		let jump = self.emit(Op::Jump(0), pos);
+		self.current().loops.last_mut().unwrap().breaks.push(jump);
+		Ok(())
+	}
+
+	fn continue_stmt(&mut self, pos: SourcePos) -> Result<()> {
+		match self.current().loops.last().map(|state| state.start) {
+			Some(start) => {
+				self.emit(Op::Unwind, pos);
+				self.emit(Op::Jump(start), pos);
+			}
+			None => {
+				self.emit(Op::None, pos);
+				self.emit(Op::Return, pos);
+			}
+		}
+		Ok(())
+	}
+
+	fn return_stmt(&mut self, expr: Box<Expression>, pos: SourcePos) -> Result<()> {
+		expr.accept(self)?;
+		self.emit(Op::Return, pos);
+		Ok(())
+	}
+
+	fn scoped_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(block)
+	}
+
+}
+
@@ -0,0 +1,106 @@
+use std::io::Write;
+
+use ansi_term::Color;
+
+use crate::{interpreter::Interpreter, utils::{result::Result, source_pos::SourcePos}};
+
+use super::{Frontend, Session, Step, Stop, launch};
+
+const PROMPT: &str = "(debug) ";
+
+const HELP: &str = "\
+break, b [file:]line    stop at a line, of the program unless a file is given
+delete [[file:]line]    remove a breakpoint, or all of them
+breakpoints             list the breakpoints
+continue, c             run until the next breakpoint
+step, s                 run until the next line, going into calls
+next, n                 run until the next line, stepping over calls
+out, o                  run until the current function returns
+locals, l               print the locals
+print, p <expr>         evaluate an expression where the program stopped
+backtrace, bt           print the call stack
+list                    print the lines around the current one
+quit, q                 stop the program
+help, h                 print this message";
+
+struct Prompt;
+
+// a breakpoint is a line of the program, or file:line for any other file
+fn location(arg: &str, session: &Session) -> Option<(String, u32)> {
+	match arg.rsplit_once(':') {
+		Some((path, line)) => Some((path.to_owned(), line.parse().ok()?)),
+		None => Some((session.main().to_owned(), arg.parse().ok()?)),
+	}
+}
+
+fn show_line(session: &Session, pos: SourcePos, lin: u32, current: bool) {
+	if let Some(line) = session.source_line(pos, lin) {
+		let marker = if current { ">" } else { " " };
+		println!("{} {:>4} | {}", marker, lin, line.replace('\t', "    "));
+	}
+}
+
+impl Frontend for Prompt {
+	fn stopped(&mut self, session: &mut Session, interpreter: &mut Interpreter, pos: SourcePos, reason: Stop) -> Step {
+		let reason = match reason {
+			Stop::Entry => "entry",
+			Stop::Breakpoint => "breakpoint",
+			Stop::Step => "step",
+		};
+		println!("{} at {}:{}", Color::Yellow.paint(format!("stopped on {}", reason)), session.file_name(pos), pos.lin);
+		show_line(session, pos, pos.lin, true);
+
+		loop {
+			print!("{}", PROMPT);
+			let _ = std::io::stdout().flush();
+
+			let mut line = String::new();
+			match std::io::stdin().read_line(&mut line) {
+				Ok(0) | Err(_) => return Step::Quit,
+				Ok(_) => (),
+			}
+			let line = line.trim();
+			let (cmd, arg) = line.split_once(char::is_whitespace).map_or((line, ""), |(cmd, arg)| (cmd, arg.trim()));
+
+			match cmd {
+				"" => (),
+				"continue" | "c" => return Step::Continue,
+				"step" | "s" => return Step::In,
+				"next" | "n" => return Step::Over,
+				"out" | "o" => return Step::Out,
+				"quit" | "q" => return Step::Quit,
+				"break" | "b" => match location(arg, session) {
+					Some((path, line)) => {
+						session.add_breakpoint(&path, line);
+						println!("breakpoint at {}:{}", path, line);
+					}
+					None => println!("expected a line, or file:line"),
+				},
+				"delete" if arg.is_empty() => session.clear_breakpoints(),
+				"delete" => match location(arg, session) {
+					Some((path, line)) if session.remove_breakpoint(&path, line) => (),
+					_ => println!("no breakpoint at {}", arg),
+				},
+				"breakpoints" => session.breakpoints().iter().for_each(|(path, line)| println!("{}:{}", path, line)),
+				"locals" | "l" => session.locals(interpreter).iter().for_each(|(name, value)| println!("{} = {}", name, value)),
+				"print" | "p" => match session.evaluate(interpreter, arg) {
+					Ok(value) => println!("{}", value),
+					Err(errors) => errors.report_source("<eval>", arg),
+				},
+				"backtrace" | "bt" => for (function, at) in session.frames(interpreter, pos) {
+					println!("at {} [{}:{}:{}]", function, session.file_name(at), at.lin, at.col);
+				},
+				"list" => for lin in pos.lin.saturating_sub(5).max(1)..=pos.lin + 5 {
+					show_line(session, pos, lin, lin == pos.lin);
+				},
+				"help" | "h" => println!("{}", HELP),
+				_ => println!("unknown command '{}', try help", cmd),
+			}
+		}
+	}
+}
+
+// runs a program stopped on its first line, with a prompt on stdin
+pub fn run(path: &str, args: Vec<String>) -> Result<()> {
+	launch(Prompt, Session::new(path), args, true, None)
+}
@@ -0,0 +1,191 @@
+use std::{cell::RefCell, io::{self, BufReader, Stdin, Stdout}, rc::Rc};
+
+use serde_json::{json, Value as Json};
+
+use crate::{interpreter::Interpreter, lsp::{receive, send}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+use super::{Frontend, Session, Step, Stop, canonical, launch};
+
+// there are no threads, but the protocol still wants one
+const THREAD_ID: i64 = 1;
+const LOCALS_REF: i64 = 1;
+const MODULE_REF: i64 = 2;
+
+struct Client {
+	input: BufReader<Stdin>,
+	out: Stdout,
+	seq: i64,
+	disconnected: bool,
+}
+
+impl Client {
+
+	fn new() -> Self {
+		Self { input: BufReader::new(io::stdin()), out: io::stdout(), seq: 1, disconnected: false }
+	}
+
+	fn send(&mut self, mut msg: Json) {
+		msg["seq"] = json!(self.seq);
+		self.seq += 1;
+		// a client that went away can't be told anything anymore
+		let _ = send(&mut self.out, &msg);
+	}
+
+	fn respond(&mut self, request: &Json, body: std::result::Result<Json, String>) {
+		let mut msg = json!({ "type": "response", "request_seq": request["seq"], "command": request["command"] });
+		match body {
+			Ok(body) => { msg["success"] = json!(true); msg["body"] = body; }
+			Err(message) => { msg["success"] = json!(false); msg["message"] = json!(message); }
+		}
+		self.send(msg);
+	}
+
+	fn event(&mut self, event: &str, body: Json) {
+		self.send(json!({ "type": "event", "event": event, "body": body }));
+	}
+
+	fn receive(&mut self) -> Option<Json> {
+		receive(&mut self.input).ok().flatten()
+	}
+
+}
+
+fn errors_text(errors: &ErrorList) -> String {
+	errors.entries().map(|(msg, _)| msg).collect::<Vec<_>>().join("\n")
+}
+
+fn variables(values: Vec<(String, String)>) -> Json {
+	values.into_iter().map(|(name, value)| json!({ "name": name, "value": value, "variablesReference": 0 })).collect()
+}
+
+fn set_breakpoints(session: &mut Session, args: &Json) -> Json {
+	let path = args["source"]["path"].as_str().unwrap_or_default();
+	let lines = args["breakpoints"].as_array().into_iter().flatten().filter_map(|bp| bp["line"].as_u64()).map(|line| line as u32).collect::<Vec<_>>();
+	session.set_breakpoints(path, lines.clone());
+	json!({ "breakpoints": lines.iter().map(|line| json!({ "verified": true, "line": line })).collect::<Vec<_>>() })
+}
+
+struct Adapter {
+	client: Rc<RefCell<Client>>,
+}
+
+impl Frontend for Adapter {
+	fn stopped(&mut self, session: &mut Session, interpreter: &mut Interpreter, pos: SourcePos, reason: Stop) -> Step {
+		let reason = match reason {
+			Stop::Entry => "entry",
+			Stop::Breakpoint => "breakpoint",
+			Stop::Step => "step",
+		};
+		// the client is only borrowed between requests, evaluating code can print to it too
+		self.client.borrow_mut().event("stopped", json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }));
+
+		loop {
+			let Some(request) = self.client.borrow_mut().receive() else { return Step::Quit };
+			let args = &request["arguments"];
+			let (body, step) = match request["command"].as_str().unwrap_or_default() {
+				"continue" => (Ok(json!({ "allThreadsContinued": true })), Some(Step::Continue)),
+				"next" => (Ok(Json::Null), Some(Step::Over)),
+				"stepIn" => (Ok(Json::Null), Some(Step::In)),
+				"stepOut" => (Ok(Json::Null), Some(Step::Out)),
+				"disconnect" | "terminate" => {
+					self.client.borrow_mut().disconnected = true;
+					(Ok(Json::Null), Some(Step::Quit))
+				}
+				"threads" => (Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })), None),
+				"setBreakpoints" => (Ok(set_breakpoints(session, args)), None),
+				"stackTrace" => {
+					let frames = session.frames(interpreter, pos).into_iter().enumerate().map(|(i, (name, at))| {
+						let path = session.file_name(at);
+						json!({
+							"id": i,
+							"name": name,
+							"source": { "name": path, "path": canonical(&path) },
+							"line": at.lin,
+							"column": at.col,
+						})
+					}).collect::<Vec<_>>();
+					(Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() })), None)
+				}
+				// only the innermost frame is still around to look into
+				"scopes" if args["frameId"].as_i64().unwrap_or_default() == 0 => (Ok(json!({ "scopes": [
+					{ "name": "Locals", "variablesReference": LOCALS_REF, "expensive": false },
+					{ "name": "Module", "variablesReference": MODULE_REF, "expensive": false },
+				] })), None),
+				"scopes" => (Ok(json!({ "scopes": [] })), None),
+				"variables" => {
+					let values = match args["variablesReference"].as_i64() {
+						Some(LOCALS_REF) => session.locals(interpreter),
+						Some(MODULE_REF) => session.module_values(interpreter),
+						_ => Vec::new(),
+					};
+					(Ok(json!({ "variables": variables(values) })), None)
+				}
+				"evaluate" => {
+					let body = match session.evaluate(interpreter, args["expression"].as_str().unwrap_or_default()) {
+						Ok(result) => Ok(json!({ "result": result, "variablesReference": 0 })),
+						Err(errors) => Err(errors_text(&errors)),
+					};
+					(body, None)
+				}
+				command => (Err(format!("Unsupported request '{}'", command)), None),
+			};
+			self.client.borrow_mut().respond(&request, body);
+			if let Some(step) = step { return step; }
+		}
+	}
+}
+
+// speaks the debug adapter protocol over stdin and stdout, the program to run comes with the launch request
+pub fn run() -> Result<()> {
+	let client = Rc::new(RefCell::new(Client::new()));
+	let mut session = Session::default();
+	let mut launched = None;
+
+	loop {
+		let Some(request) = client.borrow_mut().receive() else { break };
+		let args = &request["arguments"];
+		let body = match request["command"].as_str().unwrap_or_default() {
+			"initialize" => Ok(json!({
+				"supportsConfigurationDoneRequest": true,
+				"supportsEvaluateForHovers": true,
+				"supportsTerminateRequest": true,
+			})),
+			"launch" => match args["program"].as_str() {
+				Some(program) => {
+					let argv = args["args"].as_array().into_iter().flatten().filter_map(|arg| arg.as_str().map(str::to_owned)).collect::<Vec<_>>();
+					launched = Some((program.to_owned(), argv, args["stopOnEntry"].as_bool().unwrap_or(false)));
+					Ok(Json::Null)
+				}
+				None => Err("Expected the path of the program to launch".to_owned()),
+			},
+			"setBreakpoints" => Ok(set_breakpoints(&mut session, args)),
+			"threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+			"configurationDone" => Ok(Json::Null),
+			"disconnect" | "terminate" => Ok(Json::Null),
+			command => Err(format!("Unsupported request '{}'", command)),
+		};
+
+		let command = request["command"].as_str().unwrap_or_default().to_owned();
+		client.borrow_mut().respond(&request, body);
+		// answering initialize first tells the client it can start configuring
+		if command == "initialize" { client.borrow_mut().event("initialized", json!({})); }
+		if command == "disconnect" { break; }
+		if command != "configurationDone" { continue; }
+
+		let Some((program, argv, stop_on_entry)) = launched.take() else { continue };
+		let out = client.clone();
+		let output = Box::new(move |text: &str| out.borrow_mut().event("output", json!({ "category": "stdout", "output": text })));
+		let session = std::mem::take(&mut session);
+		let res = launch(Adapter { client: client.clone() }, Session { main: program, ..session }, argv, stop_on_entry, Some(output));
+
+		let mut client = client.borrow_mut();
+		if let Err(ref errors) = res {
+			client.event("output", json!({ "category": "stderr", "output": format!("{}\n", errors_text(errors)) }));
+		}
+		client.event("exited", json!({ "exitCode": if res.is_ok() { 0 } else { 1 } }));
+		client.event("terminated", json!({}));
+		if client.disconnected { break; }
+	}
+
+	().wrap()
+}
@@ -0,0 +1,257 @@
+pub mod cli;
+pub mod dap;
+
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}};
+
+use crate::{ast::{Block, module::Module, statement::Statement}, interpreter::{Hook, Interpreter, Message, Output, globals::assert::render}, lexer::{Lexer, LexerResult}, parser::Parser, semantics::{checker::Checker, optimizer::Optimizer, resolver::Resolver}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, sources::{add_source, get_source}}};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+	Continue,
+	In,
+	Over,
+	Out,
+	Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop {
+	Entry,
+	Breakpoint,
+	Step,
+}
+
+// the part of the debugger that talks to the user, asked what to do every time the program stops
+pub trait Frontend {
+	fn stopped(&mut self, session: &mut Session, interpreter: &mut Interpreter, pos: SourcePos, reason: Stop) -> Step;
+}
+
+fn canonical(path: &str) -> PathBuf {
+	std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+pub struct Session {
+	main: String,
+	breakpoints: HashMap<PathBuf, HashSet<u32>>,
+	// the canonical path of every file id that was seen, so positions can be matched against breakpoints
+	files: HashMap<usize, PathBuf>,
+	// the module level names of the program, by id
+	module: Vec<(String, usize)>,
+	// the code evaluated while stopped is registered under the same entry every time
+	source: usize,
+}
+
+impl Default for Session {
+	fn default() -> Self {
+		Self::new("")
+	}
+}
+
+impl Session {
+
+	pub fn new(main: &str) -> Self {
+		Self {
+			main: main.to_owned(),
+			breakpoints: HashMap::new(),
+			files: HashMap::new(),
+			module: Vec::new(),
+			source: add_source("<eval>", ""),
+		}
+	}
+
+	pub fn main(&self) -> &str {
+		&self.main
+	}
+
+	pub fn add_breakpoint(&mut self, path: &str, line: u32) -> bool {
+		self.breakpoints.entry(canonical(path)).or_default().insert(line)
+	}
+
+	pub fn remove_breakpoint(&mut self, path: &str, line: u32) -> bool {
+		self.breakpoints.get_mut(&canonical(path)).is_some_and(|lines| lines.remove(&line))
+	}
+
+	pub fn set_breakpoints(&mut self, path: &str, lines: Vec<u32>) {
+		self.breakpoints.insert(canonical(path), lines.into_iter().collect());
+	}
+
+	pub fn clear_breakpoints(&mut self) {
+		self.breakpoints.clear();
+	}
+
+	pub fn breakpoints(&self) -> Vec<(String, u32)> {
+		let mut list = self.breakpoints.iter().flat_map(|(path, lines)| lines.iter().map(move |line| (path.to_string_lossy().to_string(), *line))).collect::<Vec<_>>();
+		list.sort();
+		list
+	}
+
+	fn has_breakpoint(&mut self, pos: SourcePos) -> bool {
+		if self.breakpoints.is_empty() { return false; }
+		let main = &self.main;
+		let path = self.files.entry(pos.file).or_insert_with(|| canonical(&get_source(pos.file).map_or_else(|| main.clone(), |src| src.name.clone())));
+		self.breakpoints.get(path).is_some_and(|lines| lines.contains(&pos.lin))
+	}
+
+	// positions that aren't tied to a file, like the call to main, belong to the program itself
+	pub fn file_name(&self, pos: SourcePos) -> String {
+		get_source(pos.file).map_or_else(|| self.main.clone(), |src| src.name.clone())
+	}
+
+	pub fn source_line(&self, pos: SourcePos, lin: u32) -> Option<String> {
+		let text = match get_source(pos.file) {
+			Some(src) => src.text.clone(),
+			None => std::fs::read_to_string(&self.main).ok()?,
+		};
+		text.lines().nth((lin as usize).checked_sub(1)?).map(str::to_owned)
+	}
+
+	// the innermost frame comes first, each one with the position it is stopped at
+	pub fn frames(&self, interpreter: &Interpreter, pos: SourcePos) -> Vec<(String, SourcePos)> {
+		let mut frames = Vec::new();
+		let mut at = pos;
+		for (function, call_pos) in interpreter.call_stack.iter().rev() {
+			frames.push((function.clone(), at));
+			at = *call_pos;
+		}
+		frames.push(("<top level>".to_owned(), at));
+		frames
+	}
+
+	pub fn locals(&self, interpreter: &mut Interpreter) -> Vec<(String, String)> {
+		let mut locals = interpreter.env.locals();
+		if let Some(receiver) = interpreter.env.receiver() { locals.insert(0, ("self".to_owned(), receiver)); }
+		locals.into_iter().map(|(name, value)| {
+			let value = render(value.as_ref(), interpreter, SourcePos::new(0, 0)).unwrap_or_else(|_| "?".to_owned());
+			(name, value)
+		}).collect()
+	}
+
+	pub fn module_values(&self, interpreter: &mut Interpreter) -> Vec<(String, String)> {
+		let mut values = self.module.iter().filter(|(_, id)| interpreter.env.has_id(*id)).cloned().collect::<Vec<_>>();
+		values.sort();
+		values.into_iter().map(|(name, id)| {
+			let value = interpreter.env.get_id(id);
+			let value = render(value.as_ref(), interpreter, SourcePos::new(0, 0)).unwrap_or_else(|_| "?".to_owned());
+			(name, value)
+		}).collect()
+	}
+
+	// runs some code as if it was written where the program stopped, it can read and assign the locals there
+	pub fn evaluate(&self, interpreter: &mut Interpreter, text: &str) -> Result<String> {
+		let LexerResult { tokens, mut errors, .. } = Lexer::reuse_file(self.source, text).scan_tokens();
+		let (module, block) = Parser::new(tokens).script()?;
+
+		// stopped inside an imported module, the names of the program can't be seen
+		let names = self.module.iter().filter(|(_, id)| interpreter.env.has_id(*id)).cloned().collect();
+		let mut resolver = Resolver::frame(names, interpreter.env.local_names(), interpreter.modules.next_id());
+		errors.try_append(resolver.resolve_repl(&module, &block));
+		errors.if_empty(())?;
+		interpreter.modules.reserve(resolver.max_id());
+
+		match interpreter.interpret_repl(module, block)? {
+			Message::Eval(value) => render(value.as_ref(), interpreter, SourcePos::new(0, 0)),
+			_ => Ok("none".to_owned()),
+		}
+	}
+
+}
+
+struct Debugger<F> {
+	frontend: F,
+	session: Session,
+	step: Step,
+	// the call depth at the last stop, stepping over or out is relative to it
+	depth: usize,
+	last: Option<(usize, u32, usize)>,
+}
+
+impl<F: Frontend> Hook for Debugger<F> {
+	fn statement(&mut self, interpreter: &mut Interpreter, stmt: &Statement) -> bool {
+		let pos = stmt.pos;
+		let depth = interpreter.call_stack.len();
+
+		// a line holding many statements is only stopped at once
+		if self.last == Some((pos.file, pos.lin, depth)) { return true; }
+		self.last = Some((pos.file, pos.lin, depth));
+
+		let reason = if self.session.has_breakpoint(pos) {
+			Stop::Breakpoint
+		} else {
+			let stop = match self.step {
+				Step::In => true,
+				Step::Over => depth <= self.depth,
+				Step::Out => depth < self.depth,
+				Step::Continue | Step::Quit => false,
+			};
+			if !stop { return true; }
+			// before the first stop, stepping can only mean the program was asked to stop on entry
+			if self.depth == usize::MAX { Stop::Entry } else { Stop::Step }
+		};
+
+		self.step = self.frontend.stopped(&mut self.session, interpreter, pos, reason);
+		self.depth = depth;
+		self.step != Step::Quit
+	}
+}
+
+// a checked program, ready to run
+struct Program {
+	module: Module,
+	block: Option<Block>,
+	root: PathBuf,
+	max_id: usize,
+}
+
+fn load(path: &str) -> Result<Program> {
+	let lexer = Lexer::from_file(path).map_err(|err| ErrorList::sys(err.to_string()))?;
+	let LexerResult { tokens, directives, mut errors, .. } = lexer.scan_tokens();
+
+	let mut resolver = Resolver::new();
+	let mut checker = Checker::new();
+	let (mut module, block) = if directives.contains("script") {
+		let (module, block) = Parser::new(tokens).script()?;
+		errors.try_append(resolver.resolve(&module));
+		errors.try_append(resolver.resolve_block(&block));
+		errors.try_append(checker.check(&module));
+		errors.try_append(checker.check_block(&block));
+		(module, Some(block))
+	} else {
+		let module = Parser::new(tokens).module()?;
+		errors.try_append(resolver.resolve(&module));
+		errors.try_append(checker.check(&module));
+		(module, None)
+	};
+	errors.if_empty(())?;
+
+	Optimizer.optimize(&mut module)?;
+	let block = block.map(|block| Optimizer.optimize_block(block)).transpose()?;
+
+	let mut root = Path::new(path).to_path_buf();
+	root.pop();
+
+	Ok(Program { module, block, root, max_id: resolver.max_id() })
+}
+
+// runs a program on the tree walking interpreter, stopping wherever the frontend asks to
+pub fn launch<F: Frontend + 'static>(frontend: F, mut session: Session, args: Vec<String>, stop_on_entry: bool, output: Option<Output>) -> Result<()> {
+	let program = load(&session.main.clone())?;
+	session.module = program.module.env.keys().map(|id| (id.get_name(), id.get_id())).collect();
+
+	let mut interpreter = Interpreter::new(program.root);
+	interpreter.modules.reserve(program.max_id);
+	interpreter.modules.enter(Path::new(&session.main));
+	interpreter.env.track_names();
+	interpreter.output = output;
+	interpreter.hook = Some(Box::new(Debugger {
+		frontend,
+		session,
+		step: if stop_on_entry { Step::In } else { Step::Continue },
+		depth: usize::MAX,
+		last: None,
+	}));
+
+	match program.block {
+		Some(block) => interpreter.interpret_script(program.module, block),
+		None => interpreter.interpret_and_run(program.module, args),
+	}
+}
@@ -0,0 +1,334 @@
+use std::{collections::{HashMap, HashSet}, fmt::Debug, path::PathBuf, rc::Rc};
+
+use crate::{interpreter::{Interpreter, Message, value::{Value, ValueType, primitives::{bool::Bool, callable::{Callable, nativefn::NativeFn}, list::List, none::ValNone, number::Number, object::Object, string::Str}}}, lexer::{Lexer, LexerResult}, parser::Parser, semantics::{checker::Checker, optimizer::Optimizer, resolver::Resolver}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, sources::add_source, wrap::Wrap}};
+
+// values handed to and taken from mars don't come from any code, so they have no position
+fn outside() -> SourcePos {
+	SourcePos::new(0, 0)
+}
+
+fn expected<T>(typ: &str, value: &dyn Value, pos: SourcePos) -> Result<T> {
+	ErrorList::run(format!("Expected {}, found {}", typ, value.get_type()), pos).err()
+}
+
+pub trait IntoValue {
+	fn into_value(self) -> Box<dyn Value>;
+}
+
+pub trait FromValue: Sized {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self>;
+}
+
+impl IntoValue for Box<dyn Value> {
+	fn into_value(self) -> Box<dyn Value> { self }
+}
+
+impl FromValue for Box<dyn Value> {
+	fn from_value(value: Box<dyn Value>, _pos: SourcePos) -> Result<Self> { Ok(value) }
+}
+
+impl IntoValue for () {
+	fn into_value(self) -> Box<dyn Value> { ValNone::new() }
+}
+
+impl FromValue for () {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		match value.get_type() {
+			ValueType::None => Ok(()),
+			_ => expected("none", value.as_ref(), pos),
+		}
+	}
+}
+
+impl IntoValue for bool {
+	fn into_value(self) -> Box<dyn Value> { Bool::new(self) }
+}
+
+impl FromValue for bool {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		match value.get_type() {
+			ValueType::Bool => Ok(value.is_truthy()),
+			_ => expected("a bool", value.as_ref(), pos),
+		}
+	}
+}
+
+impl IntoValue for f64 {
+	fn into_value(self) -> Box<dyn Value> { Number::new(self) }
+}
+
+impl FromValue for f64 {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		match value.get_type() {
+			ValueType::Num => value.to_num(pos),
+			_ => expected("a number", value.as_ref(), pos),
+		}
+	}
+}
+
+// mars only has floats, so integers must not have a fractional part and fit in the type
+// the maximum of the 64 bit types rounds up to the next power of two as a float, so the check is against that bound for every type
+macro_rules! integer {
+	($($typ:ty),*) => {$(
+		impl IntoValue for $typ {
+			fn into_value(self) -> Box<dyn Value> { Number::new(self as f64) }
+		}
+
+		impl FromValue for $typ {
+			fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+				let num = f64::from_value(value, pos)?;
+				if num.fract() != 0.0 || num < <$typ>::MIN as f64 || num >= <$typ>::MAX as f64 + 1.0 {
+					return ErrorList::run(format!("Expected {} to be an integer of type {}", num, stringify!($typ)), pos).err();
+				}
+				Ok(num as $typ)
+			}
+		}
+	)*};
+}
+
+integer!(i32, i64, u32, u64, usize);
+
+impl IntoValue for String {
+	fn into_value(self) -> Box<dyn Value> { Str::new(self) }
+}
+
+impl IntoValue for &str {
+	fn into_value(self) -> Box<dyn Value> { Str::new(self.to_owned()) }
+}
+
+impl FromValue for String {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		match value.get_type() {
+			ValueType::Str => value.to_str(pos),
+			_ => expected("a string", value.as_ref(), pos),
+		}
+	}
+}
+
+impl <T: IntoValue> IntoValue for Option<T> {
+	fn into_value(self) -> Box<dyn Value> {
+		match self {
+			Some(value) => value.into_value(),
+			None => ValNone::new(),
+		}
+	}
+}
+
+impl <T: FromValue> FromValue for Option<T> {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		match value.get_type() {
+			ValueType::None => Ok(None),
+			_ => T::from_value(value, pos).map(Some),
+		}
+	}
+}
+
+impl <T: IntoValue> IntoValue for Vec<T> {
+	fn into_value(self) -> Box<dyn Value> {
+		List::new(self.into_iter().map(IntoValue::into_value).collect())
+	}
+}
+
+impl <T: FromValue> FromValue for Vec<T> {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		if value.get_type() != ValueType::Vector { return expected("a vector", value.as_ref(), pos); }
+		let items = value.to_list(pos)?.borrow().clone();
+		items.into_iter().map(|item| T::from_value(item, pos)).collect()
+	}
+}
+
+// string keyed maps become objects, which is how mars code writes records
+impl <T: IntoValue> IntoValue for HashMap<String, T> {
+	fn into_value(self) -> Box<dyn Value> {
+		Object::new(self.into_iter().map(|(key, value)| (key, value.into_value().wrap())).collect(), HashSet::new())
+	}
+}
+
+impl <T: FromValue> FromValue for HashMap<String, T> {
+	fn from_value(value: Box<dyn Value>, pos: SourcePos) -> Result<Self> {
+		if value.get_type() != ValueType::Object { return expected("an object", value.as_ref(), pos); }
+		let fields = value.to_obj(pos)?;
+		fields.into_iter().map(|(key, field)| {
+			let field = field.borrow().clone();
+			Ok((key, T::from_value(field, pos)?))
+		}).collect()
+	}
+}
+
+// what a native function can give back, a failed one reports its message as a runtime error
+pub trait IntoReturn {
+	fn into_return(self, pos: SourcePos) -> Result<Box<dyn Value>>;
+}
+
+impl <T: IntoValue> IntoReturn for T {
+	fn into_return(self, _pos: SourcePos) -> Result<Box<dyn Value>> { Ok(self.into_value()) }
+}
+
+impl <T: IntoValue> IntoReturn for std::result::Result<T, String> {
+	fn into_return(self, pos: SourcePos) -> Result<Box<dyn Value>> {
+		self.map(IntoValue::into_value).map_err(|msg| ErrorList::run(msg, pos))
+	}
+}
+
+// rust closures that mars code can call, their arguments are converted with FromValue
+pub trait NativeFunction<Args>: 'static {
+	fn arity(&self) -> usize;
+	fn invoke(&self, args: Vec<(Box<dyn Value>, SourcePos)>, pos: SourcePos) -> Result<Box<dyn Value>>;
+}
+
+macro_rules! native_function {
+	($($arg:ident),*) => {
+		impl <Fun, Ret, $($arg),*> NativeFunction<($($arg,)*)> for Fun
+		where Fun: Fn($($arg),*) -> Ret + 'static, Ret: IntoReturn, $($arg: FromValue),* {
+			fn arity(&self) -> usize {
+				<[&str]>::len(&[$(stringify!($arg)),*])
+			}
+
+			#[allow(non_snake_case, unused_variables, unused_mut)]
+			fn invoke(&self, args: Vec<(Box<dyn Value>, SourcePos)>, pos: SourcePos) -> Result<Box<dyn Value>> {
+				let mut args = args.into_iter();
+				$(
+					let (value, arg_pos) = args.next().unwrap();
+					let $arg = $arg::from_value(value, arg_pos)?;
+				)*
+				self($($arg),*).into_return(pos)
+			}
+		}
+	};
+}
+
+native_function!();
+native_function!(A);
+native_function!(A, B);
+native_function!(A, B, C);
+native_function!(A, B, C, D);
+native_function!(A, B, C, D, E);
+native_function!(A, B, C, D, E, F);
+
+type Invoke = dyn Fn(Vec<(Box<dyn Value>, SourcePos)>, SourcePos) -> Result<Box<dyn Value>>;
+
+#[derive(Clone)]
+struct Native {
+	name: String,
+	arity: usize,
+	function: Rc<Invoke>,
+}
+
+impl Debug for Native {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "<native function {}>", self.name)
+	}
+}
+
+impl Callable for Native {
+	fn display(&self) -> String { format!("<native function {}>", self.name) }
+
+	fn arity(&self) -> usize { self.arity }
+
+	fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		(self.function)(args, pos)
+	}
+}
+
+// an interpreter that keeps its names between evaluations, for running mars inside rust programs
+pub struct Engine {
+	interpreter: Interpreter,
+	resolver: Resolver,
+	checker: Checker,
+	// every evaluated piece of code is registered under the same entry
+	source: usize,
+}
+
+impl Default for Engine {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Engine {
+
+	pub fn new() -> Self {
+		Self::with_root(std::env::current_dir().unwrap_or_default())
+	}
+
+	// imports in evaluated code are looked up from the root
+	pub fn with_root(root: PathBuf) -> Self {
+		Self { interpreter: Interpreter::new(root), resolver: Resolver::repl(), checker: Checker::new(), source: add_source("<eval>", "") }
+	}
+
+	// runs some code, giving back the value of its last expression
+	pub fn eval<T: FromValue>(&mut self, source: &str) -> Result<T> {
+		let LexerResult { tokens, mut errors, .. } = Lexer::reuse_file(self.source, source).scan_tokens();
+		let (mut module, block) = Parser::new(tokens).script()?;
+
+		// code that fails to resolve or check shouldn't leave half-declared names behind
+		let (resolver, checker) = (self.resolver.clone(), self.checker.clone());
+		self.resolver.reserve(self.interpreter.modules.next_id());
+		errors.try_append(self.resolver.resolve_repl(&module, &block));
+		errors.try_append(self.checker.check(&module));
+		errors.try_append(self.checker.check_block(&block));
+		if !errors.is_empty() {
+			self.resolver = resolver;
+			self.checker = checker;
+			return errors.err();
+		}
+		self.interpreter.modules.reserve(self.resolver.max_id());
+
+		Optimizer.optimize(&mut module)?;
+		let block = Optimizer.optimize_block(block)?;
+
+		let value = match self.interpreter.interpret_repl(module, block)? {
+			Message::Eval(value) => value,
+			_ => ValNone::new(),
+		};
+		T::from_value(value, outside())
+	}
+
+	pub fn get<T: FromValue>(&self, name: &str) -> Result<T> {
+		match self.resolver.lookup(name).filter(|id| self.interpreter.env.has_id(*id)) {
+			Some(id) => T::from_value(self.interpreter.env.get_id(id), outside()),
+			None => ErrorList::mod_run(format!("Use of undefined variable '{}'", name)).err(),
+		}
+	}
+
+	// defines a name for the code evaluated from now on, replacing any other with the same name
+	pub fn set(&mut self, name: &str, value: impl IntoValue) -> Result<()> {
+		let id = self.resolver.declare(name)?;
+		self.interpreter.modules.reserve(self.resolver.max_id());
+		self.interpreter.env.define_id(id, value.into_value());
+		Ok(())
+	}
+
+	pub fn register<Args>(&mut self, name: &str, function: impl NativeFunction<Args>) -> Result<()> {
+		let native = Native {
+			name: name.to_owned(),
+			arity: function.arity(),
+			function: Rc::new(move |args, pos| function.invoke(args, pos)),
+		};
+		self.set(name, NativeFn::create(native.wrap()))
+	}
+
+	pub fn call<T: FromValue>(&mut self, name: &str, args: Vec<Box<dyn Value>>) -> Result<T> {
+		let function = self.get::<Box<dyn Value>>(name)?;
+		self.apply(function.as_ref(), args)
+	}
+
+	// calls a function value, like one that mars code handed over as a callback
+	pub fn apply<T: FromValue>(&mut self, function: &dyn Value, args: Vec<Box<dyn Value>>) -> Result<T> {
+		let pos = outside();
+		let function = function.to_callable(pos)?;
+		function.check_arity(args.len(), pos)?;
+		let value = function.call(pos, &mut self.interpreter, args.into_iter().map(|arg| (arg, pos)).collect())?;
+		T::from_value(value, pos)
+	}
+
+	pub fn to_string(&mut self, value: &dyn Value) -> Result<String> {
+		value.to_string(&mut self.interpreter, outside())
+	}
+
+	// takes what writeline and write print, which go to stdout otherwise
+	pub fn set_output(&mut self, output: impl FnMut(&str) + 'static) {
+		self.interpreter.output = Some(Box::new(output));
+	}
+
+}
@@ -0,0 +1,681 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+
+use crate::{ast::{Block, expression::{*, ExprType::*}, identifier::Identifier, pattern::{Pattern, PatternType}, statement::{*, StmtType::*}, types::Type}, lexer::{Comment, Lexer, LexerResult, token::{Symbol::*, TokenType::Symbol}}, parser::Parser, utils::{result::Result, source_pos::SourcePos}};
+
+// prints a file back with canonical layout, keeping its comments and directives
+pub fn format(name: &str, text: &str) -> Result<String> {
+	let LexerResult { tokens, directives, comments, errors } = Lexer::from_text(name, text).scan_tokens();
+	errors.if_empty(())?;
+
+	let brackets = tokens.iter().filter_map(|token| match token.typ {
+		Symbol(OpenBracket) => Some((token.pos, '{')),
+		Symbol(OpenSqr) => Some((token.pos, '[')),
+		Symbol(OpenPar) => Some((token.pos, '(')),
+		Symbol(CloseBracket) | Symbol(CloseSqr) | Symbol(ClosePar) => Some((token.pos, ')')),
+		_ => None,
+	}).collect();
+
+	let (module, block) = if directives.contains("script") {
+		Parser::new(tokens).script()?
+	} else {
+		(Parser::new(tokens).module()?, Block::new())
+	};
+
+	// declarations and statements are kept apart by the parser, so they are put back in source order
+	let mut items = module.env.values().chain(block.iter()).map(|stmt| (start(stmt), Item::Stmt(stmt))).collect::<Vec<_>>();
+	items.sort_by_key(|(pos, _)| key(*pos));
+
+	let mut formatter = Formatter {
+		out: String::new(),
+		indent: 0,
+		fresh: true,
+		lines: text.split('\n').map(|line| line.chars().collect()).collect(),
+		comments: comments.into(),
+		brackets,
+	};
+	formatter.body(items, Some(SourcePos::new(u32::MAX, u32::MAX)), "");
+
+	let mut out = formatter.out.trim_end().to_owned();
+	if !out.is_empty() { out.push('\n'); }
+	Ok(out)
+}
+
+fn key(pos: SourcePos) -> (u32, u32) {
+	(pos.lin, pos.col)
+}
+
+// assignments are positioned at their operator, but they are written starting at their target
+fn start(stmt: &Statement) -> SourcePos {
+	match &stmt.typ {
+		Assignment(data) => data.head.pos,
+		_ => stmt.pos,
+	}
+}
+
+fn is_none(expr: &Expression) -> bool {
+	matches!(expr.typ, Literal(LiteralData::None))
+}
+
+// the parts of a desugared for loop: the declaration of its variable or pattern, the iterated value and the body
+fn for_parts(block: &Block) -> Option<(&StmtType, &Expression, &Block)> {
+	if let [Statement { typ: Declaration(it), .. }, Statement { typ: Loop(body), .. }] = block.as_slice() {
+		if let (Call(CallData { args, .. }), [_, _, Statement { typ: target, .. }, Statement { typ: Scoped(inner), .. }]) = (&it.expr.typ, body.as_slice()) {
+			if it.name.name == "$it" && args.len() == 1 && matches!(target, Declaration(_) | Destructure(_)) {
+				return Some((target, &args[0], inner));
+			}
+		}
+	}
+	None
+}
+
+// the expression a desugared `?` or `!` operator was applied to, and the operator
+fn handled_parts(block: &Block) -> Option<(&Expression, &str)> {
+	if let [Statement { typ: Declaration(res), .. }, Statement { typ: If(data), .. }, _] = block.as_slice() {
+		if res.name.name == "$res" {
+			let op = match data.then_block.first() {
+				Some(Statement { typ: Return(_), .. }) => "?",
+				_ => "!",
+			};
+			return Some((&res.expr, op));
+		}
+	}
+	None
+}
+
+fn binary_op(op: &BinaryOperator) -> &'static str {
+	match op {
+		BinaryOperator::Add => "+",
+		BinaryOperator::Sub => "-",
+		BinaryOperator::Mul => "*",
+		BinaryOperator::Div => "/",
+		BinaryOperator::Rem => "mod",
+		BinaryOperator::Equ => "==",
+		BinaryOperator::Neq => "!=",
+		BinaryOperator::Lst => "<",
+		BinaryOperator::Lse => "<=",
+		BinaryOperator::Grt => ">",
+		BinaryOperator::Gre => ">=",
+		BinaryOperator::Typ => "is",
+	}
+}
+
+enum Item<'a> {
+	Stmt(&'a Statement),
+	Method(&'a FunctionData),
+	Static(&'a str, &'a Expression),
+	Field(&'a str, &'a Expression),
+	Is(&'a Identifier),
+	Element(&'a Expression),
+	Named(&'a NamedArg),
+	Entry(&'a Expression, &'a Expression),
+	Arm(&'a MatchArm),
+}
+
+struct Formatter {
+	out: String,
+	indent: usize,
+	// nothing was written yet in the current block, so no blank line is needed
+	fresh: bool,
+	lines: Vec<Vec<char>>,
+	comments: VecDeque<Comment>,
+	// every bracket in the file, closing ones are all stored as ')'
+	brackets: Vec<(SourcePos, char)>,
+}
+
+impl Formatter {
+
+	fn write(&mut self, text: &str) {
+		if self.out.is_empty() || self.out.ends_with('\n') {
+			for _ in 0..self.indent { self.out.push('\t'); }
+		}
+		self.out.push_str(text);
+	}
+
+	fn newline(&mut self) {
+		self.out.push('\n');
+	}
+
+	fn line(&self, lin: u32) -> &[char] {
+		self.lines.get(lin as usize - 1).map(|line| line.as_slice()).unwrap_or(&[])
+	}
+
+	fn blank_before(&self, lin: u32) -> bool {
+		lin > 1 && self.line(lin - 1).iter().all(|c| c.is_whitespace())
+	}
+
+	// the source text in the same line, before the given position
+	fn before(&self, pos: SourcePos) -> String {
+		self.line(pos.lin).iter().take(pos.col as usize - 1).collect::<String>().trim().to_owned()
+	}
+
+	fn written(&self, pos: SourcePos, text: &str) -> bool {
+		self.line(pos.lin).iter().skip(pos.col as usize - 1).take(text.len()).collect::<String>() == text
+	}
+
+	// whether the first thing after the given position, skipping whitespace, is the text
+	fn followed_by(&self, pos: SourcePos, text: &str) -> bool {
+		let rest = self.line(pos.lin).iter().skip(pos.col as usize)
+			.chain(self.lines.iter().skip(pos.lin as usize).flat_map(|line| line.iter()))
+			.skip_while(|c| c.is_whitespace());
+		rest.take(text.len()).collect::<String>() == text
+	}
+
+	// the bracket closing the innermost group that contains the given position
+	fn closing(&self, from: SourcePos) -> Option<SourcePos> {
+		let mut depth = 0;
+		for &(pos, bracket) in self.brackets.iter().filter(|(pos, _)| key(*pos) >= key(from)) {
+			match bracket {
+				')' if depth == 0 => return Some(pos),
+				')' => depth -= 1,
+				_ => depth += 1,
+			}
+		}
+		None
+	}
+
+	// the closing bracket of an empty block, which is the one right after the first '{' of its header
+	fn closing_empty(&self, header: SourcePos) -> Option<SourcePos> {
+		let open = self.brackets.iter().position(|(pos, bracket)| *bracket == '{' && key(*pos) >= key(header))?;
+		self.brackets.get(open + 1).map(|(pos, _)| *pos)
+	}
+
+	fn comments_before(&mut self, pos: SourcePos) {
+		while self.comments.front().is_some_and(|comment| key(comment.pos) < key(pos)) {
+			let comment = self.comments.pop_front().unwrap();
+			if !self.before(comment.pos).is_empty() && self.out.ends_with('\n') {
+				// comments after some code stay at the end of the line they were on
+				self.out.pop();
+				self.out.push(' ');
+				self.out.push_str(&comment.text);
+				self.newline();
+			} else {
+				if !self.fresh && self.blank_before(comment.pos.lin) { self.newline(); }
+				self.write(&comment.text);
+				self.newline();
+				self.fresh = false;
+			}
+		}
+	}
+
+	// groups holding comments are written one item per line, so every comment keeps its place
+	fn has_comments_before(&self, pos: Option<SourcePos>) -> bool {
+		match (self.comments.front(), pos) {
+			(Some(comment), Some(pos)) => key(comment.pos) < key(pos),
+			_ => false,
+		}
+	}
+
+	// items written one per line, up to the end of the block they are in
+	fn body(&mut self, items: Vec<(SourcePos, Item)>, end: Option<SourcePos>, sep: &str) {
+		for (pos, item) in items {
+			self.comments_before(pos);
+			if !self.fresh && self.blank_before(pos.lin) { self.newline(); }
+			self.item(&item);
+			if !sep.is_empty() { self.write(sep); }
+			self.fresh = false;
+			self.newline();
+		}
+		if let Some(end) = end { self.comments_before(end); }
+	}
+
+	// items written one per line between brackets, each one followed by the separator
+	fn braced(&mut self, open: &str, close: &str, sep: &str, items: Vec<(SourcePos, Item)>, end: Option<SourcePos>) {
+		if !self.has_comments_before(end) {
+			match items.as_slice() {
+				[] => return self.write(&format!("{}{}", open, close)),
+				// a single item that was written in one line, like in `if done { return }`, stays there
+				[(pos, item)] if open == "{" && end.is_some_and(|end| end.lin == pos.lin) => {
+					self.write("{ ");
+					self.item(item);
+					return self.write(" }");
+				}
+				_ => (),
+			}
+		}
+		self.write(open);
+		self.newline();
+		self.indent += 1;
+		self.fresh = true;
+		self.body(items, end, sep);
+		self.indent -= 1;
+		self.write(close);
+	}
+
+	// items written in a single line, with the open and close brackets already including any padding
+	fn inline(&mut self, open: &str, close: &str, items: Vec<(SourcePos, Item)>) {
+		self.write(open);
+		for (i, (_, item)) in items.iter().enumerate() {
+			if i > 0 { self.write(", "); }
+			self.item(item);
+		}
+		self.write(close);
+	}
+
+	fn item(&mut self, item: &Item) {
+		match item {
+			Item::Stmt(stmt) => self.stmt(stmt),
+			Item::Method(data) => {
+				self.write(&data.name.name);
+				self.function(&data.params, &data.ret, &data.body, data.name.pos);
+			}
+			Item::Static(name, expr) => {
+				self.write("static ");
+				self.field(name, expr);
+			}
+			Item::Field(name, expr) => self.field(name, expr),
+			Item::Is(name) => self.write(&format!("is {}", name)),
+			Item::Element(expr) => self.expr(expr),
+			Item::Named(arg) => {
+				self.write(&format!("{} = ", arg.name));
+				self.expr(&arg.expr);
+			}
+			Item::Entry(key, value) => {
+				self.expr(key);
+				self.write(": ");
+				self.expr(value);
+			}
+			Item::Arm(arm) => {
+				self.pattern(&arm.pattern);
+				if let Some(guard) = &arm.guard {
+					self.write(" if ");
+					self.expr(guard);
+				}
+				self.write(" => ");
+				self.expr(&arm.body);
+			}
+		}
+	}
+
+	fn field(&mut self, name: &str, expr: &Expression) {
+		self.write(name);
+		match &expr.typ {
+			Variable(var) if var.name == name => (),
+			_ => {
+				self.write(" = ");
+				self.expr(expr);
+			}
+		}
+	}
+
+	fn annotation(&mut self, typ: &Option<Type>) {
+		if let Some(typ) = typ { self.write(&format!(": {}", typ)); }
+	}
+
+	fn block(&mut self, block: &[Statement], header: SourcePos) -> Option<SourcePos> {
+		let end = match block.last() {
+			Some(stmt) => self.closing(start(stmt)),
+			None => self.closing_empty(header),
+		};
+		let items = block.iter().map(|stmt| (start(stmt), Item::Stmt(stmt))).collect();
+		self.braced("{", "}", "", items, end);
+		end
+	}
+
+	fn function(&mut self, params: &[Param], ret: &Option<Type>, body: &[Statement], header: SourcePos) {
+		self.write("(");
+		for (i, param) in params.iter().enumerate() {
+			if i > 0 { self.write(", "); }
+			if param.rest { self.write("..."); }
+			match &param.pattern {
+				Some(pattern) => self.pattern(pattern),
+				None => self.write(&param.name.name),
+			}
+			self.annotation(&param.typ);
+			if let Some(default) = &param.default {
+				self.write(" = ");
+				self.expr(default);
+			}
+		}
+		self.write(")");
+		self.annotation(ret);
+		match body {
+			[Statement { typ: Return(expr), pos }] if self.written(*pos, "=>") => {
+				self.write(" => ");
+				self.expr(expr);
+			}
+			_ => {
+				self.write(" ");
+				self.block(body, header);
+			}
+		}
+	}
+
+	fn stmt(&mut self, stmt: &Statement) {
+		match &stmt.typ {
+			Expr(expr) => self.expr(expr),
+			Declaration(data) => {
+				self.write(if data.constant { "let const " } else { "let " });
+				self.write(&data.name.name);
+				self.annotation(&data.typ);
+				// a declaration without a value gets a none placed at its name
+				if !(is_none(&data.expr) && data.expr.pos == data.name.pos) {
+					self.write(" = ");
+					self.expr(&data.expr);
+				}
+			}
+			Destructure(data) => {
+				self.write(if data.constant { "let const " } else { "let " });
+				self.pattern(&data.pattern);
+				self.write(" = ");
+				self.expr(&data.expr);
+			}
+			FuncDeclaration(data) => {
+				self.write(&format!("function {}", data.name));
+				self.function(&data.params, &data.ret, &data.body, data.name.pos);
+			}
+			AttrDeclaration(data) => {
+				self.write(&format!("attr {} ", data.name));
+				let mut items = data.methods.iter().map(|method| (method.name.pos, Item::Method(method)))
+					.chain(data.fields.iter().map(|(name, expr)| (expr.pos, Item::Static(name, expr))))
+					.chain(data.attributes.iter().map(|attr| (attr.pos, Item::Is(attr))))
+					.collect::<Vec<_>>();
+				items.sort_by_key(|(pos, _)| key(*pos));
+				let end = match items.last() {
+					Some((pos, _)) => self.closing(*pos),
+					None => self.closing_empty(data.name.pos),
+				};
+				self.braced("{", "}", "", items, end);
+			}
+			Import(data) => {
+				self.write(&format!("import \"{}\"", data.path));
+				// a name inferred from the path is positioned at the path itself
+				if !self.written(data.name.pos, "\"") {
+					self.write(&format!(" as {}", data.name));
+				}
+			}
+			Assignment(data) => {
+				self.expr(&data.head);
+				match &data.expr.typ {
+					// compound assignments are positioned at their operator
+					Binary(bin) if data.expr.pos == stmt.pos => {
+						self.write(&format!(" {}= ", binary_op(&bin.op)));
+						self.expr(&bin.rhs);
+					}
+					_ => {
+						self.write(" = ");
+						self.expr(&data.expr);
+					}
+				}
+			}
+			If(data) => self.if_stmt(data, stmt.pos),
+			Loop(block) => {
+				self.write("loop ");
+				self.block(block, stmt.pos);
+			}
+			Break => self.write("break"),
+			Continue => self.write("continue"),
+			Return(expr) => {
+				self.write("return");
+				if !is_none(expr) || self.written(expr.pos, "none") {
+					self.write(" ");
+					self.expr(expr);
+				}
+			}
+			Scoped(block) => match for_parts(block) {
+				Some((target, list, body)) => {
+					self.write("for ");
+					match target {
+						Destructure(data) => self.pattern(&data.pattern),
+						Declaration(data) => self.write(&data.name.name),
+						_ => (),
+					}
+					self.write(" in ");
+					self.expr(list);
+					self.write(" ");
+					self.block(body, stmt.pos);
+				}
+				None => {
+					self.write("do ");
+					self.block(block, stmt.pos);
+				}
+			}
+		}
+	}
+
+	fn if_stmt(&mut self, data: &IfData, pos: SourcePos) {
+		self.write("if ");
+		self.expr(&data.cond);
+		self.write(" ");
+		let then_end = self.block(&data.then_block, pos);
+		match data.else_block.as_slice() {
+			// an empty else block is only kept when it was written, since it can still have comments
+			[] => if let Some(end) = then_end.filter(|end| self.followed_by(*end, "else")) {
+				self.write(" else ");
+				self.block(&[], end);
+			},
+			// an else if is parsed into an else block with only that if in it
+			[Statement { typ: If(inner), pos }] if self.before(*pos).ends_with("else") => {
+				self.write(" else ");
+				self.if_stmt(inner, *pos);
+			}
+			block => {
+				self.write(" else ");
+				self.block(block, pos);
+			}
+		}
+	}
+
+	fn expr(&mut self, expr: &Expression) {
+		match &expr.typ {
+			Binding(data) => {
+				self.expr(&data.expr);
+				self.write("::");
+				self.expr(&data.method);
+			}
+			Logic(data) => {
+				self.expr(&data.lhs);
+				self.write(match data.op { LogicOperator::And => " and ", LogicOperator::Or => " or ", LogicOperator::Coalesce => " ?? " });
+				self.expr(&data.rhs);
+			}
+			Binary(data) => {
+				self.expr(&data.lhs);
+				self.write(&format!(" {} ", binary_op(&data.op)));
+				self.expr(&data.rhs);
+			}
+			Unary(data) => {
+				self.write(match data.op { UnaryOperator::Not => "!", UnaryOperator::Pos => "+", UnaryOperator::Neg => "-" });
+				self.expr(&data.expr);
+			}
+			Call(data) => self.call(data),
+			Spread(expr) => {
+				self.write("...");
+				self.expr(expr);
+			}
+			Range(data) => {
+				if let Some(start) = &data.start { self.expr(start); }
+				self.write(if data.inclusive { "..=" } else { ".." });
+				if let Some(end) = &data.end { self.expr(end); }
+				if let Some(step) = &data.step {
+					self.write(" step ");
+					self.expr(step);
+				}
+			}
+			Index(data) => {
+				self.expr(&data.head);
+				self.write(if data.optional { "?.[" } else { "[" });
+				self.expr(&data.index);
+				self.write("]");
+			}
+			FieldGet(data) => {
+				self.expr(&data.head);
+				self.write(&format!("{}{}", if data.optional { "?." } else { "." }, data.field));
+			}
+			Literal(data) => self.literal(data, expr.pos),
+			Grouping(inner) => {
+				self.write("(");
+				self.expr(inner);
+				self.write(")");
+			}
+			Variable(name) => self.write(&name.name),
+			Lambda(data) => {
+				self.write("function");
+				self.function(&data.params, &data.ret, &data.body, expr.pos);
+			}
+			DoExpr(block) => match handled_parts(block) {
+				Some((inner, op)) => {
+					self.expr(inner);
+					self.write(op);
+				}
+				None => {
+					self.write("do ");
+					self.block(block, expr.pos);
+				}
+			}
+			Match(data) => {
+				self.write("match ");
+				self.expr(&data.value);
+				self.write(" ");
+				let items = data.arms.iter().map(|arm| (arm.pattern.pos, Item::Arm(arm))).collect::<Vec<_>>();
+				let end = data.arms.last().and_then(|arm| self.closing(arm.pattern.pos));
+				match data.arms.first() {
+					Some(arm) if arm.pattern.pos.lin == data.value.pos.end_lin && !self.has_comments_before(end) => self.inline("{ ", " }", items),
+					Some(_) => self.braced("{", "}", "", items, end),
+					None => {
+						let end = self.closing_empty(expr.pos);
+						self.braced("{", "}", "", items, end);
+					}
+				}
+			}
+			SelfRef => self.write("self"),
+		}
+	}
+
+	fn call(&mut self, data: &CallData) {
+		// a piped call is written before its calee
+		if let [arg] = data.args.as_slice() {
+			if key(arg.pos) < key(data.calee.pos) {
+				self.expr(arg);
+				if data.calee.pos.lin > arg.pos.end_lin {
+					self.newline();
+					self.indent += 1;
+					self.write("|> ");
+					self.indent -= 1;
+				} else {
+					self.write(" |> ");
+				}
+				return self.expr(&data.calee);
+			}
+		}
+		self.expr(&data.calee);
+		if data.optional { self.write("?."); }
+		let items = data.args.iter().map(|arg| (arg.pos, Item::Element(arg)))
+			.chain(data.named.iter().map(|arg| (arg.pos, Item::Named(arg))))
+			.collect::<Vec<_>>();
+		let end = items.last().and_then(|(last, _)| self.closing(*last));
+		match items.first() {
+			Some((first, _)) if first.lin > data.calee.pos.end_lin || self.has_comments_before(end) => self.braced("(", ")", ",", items, end),
+			_ => self.inline("(", ")", items),
+		}
+	}
+
+	fn literal(&mut self, data: &LiteralData, pos: SourcePos) {
+		match data {
+			LiteralData::None => self.write("none"),
+			LiteralData::Str(str) => self.write(&format!("\"{}\"", str)),
+			LiteralData::Num(n) => self.write(&n.to_string()),
+			LiteralData::Bool(b) => self.write(&b.to_string()),
+			LiteralData::Template(parts) => {
+				self.write("'");
+				for part in parts {
+					match &part.typ {
+						// the text between interpolations is kept as string literals
+						Literal(LiteralData::Str(text)) if !self.before(part.pos).ends_with('{') => self.write(text),
+						_ => {
+							self.write("#{");
+							self.expr(part);
+							self.write("}");
+						}
+					}
+				}
+				self.write("'");
+			}
+			LiteralData::List(exprs) => {
+				let items = exprs.iter().map(|expr| (expr.pos, Item::Element(expr))).collect();
+				let end = exprs.last().and_then(|last| self.closing(last.pos));
+				match exprs.first() {
+					Some(first) if first.pos.lin > pos.lin || self.has_comments_before(end) => self.braced("[", "]", ",", items, end),
+					_ => self.inline("[", "]", items),
+				}
+			}
+			LiteralData::Map(entries) => {
+				let items = entries.iter().map(|(key, value)| (key.pos, Item::Entry(key, value))).collect();
+				let end = entries.last().and_then(|(last, _)| self.closing(last.pos));
+				match entries.first() {
+					Some((first, _)) if first.pos.lin > pos.lin || self.has_comments_before(end) => self.braced("[", "]", ",", items, end),
+					Some(_) => self.inline("[", "]", items),
+					None => self.write("[:]"),
+				}
+			}
+			LiteralData::Object(fields, attrs, spreads) => {
+				let mut items = fields.iter().map(|(name, expr)| (expr.pos, Item::Field(name, expr)))
+					.chain(attrs.iter().map(|attr| (attr.pos, Item::Is(attr))))
+					.chain(spreads.iter().map(|expr| (expr.pos, Item::Element(expr))))
+					.collect::<Vec<_>>();
+				items.sort_by_key(|(pos, _)| key(*pos));
+				let end = items.last().and_then(|(last, _)| self.closing(*last));
+				match items.first() {
+					Some((first, _)) if first.lin > pos.lin || self.has_comments_before(end) => self.braced("{", "}", "", items, end),
+					Some(_) => self.inline("{ ", " }", items),
+					None => {
+						let end = self.closing_empty(pos);
+						self.braced("{", "}", "", items, end);
+					}
+				}
+			}
+			LiteralData::Error(expr) => {
+				self.write("error");
+				if !is_none(expr) || self.written(expr.pos, "none") {
+					self.write(" ");
+					self.expr(expr);
+				}
+			}
+		}
+	}
+
+	fn pattern(&mut self, pattern: &Pattern) {
+		match &pattern.typ {
+			PatternType::Wildcard => self.write("_"),
+			PatternType::Literal(LiteralData::Str(str)) if str.contains('"') || self.written(pattern.pos, "'") => self.write(&format!("'{}'", str)),
+			PatternType::Literal(data) => self.literal(data, pattern.pos),
+			PatternType::Binding(name) => self.write(&name.name),
+			PatternType::List(patterns) => {
+				self.write("[");
+				for (i, pattern) in patterns.iter().enumerate() {
+					if i > 0 { self.write(", "); }
+					self.pattern(pattern);
+				}
+				self.write("]");
+			}
+			PatternType::Object(fields, attrs) => {
+				let mut members = fields.iter().map(|(name, pattern)| (pattern.pos, Ok((name, pattern))))
+					.chain(attrs.iter().map(|attr| (attr.pos, Err(attr))))
+					.collect::<Vec<_>>();
+				members.sort_by_key(|(pos, _)| key(*pos));
+				if members.is_empty() { return self.write("{}"); }
+				self.write("{ ");
+				for (i, (_, member)) in members.into_iter().enumerate() {
+					if i > 0 { self.write(", "); }
+					match member {
+						Ok((name, Pattern { typ: PatternType::Binding(var), .. })) if &var.name == name => self.write(name),
+						Ok((name, pattern)) => {
+							self.write(&format!("{} = ", name));
+							self.pattern(pattern);
+						}
+						Err(attr) => self.write(&format!("is {}", attr)),
+					}
+				}
+				self.write(" }");
+			}
+			PatternType::Attribute(name) => self.write(&format!("is {}", name)),
+			PatternType::Rest(pattern) => {
+				self.write("...");
+				// ignoring the items left is always written as a bare `...`
+				if !matches!(pattern.typ, PatternType::Wildcard) { self.pattern(pattern); }
+			}
+		}
+	}
+
+}
@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+
+use super::format;
+
+fn mars_files(dir: &Path, files: &mut Vec<PathBuf>) {
+	for entry in std::fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		if path.is_dir() {
+			mars_files(&path, files);
+		} else if path.extension().is_some_and(|ext| ext == "mars") {
+			files.push(path);
+		}
+	}
+}
+
+// the files in tests are kept formatted, so formatting them changes nothing
+#[test]
+fn formatted_files_are_left_alone() {
+	let mut files = Vec::new();
+	mars_files(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").as_path(), &mut files);
+	assert!(!files.is_empty());
+	for path in files {
+		let text = std::fs::read_to_string(&path).unwrap();
+		let name = path.to_string_lossy();
+		match format(&name, &text) {
+			Ok(out) => assert_eq!(out, text, "{} changed when formatted", name),
+			Err(err) => panic!("{} failed to format: {}", name, err),
+		}
+	}
+}
+
+#[test]
+fn comments_inside_a_group_keep_it_multiline() {
+	let text = "function main(args) {\n\tlet x = [1, # first\n\t2, (# inline #) 3]\n}\n";
+	let out = format("comments", text).unwrap();
+	assert_eq!(out, "function main(args) {\n\tlet x = [\n\t\t1, # first\n\t\t2, (# inline #)\n\t\t3,\n\t]\n}\n");
+	assert_eq!(format("comments", &out).unwrap(), out);
+}
+
+#[test]
+fn groups_without_comments_stay_inline() {
+	let text = "function main(args) {\n\tlet x = [1, 2, 3] # after\n\tlet y = { a = 1, b = 2 }\n\tf(x, y)\n}\n";
+	assert_eq!(format("inline", text).unwrap(), text);
+}
@@ -1,71 +1,148 @@
+use std::{cell::RefCell, collections::{HashMap, HashSet}, rc::Rc};
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use crate::{ast::identifier::{Identifier, Location}, utils::wrap::Wrap};
 
-use crate::utils::wrap::Wrap;
-
-use super::value::Value;
+use super::value::{Value, gc::{Node, Tracer}, primitives::none::ValNone};
 
 pub type ValueMap = HashMap<usize, Box<dyn Value>>;
 
+pub type Scope = Rc<RefCell<Vec<Box<dyn Value>>>>;
+
+type Names = Rc<RefCell<Vec<String>>>;
+
 #[derive(Debug, Clone)]
-pub struct Environment(Vec<Rc<RefCell<ValueMap>>>);
+pub struct Environment {
+	globals: Rc<RefCell<ValueMap>>,
+	module: Rc<RefCell<ValueMap>>,
+	scopes: Vec<Scope>,
+	receiver: Option<Box<dyn Value>>,
+	// slots don't know their names, so they are only kept next to them while debugging
+	names: Option<Vec<Names>>,
+}
 
 impl Environment {
-	
+
 	pub fn new(globals: ValueMap) -> Self {
-		Self(vec![globals.wrap(), ValueMap::new().wrap()])
+		Self { globals: globals.wrap(), module: ValueMap::new().wrap(), scopes: Vec::new(), receiver: None, names: None }
+	}
+
+	pub fn track_names(&mut self) {
+		self.names = Some(self.scopes.iter().map(|_| Vec::new().wrap()).collect());
+	}
+
+	// a fresh module scope that still shares the global one
+	pub fn for_module(&self) -> Self {
+		let names = self.names.as_ref().map(|_| Vec::new());
+		Self { globals: self.globals.clone(), module: ValueMap::new().wrap(), scopes: Vec::new(), receiver: None, names }
 	}
 
 	pub fn push_new(&mut self) {
-		self.0.push(ValueMap::new().wrap())
+		self.scopes.push(Vec::new().wrap());
+		if let Some(ref mut names) = self.names { names.push(Vec::new().wrap()); }
 	}
 
 	pub fn pop(&mut self) {
-		self.0.pop();
+		self.scopes.pop();
+		if let Some(ref mut names) = self.names { names.pop(); }
 	}
 
-	pub fn define(&mut self, key: usize, value: Box<dyn Value>) {
-		self.0.last_mut().unwrap().borrow_mut().insert(key, value);
+	fn scope(&self, depth: usize) -> &Scope {
+		&self.scopes[self.scopes.len() - 1 - depth]
 	}
-	
-	pub fn has(&self, key: usize) -> bool {
-		let mut cur = self.0.as_slice();
-		while let [rest @ .., top] = cur {
-			if top.borrow().contains_key(&key) { return true; }
-			cur = rest;
+
+	pub fn define(&mut self, name: &Identifier, value: Box<dyn Value>) {
+		match name.get_location() {
+			Location::Global => self.define_id(name.get_id(), value),
+			Location::Local(depth, slot) => {
+				let mut scope = self.scope(depth).borrow_mut();
+				if scope.len() <= slot { scope.resize_with(slot + 1, ValNone::new); }
+				scope[slot] = value;
+				if let Some(ref names) = self.names {
+					let mut names = names[names.len() - 1 - depth].borrow_mut();
+					if names.len() <= slot { names.resize(slot + 1, String::new()); }
+					names[slot] = name.get_name();
+				}
+			}
 		}
-		false
 	}
 
-	pub fn get(&self, key: usize) -> Box<dyn Value> {
-		let mut cur = self.0.as_slice();
-		while let [rest @ .., top] = cur {
-			if top.borrow().contains_key(&key) {
-				return top.borrow().get(&key).unwrap().clone()
+	pub fn get(&self, name: &Identifier) -> Box<dyn Value> {
+		match name.get_location() {
+			Location::Global => self.get_id(name.get_id()),
+			Location::Local(depth, slot) => match self.scope(depth).borrow().get(slot) {
+				Some(value) => value.clone(),
+				None => panic!("use of unresolved variable"),
 			}
-			cur = rest;
 		}
-		panic!("use of unresolved variable");
-	}
-	
-	pub fn assign(&mut self, key: usize, value: Box<dyn Value>) {
-		let mut cur = self.0.as_mut_slice();
-		while let [rest @ .., top] = cur {
-			if top.borrow().contains_key(&key) {
-				top.borrow_mut().insert(key, value);
-				return;
-			}
-			cur = rest;
+	}
+
+	pub fn assign(&mut self, name: &Identifier, value: Box<dyn Value>) {
+		match name.get_location() {
+			Location::Global => self.assign_id(name.get_id(), value),
+			Location::Local(depth, slot) => self.scope(depth).borrow_mut()[slot] = value,
 		}
-		panic!("use of unresolved variable");
 	}
 
-	pub fn cloned(&self) -> Environment {
-		let mut env = Vec::new();
-		for r in &self.0 {
-			env.push(r.borrow().clone().wrap())
+	pub fn define_id(&mut self, key: usize, value: Box<dyn Value>) {
+		self.module.borrow_mut().insert(key, value);
+	}
+
+	pub fn define_global(&mut self, key: usize, value: Box<dyn Value>) {
+		self.globals.borrow_mut().insert(key, value);
+	}
+
+	pub fn has_id(&self, key: usize) -> bool {
+		self.module.borrow().contains_key(&key) || self.globals.borrow().contains_key(&key)
+	}
+
+	pub fn get_id(&self, key: usize) -> Box<dyn Value> {
+		if let Some(value) = self.module.borrow().get(&key) { return value.clone(); }
+		match self.globals.borrow().get(&key) {
+			Some(value) => value.clone(),
+			None => panic!("use of unresolved variable"),
+		}
+	}
+
+	pub fn assign_id(&mut self, key: usize, value: Box<dyn Value>) {
+		let table = if self.module.borrow().contains_key(&key) { &self.module } else { &self.globals };
+		match table.borrow_mut().get_mut(&key) {
+			Some(cur) => *cur = value,
+			None => panic!("use of unresolved variable"),
 		}
-		Environment(env)
+	}
+
+	pub fn bind(&mut self, receiver: Box<dyn Value>) {
+		self.receiver = Some(receiver);
+	}
+
+	pub fn receiver(&self) -> Option<Box<dyn Value>> {
+		self.receiver.clone()
+	}
+
+	// the names of every local scope, from the outermost to the innermost
+	pub fn local_names(&self) -> Vec<Vec<String>> {
+		self.names.iter().flatten().map(|names| names.borrow().clone()).collect()
+	}
+
+	// the locals that can be seen from the innermost scope, the most recent ones first
+	pub fn locals(&self) -> Vec<(String, Box<dyn Value>)> {
+		let Some(ref names) = self.names else { return Vec::new() };
+		let mut seen = HashSet::new();
+		let mut locals = Vec::new();
+		for (scope, names) in self.scopes.iter().zip(names).rev() {
+			let scope = scope.borrow();
+			for (name, value) in names.borrow().iter().zip(scope.iter()).rev() {
+				// names starting with $ are made up by desugarings
+				if name.is_empty() || name.starts_with('$') || !seen.insert(name.clone()) { continue; }
+				locals.push((name.clone(), value.clone()));
+			}
+		}
+		locals
+	}
+
+	pub fn trace(&self, visit: &mut Tracer) {
+		self.scopes.iter().for_each(|scope| visit(Node::Scope(scope.clone())));
+		if let Some(ref receiver) = self.receiver { receiver.trace(visit); }
 	}
 
 }
@@ -0,0 +1,91 @@
+use crate::{interpreter::{Interpreter, value::{Value, ValueType, primitives::{callable::{Callable, nativefn::NativeFn}, none::ValNone}}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+// strings are quoted in failure messages, so "1" and 1 can be told apart
+pub fn render(value: &dyn Value, interpreter: &mut Interpreter, pos: SourcePos) -> Result<String> {
+	let str = value.to_string(interpreter, pos)?;
+	match value.get_type() {
+		ValueType::Str => format!("\"{}\"", str),
+		_ => str,
+	}.wrap()
+}
+
+// assert and assert_eq take an optional last argument, a message that goes before the failure
+fn fail(msg: String, note: Option<&(Box<dyn Value>, SourcePos)>, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Box<dyn Value>> {
+	let msg = match note {
+		Some((note, note_pos)) => format!("{}: {}", note.to_string(interpreter, *note_pos)?, msg),
+		None => msg,
+	};
+	ErrorList::run(msg, pos).err()
+}
+
+pub fn assert() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Assert;
+
+	impl Callable for Assert {
+		fn arity(&self) -> usize { 2 }
+
+		fn optional(&self) -> usize { 1 }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = &args[0];
+			if !v0.is_truthy() {
+				let msg = format!("Assertion failed, found {}", render(v0.as_ref(), interpreter, *p0)?);
+				return fail(msg, args.get(1), interpreter, pos);
+			}
+			ValNone.wrap()
+		}
+	}
+
+	NativeFn::create(Assert.wrap())
+}
+
+pub fn assert_eq() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct AssertEq;
+
+	impl Callable for AssertEq {
+		fn arity(&self) -> usize { 3 }
+
+		fn optional(&self) -> usize { 1 }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let (v1, p1) = args[1].clone();
+			if !v0.equals(v1.clone(), p1, interpreter, p0)? {
+				let msg = format!("Expected values to be equal, left: {}, right: {}", render(v0.as_ref(), interpreter, p0)?, render(v1.as_ref(), interpreter, p1)?);
+				return fail(msg, args.get(2), interpreter, pos);
+			}
+			ValNone.wrap()
+		}
+	}
+
+	NativeFn::create(AssertEq.wrap())
+}
+
+pub fn assert_error() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct AssertError;
+
+	impl Callable for AssertError {
+		fn arity(&self) -> usize { 2 }
+
+		fn optional(&self) -> usize { 1 }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			if v0.get_type() != ValueType::Error {
+				let msg = format!("Expected an error, found {}", render(v0.as_ref(), interpreter, p0)?);
+				return fail(msg, None, interpreter, pos);
+			}
+			// the kind is any attribute the error should have, like ValueError
+			if let Some((v1, p1)) = args.get(1) {
+				let kind = v1.to_attr(*p1)?;
+				if !v0.has_attr(kind.get_id(), interpreter) {
+					let msg = format!("Expected an error of kind {}, found {}", v1.to_string(interpreter, *p1)?, render(v0.as_ref(), interpreter, p0)?);
+					return fail(msg, None, interpreter, pos);
+				}
+			}
+			ValNone.wrap()
+		}
+	}
+
+	NativeFn::create(AssertError.wrap())
+}
@@ -1,37 +1,148 @@
-
 use std::collections::{HashMap, HashSet};
 
-use crate::{interpreter::{value::{Value, primitives::{attribute::Attribute, object::ObjectMap, callable::{Callable, nativefn::NativeFn}}, macros::castf}, globals::attributes::NatSelf, Interpreter}, ast::identifier::Identifier, utils::{result::Result, wrap::Wrap, source_pos::SourcePos, global_ids::global_id}};
+use crate::{interpreter::{value::{Value, primitives::{attribute::Attribute, error::Error, object::ObjectMap, callable::{Callable, nativefn::NativeFn}, none::ValNone, string::Str}}, globals::attributes::NatSelf, Interpreter}, ast::identifier::Identifier, utils::{result::{ErrorList, Result}, wrap::Wrap, source_pos::SourcePos, global_ids::global_id, sources::get_source}};
 
 pub const ERROR_ATTR: &str = "Error";
 
+// kinds of the errors natives return
+pub const IO_ERROR: &str = "IoError";
+pub const PARSE_ERROR: &str = "ParseError";
+pub const VALUE_ERROR: &str = "ValueError";
+
+fn this(binding: &NatSelf, pos: SourcePos) -> Result<Error> {
+	binding.clone().unwrap().borrow().to_error(pos)
+}
+
 fn get() -> Box<dyn Value> {
 	#[derive(Clone, Debug)] struct Get(NatSelf);
-	
+
 	impl Callable for Get {
 		fn arity(&self) -> usize { 0 }
-		
+
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
-		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
-			let data = self.0.clone().unwrap();
-			castf!(err data.borrow()).wrap()
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			this(&self.0, pos)?.data().wrap()
 		}
 	}
-	
+
 	NativeFn::create(Get(None).wrap())
 }
 
+fn message() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Message(NatSelf);
+
+	impl Callable for Message {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			Str::new(this(&self.0, pos)?.data().to_string(interpreter, pos)?).wrap()
+		}
+	}
+
+	NativeFn::create(Message(None).wrap())
+}
+
+fn kind() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Kind(NatSelf);
+
+	impl Callable for Kind {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			interpreter.env.get_id(this(&self.0, pos)?.kind()).wrap()
+		}
+	}
+
+	NativeFn::create(Kind(None).wrap())
+}
+
+fn cause() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Cause(NatSelf);
+
+	impl Callable for Cause {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			this(&self.0, pos)?.cause().unwrap_or_else(ValNone::new).wrap()
+		}
+	}
+
+	NativeFn::create(Cause(None).wrap())
+}
+
+fn location() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct At(NatSelf);
+
+	impl Callable for At {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			match this(&self.0, pos)?.pos() {
+				Some(at) => match get_source(at.file) {
+					Some(src) => Str::new(format!("{}:{}:{}", src.name, at.lin, at.col)),
+					None => Str::new(format!("{}:{}", at.lin, at.col)),
+				},
+				None => ValNone::new(),
+			}.wrap()
+		}
+	}
+
+	NativeFn::create(At(None).wrap())
+}
+
+// Error.new(kind, message, cause), where kind is Error or any attribute that has it
+fn new() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct New;
+
+	impl Callable for New {
+		fn arity(&self) -> usize { 3 }
+
+		fn optional(&self) -> usize { 1 }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let kind = v0.to_attr(p0)?;
+			let error_id = global_id(ERROR_ATTR);
+			if kind.get_id() != error_id && !kind.has_attr(error_id, interpreter) {
+				return ErrorList::run(format!("{} is not an error attribute, it needs to have the Error attribute", kind.to_string(interpreter, p0)?), p0).err();
+			}
+
+			let cause = match args.get(2) {
+				Some((v2, p2)) => Some(v2.to_error(*p2)?.wrap()),
+				None => None,
+			};
+
+			Error::create(kind.get_id(), args[1].0.clone(), cause, pos).wrap()
+		}
+	}
+
+	NativeFn::create(New.wrap())
+}
+
+// a builtin error kind, which can be matched against with 'is' like any other attribute
+pub fn error_kind(name: &str) -> Box<dyn Value> {
+	let attributes = vec![global_id(ERROR_ATTR)].into_iter().collect();
+	Attribute::new(Identifier::native(name, global_id(name)), HashMap::new(), ObjectMap::new(), attributes)
+}
+
 pub fn error() -> Box<dyn Value> {
 	let mut methods = HashMap::new();
-	
+
 	let v = vec![
 	("get", get()),
+	("message", message()),
+	("kind", kind()),
+	("cause", cause()),
+	("location", location()),
 	];
-	
+
 	for (key, val) in v {
 		methods.insert(key.to_owned(), val.wrap());
 	}
-	
-	Attribute::new(Identifier { name: "error".to_owned(), id: global_id(ERROR_ATTR).wrap() }, methods, ObjectMap::new(), HashSet::new())
-}
\ No newline at end of file
+
+	let mut fields = ObjectMap::new();
+	fields.insert("new".to_owned(), new().wrap());
+
+	Attribute::new(Identifier::native("error", global_id(ERROR_ATTR)), methods, fields, HashSet::new())
+}
@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{interpreter::{Interpreter, globals::attributes::NatSelf, value::{Value, ValueType, gc::{Node, Tracer}, primitives::{attribute::Attribute, callable::{Callable, CallableRef, nativefn::NativeFn}, iterator::{Done, Iter, IterRef, NativeIter, advance}, list::List, number::Number, object::ObjectMap}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}, ast::identifier::Identifier};
+
+pub const ITER_ATTR: &str = "Iter";
+
+fn source(binding: &NatSelf, pos: SourcePos) -> Result<IterRef> {
+	binding.clone().unwrap().borrow().to_iter(pos)
+}
+
+fn callback(val: Box<dyn Value>, pos: SourcePos) -> Result<CallableRef> {
+	let fun = val.to_callable(pos)?;
+	fun.check_arity(1, pos)?;
+	fun.wrap()
+}
+
+pub fn of() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Of;
+
+	impl Callable for Of {
+		fn arity(&self) -> usize { 1 }
+
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			Iter::new(v0.to_iter(p0)?).wrap()
+		}
+	}
+
+	NativeFn::create(Of.wrap())
+}
+
+fn map() -> Box<dyn Value> {
+	#[derive(Debug)] struct Mapped { source: IterRef, fun: CallableRef }
+
+	impl NativeIter for Mapped {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			match advance(&self.source, interpreter, pos)? {
+				Some(val) => Some(self.fun.call(pos, interpreter, vec![(val, pos)])?),
+				None => None,
+			}.wrap()
+		}
+
+		fn trace(&self, visit: &mut Tracer) {
+			visit(Node::Iter(self.source.clone()));
+			visit(Node::Callable(self.fun.clone()));
+		}
+	}
+
+	#[derive(Clone, Debug)] struct Map(NatSelf);
+
+	impl Callable for Map {
+		fn arity(&self) -> usize { 1 }
+
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			Iter::new(Mapped { source: source(&self.0, pos)?, fun: callback(v0, p0)? }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Map(None).wrap())
+}
+
+fn filter() -> Box<dyn Value> {
+	#[derive(Debug)] struct Filtered { source: IterRef, fun: CallableRef }
+
+	impl NativeIter for Filtered {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			while let Some(val) = advance(&self.source, interpreter, pos)? {
+				let ret = self.fun.call(pos, interpreter, vec![(val.clone(), pos)])?;
+				match ret.get_type() {
+					ValueType::Error => return ret.wrap(),
+					_ if ret.is_truthy() => return val.wrap(),
+					_ => (),
+				}
+			}
+			None.wrap()
+		}
+
+		fn trace(&self, visit: &mut Tracer) {
+			visit(Node::Iter(self.source.clone()));
+			visit(Node::Callable(self.fun.clone()));
+		}
+	}
+
+	#[derive(Clone, Debug)] struct Filter(NatSelf);
+
+	impl Callable for Filter {
+		fn arity(&self) -> usize { 1 }
+
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			Iter::new(Filtered { source: source(&self.0, pos)?, fun: callback(v0, p0)? }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Filter(None).wrap())
+}
+
+fn take() -> Box<dyn Value> {
+	#[derive(Debug)] struct Taken { source: IterRef, left: usize }
+
+	impl NativeIter for Taken {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			// stop before stepping the source, so it isn't consumed past what was taken
+			if self.left == 0 { return None.wrap(); }
+			self.left -= 1;
+			advance(&self.source, interpreter, pos)
+		}
+
+		fn trace(&self, visit: &mut Tracer) { visit(Node::Iter(self.source.clone())) }
+	}
+
+	#[derive(Clone, Debug)] struct Take(NatSelf);
+
+	impl Callable for Take {
+		fn arity(&self) -> usize { 1 }
+
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let n0 = v0.to_num(p0)?;
+			Iter::new(Taken { source: source(&self.0, pos)?, left: n0.max(0.0) as usize }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Take(None).wrap())
+}
+
+fn zip() -> Box<dyn Value> {
+	#[derive(Debug)] struct Zipped { left: IterRef, right: IterRef }
+
+	impl NativeIter for Zipped {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			let left = match advance(&self.left, interpreter, pos)? {
+				Some(val) => val,
+				None => return None.wrap(),
+			};
+			advance(&self.right, interpreter, pos)?.map(|right| List::new(vec![left, right])).wrap()
+		}
+
+		fn trace(&self, visit: &mut Tracer) {
+			visit(Node::Iter(self.left.clone()));
+			visit(Node::Iter(self.right.clone()));
+		}
+	}
+
+	#[derive(Clone, Debug)] struct Zip(NatSelf);
+
+	impl Callable for Zip {
+		fn arity(&self) -> usize { 1 }
+
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			Iter::new(Zipped { left: source(&self.0, pos)?, right: v0.to_iter(p0)? }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Zip(None).wrap())
+}
+
+fn enumerate() -> Box<dyn Value> {
+	#[derive(Debug)] struct Enumerated { source: IterRef, i: usize }
+
+	impl NativeIter for Enumerated {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			let val = advance(&self.source, interpreter, pos)?.map(|val| List::new(vec![Number::new(self.i as f64), val]));
+			self.i += 1;
+			val.wrap()
+		}
+
+		fn trace(&self, visit: &mut Tracer) { visit(Node::Iter(self.source.clone())) }
+	}
+
+	#[derive(Clone, Debug)] struct Enumerate(NatSelf);
+
+	impl Callable for Enumerate {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			Iter::new(Enumerated { source: source(&self.0, pos)?, i: 0 }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Enumerate(None).wrap())
+}
+
+fn chain() -> Box<dyn Value> {
+	#[derive(Debug)] struct Chained { first: IterRef, second: IterRef, on_second: bool }
+
+	impl NativeIter for Chained {
+		fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			if !self.on_second {
+				match advance(&self.first, interpreter, pos)? {
+					Some(val) => return val.wrap(),
+					None => self.on_second = true,
+				}
+			}
+			advance(&self.second, interpreter, pos)
+		}
+
+		fn trace(&self, visit: &mut Tracer) {
+			visit(Node::Iter(self.first.clone()));
+			visit(Node::Iter(self.second.clone()));
+		}
+	}
+
+	#[derive(Clone, Debug)] struct Chain(NatSelf);
+
+	impl Callable for Chain {
+		fn arity(&self) -> usize { 1 }
+
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			Iter::new(Chained { first: source(&self.0, pos)?, second: v0.to_iter(p0)?, on_second: false }.wrap()).wrap()
+		}
+	}
+
+	NativeFn::create(Chain(None).wrap())
+}
+
+fn collect() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Collect(NatSelf);
+
+	impl Callable for Collect {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
+
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let iter = source(&self.0, pos)?;
+			let mut collected = Vec::new();
+			while let Some(val) = advance(&iter, interpreter, pos)? {
+				match val.get_type() {
+					ValueType::Error => return val.wrap(),
+					_ => collected.push(val),
+				}
+			}
+			List::new(collected).wrap()
+		}
+	}
+
+	NativeFn::create(Collect(None).wrap())
+}
+
+pub fn iter() -> Box<dyn Value> {
+	let mut methods = HashMap::new();
+
+	let v = vec![
+	("map", map()),
+	("filter", filter()),
+	("take", take()),
+	("zip", zip()),
+	("enumerate", enumerate()),
+	("chain", chain()),
+	("collect", collect()),
+	];
+
+	for (key, val) in v {
+		methods.insert(key.to_owned(), val.wrap());
+	}
+
+	let mut fields = ObjectMap::new();
+	fields.insert("of".to_owned(), of().wrap());
+	fields.insert("done".to_owned(), Done::new().wrap());
+
+	Attribute::new(Identifier::native("Iter", global_id(ITER_ATTR)), methods, fields, HashSet::new())
+}
@@ -11,7 +11,7 @@ fn size() -> Box<dyn Value> {
 	impl Callable for Size {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let list_ref = self.0.clone().unwrap();
 			Number::new(castf!(vec list_ref.borrow()).borrow().len() as f64).wrap()
 		}
@@ -28,7 +28,7 @@ fn get() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let p0 = args[0].1.clone();
 			let list_ref = self.0.clone().unwrap();
@@ -56,7 +56,7 @@ fn push() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let v0 = args[0].0.clone();
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
@@ -74,7 +74,7 @@ fn pop() -> Box<dyn Value> {
 	impl Callable for Pop {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
 			let val = list.borrow_mut().pop();
@@ -93,7 +93,7 @@ fn contains() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
@@ -115,7 +115,7 @@ fn reverse() -> Box<dyn Value> {
 	impl Callable for Reverse {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
 			let mut rev = list.borrow().clone();
@@ -135,12 +135,12 @@ fn iter() -> Box<dyn Value> {
 
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
 
-		fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let c0 = castf!(fun args[0].0.clone());
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
 			for val in list.borrow().iter().cloned() {
-				c0.borrow_mut().call(pos, interpreter, vec![(val, pos)])?;
+				c0.call(pos, interpreter, vec![(val, pos)])?;
 			}
 			ValNone::new().wrap()
 		}
@@ -157,14 +157,14 @@ fn map() -> Box<dyn Value> {
 
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
 
-		fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let c0 = castf!(fun args[0].0.clone());
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
 			let mut mapped = Vec::new();
 			for val in list.borrow().iter().cloned() {
 				mapped.push({
-					let ret = c0.borrow_mut().call(pos, interpreter, vec![(val, pos)])?;
+					let ret = c0.call(pos, interpreter, vec![(val, pos)])?;
 					match ret.get_type() {
 						ValueType::Error => return ret.wrap(),
 						_ => ret
@@ -186,13 +186,13 @@ fn filter() -> Box<dyn Value> {
 
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap(); }
 
-		fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let c0 = castf!(fun args[0].0.clone());
 			let list_ref = self.0.clone().unwrap();
 			let list = castf!(vec list_ref.borrow());
 			let mut filtered = Vec::new();
 			for val in list.borrow().iter().cloned() {
-				let ret = c0.borrow_mut().call(pos, interpreter, vec![(val.clone(), pos)])?;
+				let ret = c0.call(pos, interpreter, vec![(val.clone(), pos)])?;
 				match ret.get_type() {
 					ValueType::Error => return ret.wrap(),
 					_ if ret.is_truthy() => filtered.push(val),
@@ -225,5 +225,5 @@ pub fn list() -> Box<dyn Value> {
 		methods.insert(key.to_owned(), val.wrap());
 	}
 	
-	Attribute::new(Identifier { name: "list".to_owned(), id: global_id(LIST_ATTR).wrap() }, methods, ObjectMap::new(), HashSet::new())
+	Attribute::new(Identifier::native("list", global_id(LIST_ATTR)), methods, ObjectMap::new(), HashSet::new())
 }
\ No newline at end of file
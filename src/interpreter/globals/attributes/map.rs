@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{interpreter::{Interpreter, globals::attributes::NatSelf, value::{Value, macros::castf, primitives::{attribute::Attribute, bool::Bool, callable::{Callable, nativefn::NativeFn}, none::ValNone, number::Number, object::ObjectMap, list::List}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}, ast::identifier::Identifier};
+
+pub const MAP_ATTR: &str = "Map";
+
+fn size() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Size(NatSelf);
+	
+	impl Callable for Size {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let map_ref = self.0.clone().unwrap();
+			Number::new(castf!(map map_ref.borrow()).borrow().len() as f64).wrap()
+		}
+	}
+	
+	NativeFn::create(Size(None).wrap())
+}
+
+fn get() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Get(NatSelf);
+	
+	impl Callable for Get {
+		fn arity(&self) -> usize { 1 }
+		
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let key = v0.to_key(p0)?;
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let val = map.borrow().get(&key);
+			val.unwrap_or(ValNone::new()).wrap()
+		}
+	}
+	
+	NativeFn::create(Get(None).wrap())
+}
+
+fn set() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Set(NatSelf);
+	
+	impl Callable for Set {
+		fn arity(&self) -> usize { 2 }
+		
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let v1 = args[1].0.clone();
+			let key = v0.to_key(p0)?;
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			map.borrow_mut().insert(key, v1);
+			ValNone::new().wrap()
+		}
+	}
+	
+	NativeFn::create(Set(None).wrap())
+}
+
+fn remove() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Remove(NatSelf);
+	
+	impl Callable for Remove {
+		fn arity(&self) -> usize { 1 }
+		
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let key = v0.to_key(p0)?;
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let val = map.borrow_mut().remove(&key);
+			val.unwrap_or(ValNone::new()).wrap()
+		}
+	}
+	
+	NativeFn::create(Remove(None).wrap())
+}
+
+fn contains() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Contains(NatSelf);
+	
+	impl Callable for Contains {
+		fn arity(&self) -> usize { 1 }
+		
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let (v0, p0) = args[0].clone();
+			let key = v0.to_key(p0)?;
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let found = map.borrow().contains(&key);
+			Bool::new(found).wrap()
+		}
+	}
+	
+	NativeFn::create(Contains(None).wrap())
+}
+
+fn keys() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Keys(NatSelf);
+	
+	impl Callable for Keys {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let keys = map.borrow().entries().iter().map(|(key, _)| key.to_value()).collect();
+			List::new(keys).wrap()
+		}
+	}
+	
+	NativeFn::create(Keys(None).wrap())
+}
+
+fn values() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Values(NatSelf);
+	
+	impl Callable for Values {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let values = map.borrow().entries().iter().map(|(_, val)| val.clone()).collect();
+			List::new(values).wrap()
+		}
+	}
+	
+	NativeFn::create(Values(None).wrap())
+}
+
+fn entries() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Entries(NatSelf);
+	
+	impl Callable for Entries {
+		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
+		
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let map_ref = self.0.clone().unwrap();
+			let map = castf!(map map_ref.borrow());
+			let entries = map.borrow().entries().iter().map(|(key, val)| List::new(vec![key.to_value(), val.clone()])).collect();
+			List::new(entries).wrap()
+		}
+	}
+	
+	NativeFn::create(Entries(None).wrap())
+}
+
+pub fn map() -> Box<dyn Value> {
+	let mut methods = HashMap::new();
+	
+	let v = vec![
+	("get", get()),
+	("set", set()),
+	("remove", remove()),
+	("contains", contains()),
+	("keys", keys()),
+	("values", values()),
+	("entries", entries()),
+	("size", size()),
+	];
+	
+	for (key, val) in v {
+		methods.insert(key.to_owned(), val.wrap());
+	}
+	
+	Attribute::new(Identifier::native("map", global_id(MAP_ATTR)), methods, ObjectMap::new(), HashSet::new())
+}
@@ -5,6 +5,8 @@ use crate::interpreter::value::Value;
 
 pub mod string;
 pub mod list;
+pub mod map;
 pub mod error;
+pub mod iter;
 
 type NatSelf = Option<Rc<RefCell<Box<dyn Value>>>>;
@@ -1,7 +1,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::{interpreter::{Interpreter, get_index, globals::attributes::NatSelf, value::{Value, macros::{cast, castf}, primitives::{attribute::Attribute, bool::Bool, callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, number::Number, string::Str, object::ObjectMap}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}, ast::identifier::Identifier};
+use crate::{interpreter::{Interpreter, get_index, globals::attributes::{NatSelf, error::PARSE_ERROR}, value::{Value, macros::{cast, castf}, primitives::{attribute::Attribute, bool::Bool, callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, number::Number, string::Str, object::ObjectMap}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}, ast::identifier::Identifier};
 
 pub const STRING_ATTR: &str = "String";
 
@@ -11,7 +11,7 @@ fn is_num() -> Box<dyn Value> {
 	impl Callable for IsNum {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
 			Bool::new(castf!(str str_ref.borrow()).parse::<f64>().is_ok()).wrap()
 		}
@@ -26,11 +26,12 @@ fn to_num() -> Box<dyn Value> {
 	impl Callable for ToNum {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
-			match castf!(str str_ref.borrow()).parse::<f64>() {
+			let str = castf!(str str_ref.borrow());
+			match str.parse::<f64>() {
 				Ok(n) => Number::new(n),
-				Err(_) => Error::new(Str::from("Cannot convert to number"))
+				Err(_) => Error::of_kind(PARSE_ERROR, format!("Cannot convert '{}' to a number", str), pos)
 			}.wrap()
 		}
 	}
@@ -44,7 +45,7 @@ fn uppercase() -> Box<dyn Value> {
 	impl Callable for Uppercase {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
 			Str::new(
 				castf!(str str_ref.borrow())
@@ -64,7 +65,7 @@ fn lowercase() -> Box<dyn Value> {
 	impl Callable for Lowercase {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
 			Str::new(
 				castf!(str str_ref.borrow())
@@ -86,7 +87,7 @@ fn repeat() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let v0 = cast!(num args[0].0.clone());
 			let str_ref = self.0.clone().unwrap();
 			Str::new(
@@ -106,7 +107,7 @@ fn substring() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let v0 = cast!(num args[0].0.clone());
 			let v1 = cast!(num args[1].0.clone());
 			let str_ref = self.0.clone().unwrap();
@@ -130,7 +131,7 @@ fn size() -> Box<dyn Value> {
 	impl Callable for Size {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
 			Number::new(castf!(str str_ref.borrow()).len() as f64).wrap()
 		}
@@ -147,7 +148,7 @@ fn get() -> Box<dyn Value> {
 		
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let p0 = args[0].1.clone();
 			let str_ref = self.0.clone().unwrap();
@@ -172,7 +173,7 @@ fn reverse() -> Box<dyn Value> {
 	impl Callable for Reverse {
 		fn bind(&mut self, binding: Box<dyn Value>) { self.0 = binding.wrap() }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let str_ref = self.0.clone().unwrap();
 			Str::new(castf!(str str_ref.borrow()).chars().rev().map(|c| c.to_string()).collect::<String>()).wrap()
 		}
@@ -200,5 +201,5 @@ pub fn string() -> Box<dyn Value> {
 		methods.insert(key.to_owned(), val.wrap());
 	}
 	
-	Attribute::new(Identifier { name: "string".to_owned(), id: global_id(STRING_ATTR).wrap() }, methods, ObjectMap::new(), HashSet::new())
+	Attribute::new(Identifier::native("string", global_id(STRING_ATTR)), methods, ObjectMap::new(), HashSet::new())
 }
\ No newline at end of file
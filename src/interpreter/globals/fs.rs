@@ -1,7 +1,7 @@
 
 use std::{collections::{HashMap, HashSet}, fs::OpenOptions, io::Write, path::{Path, PathBuf}};
 
-use crate::{interpreter::{Interpreter, value::{Value, primitives::{bool::Bool, callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, object::Object, string::Str}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
+use crate::{interpreter::{Interpreter, globals::attributes::error::IO_ERROR, value::{Value, primitives::{bool::Bool, callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, object::Object, string::Str}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
 
 fn wipe(path: &PathBuf) -> Box<dyn Value> {
 	#[derive(Clone, Debug)] struct WipeFile(PathBuf);
@@ -9,17 +9,17 @@ fn wipe(path: &PathBuf) -> Box<dyn Value> {
 	impl Callable for WipeFile {
     fn arity(&self) -> usize { 0 }
 
-    fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			match OpenOptions::new().write(true).open(&self.0) {
 				Ok(file) => {
 					println!("wiping");
 					if let Err(err) = file.set_len(0) {
-						Error::new(Str::new(err.to_string()))
+						Error::of_kind(IO_ERROR, err.to_string(), pos)
 					} else {
 						ValNone::new()
 					}
 				},
-				Err(err) => Error::new(Str::new(err.to_string()))
+				Err(err) => Error::of_kind(IO_ERROR, err.to_string(), pos)
 			}.wrap()
     }
 	}
@@ -33,19 +33,19 @@ fn writeline(path: &PathBuf) -> Box<dyn Value> {
 	impl Callable for WriteLineFile {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let text = v0.to_string(interpreter, p0)?;
 			match OpenOptions::new().write(true).open(&self.0) {
 				Ok(mut file) => {
 					let original = std::fs::read_to_string(&self.0).unwrap();
 					if let Err(err) = writeln!(file, "{}{}", original, text) {
-						Error::new(Str::new(err.to_string()))
+						Error::of_kind(IO_ERROR, err.to_string(), pos)
 					} else {
 						ValNone::new()
 					}
 				},
-				Err(err) => Error::new(Str::new(err.to_string())),
+				Err(err) => Error::of_kind(IO_ERROR, err.to_string(), pos),
 			}.wrap()
     }
 	}
@@ -59,19 +59,19 @@ fn write(path: &PathBuf) -> Box<dyn Value> {
 	impl Callable for WriteFile {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let text = v0.to_string(interpreter, p0)?;
 			match OpenOptions::new().write(true).open(&self.0) {
 				Ok(mut file) => {
 					let original = std::fs::read_to_string(&self.0).unwrap();
 					if let Err(err) = write!(file, "{}{}", original, text) {
-						Error::new(Str::new(err.to_string()))
+						Error::of_kind(IO_ERROR, err.to_string(), pos)
 					} else {
 						ValNone::new()
 					}
 				},
-				Err(err) => Error::new(Str::new(err.to_string()))
+				Err(err) => Error::of_kind(IO_ERROR, err.to_string(), pos)
 			}.wrap()
     }
 	}
@@ -85,10 +85,10 @@ fn read(path: &PathBuf) -> Box<dyn Value> {
 	impl Callable for ReadFile {
     fn arity(&self) -> usize { 0 }
 
-    fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			match std::fs::read_to_string(&self.0) {
 				Ok(str) => Str::new(str),
-				Err(err) => Error::new(Str::new(err.to_string())),
+				Err(err) => Error::of_kind(IO_ERROR, err.to_string(), pos),
 			}.wrap()
     }
 	}
@@ -123,7 +123,7 @@ pub fn open() -> Box<dyn Value> {
 	impl Callable for Open {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let path_str = v0.to_string(interpreter, p0)?;
 
@@ -133,7 +133,7 @@ pub fn open() -> Box<dyn Value> {
 			if Path::exists(&path) {
 				new_file(path.to_path_buf())
 			} else {
-				Error::new(Str::new("File not found".to_owned()))
+				Error::of_kind(IO_ERROR, format!("File not found: {}", path.display()), pos)
 			}.wrap()
     }
 	}
@@ -147,7 +147,7 @@ pub fn create() -> Box<dyn Value> {
 	impl Callable for Create {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let path_str = v0.to_string(interpreter, p0)?;
 
@@ -155,7 +155,7 @@ pub fn create() -> Box<dyn Value> {
 			path.push(path_str);
 
 			if let Err(err) = std::fs::File::create(&path) {
-				Error::new(Str::new(err.to_string()))
+				Error::of_kind(IO_ERROR, err.to_string(), pos)
 			} else {
 				new_file(path.to_path_buf())
 			}.wrap()
@@ -171,7 +171,7 @@ pub fn exists() -> Box<dyn Value> {
 	impl Callable for Exists {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let path_str = v0.to_string(interpreter, p0)?;
 			let mut path = interpreter.root_path.clone();
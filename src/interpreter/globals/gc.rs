@@ -0,0 +1,56 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{interpreter::{Interpreter, value::{Value, gc, primitives::{callable::{Callable, nativefn::NativeFn}, map::{Map, MapData, MapKey}, number::Number, object::Object}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
+
+fn collect() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Collect;
+
+	impl Callable for Collect {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			Number::new(gc::collect() as f64).wrap()
+		}
+	}
+
+	NativeFn::create(Collect.wrap())
+}
+
+fn stats() -> Box<dyn Value> {
+	#[derive(Clone, Debug)] struct Stats;
+
+	impl Callable for Stats {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+			let stats = gc::stats();
+			let v = vec![
+				("lists", stats.lists),
+				("maps", stats.maps),
+				("fields", stats.fields),
+				("functions", stats.functions),
+				("collections", stats.collections),
+				("freed", stats.freed),
+			];
+
+			let mut map = MapData::default();
+			for (key, val) in v {
+				map.insert(MapKey::Str(key.to_owned()), Number::new(val as f64));
+			}
+			Map::new(map).wrap()
+		}
+	}
+
+	NativeFn::create(Stats.wrap())
+}
+
+pub fn gc() -> Box<dyn Value> {
+	let mut map = HashMap::new();
+
+	let v = vec![
+		("collect", collect()),
+		("stats", stats()),
+	];
+
+	for (key, val) in v {
+		map.insert(key.to_owned(), val.wrap());
+	}
+
+	Object::new(map, HashSet::new())
+}
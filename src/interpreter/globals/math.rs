@@ -1,7 +1,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::{interpreter::{Interpreter, value::{Value, macros::cast, primitives::{callable::{Callable, nativefn::NativeFn}, error::Error, number::Number, object::Object, string::Str}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
+use crate::{interpreter::{Interpreter, globals::attributes::error::VALUE_ERROR, value::{Value, macros::cast, primitives::{callable::{Callable, nativefn::NativeFn}, error::Error, number::Number, object::Object}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
 
 fn sin() -> Box<dyn Value> {
 	#[derive(Clone, Debug)] struct Sin;
@@ -9,7 +9,7 @@ fn sin() -> Box<dyn Value> {
 	impl Callable for Sin {
     fn arity(&self) -> usize { 1 }
 
-    fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+    fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.sin()).wrap()
     }
@@ -24,7 +24,7 @@ fn cos() -> Box<dyn Value> {
 	impl Callable for Cos {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.cos()).wrap()
 		}
@@ -39,7 +39,7 @@ fn tan() -> Box<dyn Value> {
 	impl Callable for Tan {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.tan()).wrap()
 		}
@@ -54,7 +54,7 @@ fn pow() -> Box<dyn Value> {
 	impl Callable for Pow {
 		fn arity(&self) -> usize { 2 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			Number::new(n0.powf(n1)).wrap()
@@ -70,10 +70,10 @@ fn sqrt() -> Box<dyn Value> {
 	impl Callable for Sqrt {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			if n0 < 0.0 {
-				Error::new(Str::from("sqrt of negative numbers is undefined"))
+				Error::of_kind(VALUE_ERROR, "sqrt of negative numbers is undefined".to_owned(), pos)
 			} else {
 				Number::new(n0.sqrt())
 			}.wrap()
@@ -89,7 +89,7 @@ fn floor() -> Box<dyn Value> {
 	impl Callable for Floor {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.floor()).wrap()
 		}
@@ -104,7 +104,7 @@ fn ceil() -> Box<dyn Value> {
 	impl Callable for Ceil {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.ceil()).wrap()
 		}
@@ -119,7 +119,7 @@ fn round() -> Box<dyn Value> {
 	impl Callable for Round {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.round()).wrap()
 		}
@@ -134,7 +134,7 @@ fn abs() -> Box<dyn Value> {
 	impl Callable for Abs {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.abs()).wrap()
 		}
@@ -149,7 +149,7 @@ fn max() -> Box<dyn Value> {
 	impl Callable for Max {
 		fn arity(&self) -> usize { 2 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			Number::new(n0.max(n1)).wrap()
@@ -165,7 +165,7 @@ fn min() -> Box<dyn Value> {
 	impl Callable for Floor {
 		fn arity(&self) -> usize { 2 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			Number::new(n0.min(n1)).wrap()
@@ -181,7 +181,7 @@ fn clamp() -> Box<dyn Value> {
 	impl Callable for Clamp {
 		fn arity(&self) -> usize { 3 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			let n2 = cast!(num args[2].0.clone());
@@ -198,7 +198,7 @@ fn frac() -> Box<dyn Value> {
 	impl Callable for Frac {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.fract()).wrap()
 		}
@@ -213,7 +213,7 @@ fn sign() -> Box<dyn Value> {
 	impl Callable for Sign {
 		fn arity(&self) -> usize { 1 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			Number::new(n0.signum()).wrap()
 		}
@@ -228,7 +228,7 @@ fn lerp() -> Box<dyn Value> {
 	impl Callable for Clamp {
 		fn arity(&self) -> usize { 3 }
 	
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			let n2 = cast!(num args[2].0.clone());
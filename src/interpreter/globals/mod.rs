@@ -1,17 +1,19 @@
 
 mod math;
 mod fs;
+mod gc;
+pub mod assert;
 pub mod attributes;
 
-use std::{collections::{HashMap, HashSet}, io::Write, time::{SystemTime, UNIX_EPOCH}};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, time::{SystemTime, UNIX_EPOCH}};
 
 use ansi_term::Color;
 use rand::{SeedableRng, prelude::StdRng};
 use text_io::try_read;
 
-use crate::{interpreter::{Interpreter, globals::{fs::fs, math::math}, value::{ValueType, macros::{cast, castf}, primitives::{callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, number::Number, object::Object, string::Str, list::List}, messenger::Messenger}, Message}, utils::{result::*, source_pos::SourcePos, wrap::Wrap, global_ids::{global_id}}};
+use crate::{interpreter::{Interpreter, globals::{fs::fs, gc::gc, math::math, assert::{assert, assert_eq, assert_error}}, value::{ValueType, macros::{cast, castf}, primitives::{callable::{Callable, nativefn::NativeFn}, error::Error, none::ValNone, number::Number, object::Object, string::Str, iterator::{Done, Iter, NativeIter}}, messenger::Messenger}, Message}, utils::{result::*, source_pos::SourcePos, wrap::Wrap, global_ids::{global_id}}};
 
-use self::attributes::{string::string, list::list, error::error};
+use self::attributes::{string::string, list::list, map::map, error::{error, error_kind, IO_ERROR, PARSE_ERROR, VALUE_ERROR}, iter::{iter, of}};
 
 use super::value::Value;
 
@@ -21,7 +23,7 @@ fn clock() -> Box<dyn Value> {
 	impl Callable for Clock {
 		fn arity(&self) -> usize { 0 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
 			Number::new(now).wrap()
 		}
@@ -36,10 +38,10 @@ fn write() -> Box<dyn Value> {
 	impl Callable for Write {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone(); 
-			print!("{}", v0.to_string(interpreter, p0)?);
-			let _ = std::io::stdout().flush();
+			let str = v0.to_string(interpreter, p0)?;
+			interpreter.print(&str);
 			ValNone.wrap()
 		}
 	}
@@ -53,9 +55,10 @@ fn writeline() -> Box<dyn Value> {
 	impl Callable for Writeline {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone(); 
-			println!("{}", v0.to_string(interpreter, p0)?);
+			let str = v0.to_string(interpreter, p0)?;
+			interpreter.print(&format!("{}\n", str));
 			ValNone.wrap()
 		}
 	}
@@ -69,9 +72,9 @@ fn debug() -> Box<dyn Value> {
 	impl Callable for Debug {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let v0 = args[0].0.clone(); 
-			println!("{:?}", v0);
+			interpreter.print(&format!("{:?}\n", v0));
 			ValNone.wrap()
 		}
 	}
@@ -84,11 +87,11 @@ fn read() -> Box<dyn Value> {
 	
 	impl Callable for Read {
 		fn arity(&self) -> usize { 0 }
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let in_res: std::result::Result<String, text_io::Error> = try_read!("{}\r\n");
 			match in_res {
 				Ok(str) => Str::new(str),
-				Err(_) => Error::new(Str::from("Invalid console input")),
+				Err(_) => Error::of_kind(IO_ERROR, "Invalid console input".to_owned(), pos),
 			}.wrap()
 		}
 	}
@@ -100,7 +103,7 @@ fn rand() -> Box<dyn Value> {
 	#[derive(Debug, Clone)] struct Rand;
 	
 	impl Callable for Rand {
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			Number::new(rand::random()).wrap()
 		}
 	}
@@ -113,15 +116,11 @@ fn random() -> Box<dyn Value> {
 	
 	impl Callable for Random {
 		
-		fn check_arity(&self, args_in: usize, pos: SourcePos) -> Result<()> {
-			if (0..=1).contains(&args_in) {
-				Ok(())
-			} else {
-				ErrorList::run(format!("Expected 0 or 1 arguments, but got {}", args_in), pos).err()
-			}
-		}
+		fn arity(&self) -> usize { 1 }
+
+		fn optional(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			
 			let rng = if args.len() == 1 {
 				let n0 = cast!(num args[0].0.clone());
@@ -130,16 +129,17 @@ fn random() -> Box<dyn Value> {
 				StdRng::seed_from_u64(rand::random())
 			};
 			
-			#[derive(Clone, Debug)] struct Rng(StdRng);
+			// the generator's state is the only thing that changes between calls
+			#[derive(Clone, Debug)] struct Rng(RefCell<StdRng>);
 			
 			impl Callable for Rng {
 				fn arity(&self) -> usize { 0 }
-				fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
-					Number::new(rand::Rng::gen(&mut self.0)).wrap()
+				fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+					Number::new(rand::Rng::gen(&mut *self.0.borrow_mut())).wrap()
 				}
 			}
 			
-			NativeFn::create(Rng(rng).wrap()).wrap()
+			NativeFn::create(Rng(RefCell::new(rng)).wrap()).wrap()
 		}
 	}
 	
@@ -152,7 +152,7 @@ fn exit() -> Box<dyn Value> {
 	impl Callable for Exit {
 		fn arity(&self) -> usize { 0 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			Messenger::new(Message::Halt).wrap()
 		}
 	}
@@ -166,10 +166,10 @@ fn abort() -> Box<dyn Value> {
 	impl Callable for Exit {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			let str = match v0.get_type() {
-				ValueType::Error => castf!(err v0).to_string(interpreter, p0)?,
+				ValueType::Error => castf!(err v0).data().to_string(interpreter, p0)?,
 				_ => v0.to_string(interpreter, p0)?,
 			};
 			eprintln!("{}: {}", Color::Red.paint("error"), str);
@@ -186,7 +186,7 @@ fn sleep() -> Box<dyn Value> {
 	impl Callable for Sleep {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let d = std::time::Duration::from_secs_f64(n0);
 			std::thread::sleep(d);
@@ -198,17 +198,26 @@ fn sleep() -> Box<dyn Value> {
 }
 
 fn range() -> Box<dyn Value> {
+	#[derive(Debug)] struct Counter { cur: i64, end: i64 }
+	
+	impl NativeIter for Counter {
+		fn next(&mut self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+			if self.cur >= self.end { return None.wrap(); }
+			self.cur += 1;
+			Some(Number::new((self.cur - 1) as f64)).wrap()
+		}
+	}
+	
 	#[derive(Clone, Debug)] struct Range;
 	
 	impl Callable for Range {
 		fn arity(&self) -> usize { 2 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
-			let mut vec = Vec::new();
-			for i in (n0 as i32)..(n1 as i32) { vec.push(Number::new(i as f64)) }
-			List::new(vec).wrap()
+			// numbers are produced as they are asked for, so huge ranges cost nothing up front
+			Iter::new(Counter { cur: n0 as i64, end: n1 as i64 }.wrap()).wrap()
 		}
 	}
 	
@@ -221,7 +230,7 @@ fn _typeof() -> Box<dyn Value> {
 	impl Callable for TypeOf {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			Str::new(args[0].0.clone().get_type().to_string()).wrap()
 		}
 	}
@@ -237,11 +246,11 @@ fn _char() -> Box<dyn Value> {
 	impl Callable for CharFromCode {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			match char::from_u32(n0 as u32) {
 				Some(c) => Str::new(String::from(c)),
-				None => Error::new(Str::from("Invalid char code")),
+				None => Error::of_kind(VALUE_ERROR, format!("{} is not a valid char code", n0), pos),
 			}.wrap()
 		}
 	}
@@ -267,7 +276,7 @@ fn paint() -> Box<dyn Value> {
 	impl Callable for Paint {
 		fn arity(&self) -> usize { 1 }
 		
-		fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let (v0, p0) = args[0].clone();
 			Str::new(self.0.paint(v0.to_string(interpreter, p0)?).to_string()).wrap()
 		}
@@ -278,7 +287,7 @@ fn paint() -> Box<dyn Value> {
 	impl Callable for RGBPaint {
 		fn arity(&self) -> usize { 3 }
 		
-		fn call(&mut self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		fn call(&self, _pos: SourcePos, _interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
 			let n0 = cast!(num args[0].0.clone());
 			let n1 = cast!(num args[1].0.clone());
 			let n2 = cast!(num args[2].0.clone());
@@ -332,14 +341,31 @@ pub fn init_globals() -> HashMap<usize, Box<dyn Value>> {
 	("char", _char()),
 	("paint", paint()),
 	
+	// testing
+	("assert", assert()),
+	("assert_eq", assert_eq()),
+	("assert_error", assert_error()),
+	
 	// std lib	
 	("math", math()),
 	("fs", fs()),
+	("gc", gc()),
 	
 	// attributes
 	("String", string()),
 	("List", list()),
+	("Map", map()),
 	("Error", error()),
+	("Iter", iter()),
+	
+	// error kinds
+	(IO_ERROR, error_kind(IO_ERROR)),
+	(PARSE_ERROR, error_kind(PARSE_ERROR)),
+	(VALUE_ERROR, error_kind(VALUE_ERROR)),
+	
+	// for loops
+	("$iter", of()),
+	("$done", Done::new()),
 	];
 	
 	v.into_iter().map(|(key, val)| (global_id(key), val)).collect()
@@ -2,12 +2,13 @@
 pub mod value;
 pub mod environment;
 pub mod globals;
+pub mod modules;
 
-use std::{collections::{HashMap, HashSet}, path::PathBuf};
+use std::{collections::{HashMap, HashSet}, io::Write, path::PathBuf};
 
-use crate::{ast::{identifier::Identifier, expression::*, statement::*, Block, module::Module}, interpreter::value::{ValueType, macros::{castf, pass_msg, unwrap_msg}, messenger::Messenger, primitives::{bool::Bool, error::Error, none::ValNone, number::Number, object::Object, string::Str, list::List}}, utils::{result::{Result, ErrorList}, source_pos::SourcePos, wrap::Wrap}};
+use crate::{ast::{identifier::Identifier, expression::*, pattern::{Pattern, PatternType}, statement::*, Block, module::Module}, interpreter::value::{ValueType, macros::{castf, pass_msg, unwrap_msg}, messenger::Messenger, primitives::{bool::Bool, error::Error, none::ValNone, number::Number, object::{Object, ObjectMap}, iterator::advance, range::Range, string::Str, list::{List, ListData}, map::{Map, MapData}}}, utils::{result::{CallFrame, Result, ErrorList}, source_pos::SourcePos, wrap::Wrap}, vm};
 
-use self::{environment::Environment, value::{Value, primitives::{callable::{ValCallable, function::{Function, SELF}}, attribute::Attribute}}, globals::init_globals};
+use self::{environment::Environment, modules::Modules, value::{Value, gc, primitives::{callable::{ValCallable, function::Function}, attribute::Attribute}}, globals::init_globals};
 
 pub fn get_index(mut n: f64, len: usize, pos: SourcePos) -> Result<usize> {
 	if n < 0.0 { n += len as f64; }
@@ -18,6 +19,80 @@ pub fn get_index(mut n: f64, len: usize, pos: SourcePos) -> Result<usize> {
 	}
 }
 
+// the bounds and step a range picks out of a vector or string, negative bounds count from the end like indexes
+pub fn get_slice(range: &Range, len: usize, pos: SourcePos) -> Result<(usize, usize, usize)> {
+	if range.step < 1.0 || range.step.fract() != 0.0 {
+		return ErrorList::run("The step of a slice must be a positive whole number".to_owned(), pos).err();
+	}
+	let bound = |n: f64| if n < 0.0 { n + len as f64 } else { n };
+	let start = range.start.map_or(0.0, bound);
+	let end = range.end.map_or(len as f64, |end| if range.inclusive { bound(end) + 1.0 } else { bound(end) });
+	if start < 0.0 || start > len as f64 || end < 0.0 || end > len as f64 {
+		return ErrorList::run("Index out of bounds".to_owned(), pos).err();
+	}
+	(start as usize, (end as usize).max(start as usize), range.step as usize).wrap()
+}
+
+// the part of a vector or string that a range picks out, as a new value of the same kind
+pub fn slice(head: &dyn Value, range: &Range, head_pos: SourcePos, index_pos: SourcePos) -> Result<Box<dyn Value>> {
+	match head.get_type() {
+		ValueType::Vector => {
+			let list = head.to_list(head_pos)?;
+			let list = list.borrow();
+			let (start, end, step) = get_slice(range, list.len(), index_pos)?;
+			List::new(list[start..end].iter().step_by(step).cloned().collect()).wrap()
+		}
+		ValueType::Str => {
+			let chars = head.to_str(head_pos)?.chars().collect::<Vec<_>>();
+			let (start, end, step) = get_slice(range, chars.len(), index_pos)?;
+			Str::new(chars[start..end].iter().step_by(step).collect()).wrap()
+		}
+		typ => ErrorList::run(format!("Cannot index {}", typ), head_pos).err(),
+	}
+}
+
+// replaces the items a range picks out of a vector, a slice without a step can take any number of items
+pub fn assign_slice(list: &ListData, range: &Range, items: Vec<Box<dyn Value>>, pos: SourcePos) -> Result<()> {
+	let (start, end, step) = get_slice(range, list.borrow().len(), pos)?;
+	if step == 1 {
+		list.borrow_mut().splice(start..end, items);
+		return Ok(());
+	}
+	let indexes = (start..end).step_by(step).collect::<Vec<_>>();
+	if indexes.len() != items.len() {
+		return ErrorList::run(format!("Expected {} item{} to replace the slice, found {}", indexes.len(), if indexes.len() == 1 { "" } else { "s" }, items.len()), pos).err();
+	}
+	let mut list = list.borrow_mut();
+	for (i, item) in indexes.into_iter().zip(items) { list[i] = item; }
+	Ok(())
+}
+
+// the items of a list pattern that take one item each, and the rest pattern at its end
+fn split_rest(patterns: &[Pattern]) -> (&[Pattern], Option<&Pattern>) {
+	match patterns {
+		[fixed @ .., Pattern { typ: PatternType::Rest(rest), .. }] => (fixed, Some(rest)),
+		_ => (patterns, None),
+	}
+}
+
+// copies the fields of a spread object into the one being built, its attributes carry over too
+pub fn spread_object(value: &dyn Value, pos: SourcePos, fields: &mut ObjectMap, attributes: &mut HashSet<usize>) -> Result<()> {
+	if value.get_type() != ValueType::Object {
+		return ErrorList::run(format!("Cannot spread {} into an object", value.get_type()), pos).err();
+	}
+	for (key, field) in value.to_obj(pos)? {
+		fields.insert(key, field.borrow().clone().wrap());
+	}
+	attributes.extend(value.get_attributes());
+	Ok(())
+}
+
+// receives the names bound by a pattern, since each backend stores them in its own way
+pub type Binder<'a> = dyn FnMut(&mut Interpreter, &Identifier, Box<dyn Value>) + 'a;
+
+// where the program output goes instead of stdout, a debug adapter needs stdout for itself
+pub type Output = Box<dyn FnMut(&str)>;
+
 #[derive(Clone, Debug)]
 pub enum Message {
 	None,
@@ -28,9 +103,52 @@ pub enum Message {
 	Halt,
 }
 
+// the ways a program can go at an if or a loop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Branch {
+	Then,
+	Else,
+	Iteration,
+	Exit,
+}
+
+// lets tools like the debugger and the profiler follow the program as it runs
+pub trait Hook {
+	// called right before a statement runs, returning false stops the program
+	fn statement(&mut self, interpreter: &mut Interpreter, stmt: &Statement) -> bool;
+	fn finished(&mut self, _pos: SourcePos) {}
+	// body is where the called function starts, if it has any statements
+	fn enter(&mut self, _name: &str, _body: Option<SourcePos>) {}
+	fn leave(&mut self) {}
+	fn branch(&mut self, _pos: SourcePos, _branch: Branch) {}
+	// module declarations are only defined, while the statements of a script block are run
+	fn loaded(&mut self, _decls: &[Statement], _block: &[Statement]) {}
+}
+
+// the statements a for loop adds to fetch each item and stop at the end aren't shown to hooks, only the parts written in the code are
+pub fn is_loop_step(stmt: &Statement) -> bool {
+	match &stmt.typ {
+		StmtType::Scoped(_) => true,
+		StmtType::Declaration(data) => data.name.name == "$next",
+		StmtType::If(data) => matches!(&data.cond.typ, ExprType::Binary(BinaryData { lhs, .. }) if matches!(&lhs.typ, ExprType::Variable(name) if name.name == "$next")),
+		_ => false,
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+	TreeWalk,
+	Bytecode,
+}
+
 pub struct Interpreter {
-	env: Environment,
+	pub env: Environment,
 	pub root_path: PathBuf,
+	pub modules: Modules,
+	pub backend: Backend,
+	pub call_stack: Vec<CallFrame>,
+	pub hook: Option<Box<dyn Hook>>,
+	pub output: Option<Output>,
 }
 
 impl Interpreter {
@@ -39,15 +157,51 @@ impl Interpreter {
 		Self {
 			env: Environment::new(init_globals()),
 			root_path,
+			modules: Modules::default(),
+			backend: Backend::TreeWalk,
+			call_stack: Vec::new(),
+			hook: None,
+			output: None,
+		}
+	}
+
+	pub fn print(&mut self, text: &str) {
+		match self.output {
+			Some(ref mut output) => output(text),
+			None => {
+				print!("{}", text);
+				let _ = std::io::stdout().flush();
+			}
 		}
 	}
 
-	fn execute_block(&mut self, block: Block) -> Result<Message> {
+	fn notify(&mut self, event: impl FnOnce(&mut dyn Hook)) {
+		if let Some(ref mut hook) = self.hook { event(hook.as_mut()); }
+	}
+
+	pub fn traced<T>(&self, res: Result<T>) -> Result<T> {
+		res.map_err(|err| err.with_trace(&self.call_stack))
+	}
+
+	// statements are only cloned as they run, so the rest of a block that returned early is never copied
+	fn execute_block(&mut self, block: &[Statement]) -> Result<Message> {
 		self.env.push_new();
 
 		let mut last_eval = Message::None;
 		for stmt in block {
-			match stmt.accept(self)? {
+			let pos = stmt.pos;
+			let hooked = !is_loop_step(stmt);
+			if let Some(mut hook) = self.hook.take_if(|_| hooked) {
+				let go_on = hook.statement(self, stmt);
+				self.hook = Some(hook);
+				if !go_on {
+					self.env.pop();
+					return Message::Halt.wrap();
+				}
+			}
+			let res = stmt.clone().accept(self);
+			if hooked { self.notify(|hook| hook.finished(pos)); }
+			match res? {
 				Message::None => continue,
 				Message::Eval(val) => last_eval = Message::Eval(unwrap_msg!(val)),
 				msg => {
@@ -60,33 +214,283 @@ impl Interpreter {
 		last_eval.wrap()
 	}
 
+	// tests a value against a pattern, handing every name it binds to `bind`
+	pub fn match_pattern(&mut self, pattern: &Pattern, value: Box<dyn Value>, bind: &mut Binder) -> Result<bool> {
+		let pos = pattern.pos;
+		match &pattern.typ {
+			PatternType::Wildcard => true,
+			PatternType::Binding(name) => {
+				bind(self, name, value);
+				true
+			}
+			PatternType::Literal(lit) => {
+				// unlike ==, a pattern never matches values of another type
+				let expected = self.literal(lit.clone(), pos)?;
+				value.get_type() == expected.get_type() && value.equals(expected, pos, self, pos)?
+			}
+			PatternType::Attribute(attr) => value.has_attr(attr.get_id(), self),
+			PatternType::List(patterns) => {
+				if value.get_type() != ValueType::Vector { return false.wrap(); }
+				let mut items = value.to_list(pos)?.borrow().clone();
+				let (fixed, rest) = split_rest(patterns);
+				if items.len() < fixed.len() || rest.is_none() && items.len() > fixed.len() { return false.wrap(); }
+				let extra = items.split_off(fixed.len());
+				for (pattern, item) in fixed.iter().zip(items) {
+					if !self.match_pattern(pattern, item, bind)? { return false.wrap(); }
+				}
+				match rest {
+					Some(rest) => self.match_pattern(rest, List::new(extra), bind)?,
+					None => true,
+				}
+			}
+			PatternType::Rest(pattern) => self.match_pattern(pattern, value, bind)?,
+			PatternType::Object(fields, attrs) => {
+				if value.get_type() != ValueType::Object { return false.wrap(); }
+				for attr in attrs {
+					if !value.has_attr(attr.get_id(), self) { return false.wrap(); }
+				}
+				let map = value.to_obj(pos)?;
+				for (key, pattern) in fields {
+					let field = match map.get(key) {
+						Some(field) => field.borrow().clone(),
+						None => return false.wrap(),
+					};
+					if !self.match_pattern(pattern, field, bind)? { return false.wrap(); }
+				}
+				true
+			}
+		}.wrap()
+	}
+
+	// binds a pattern like matching does, but a value that doesn't fit is an error at the part it didn't fit
+	pub fn destructure(&mut self, pattern: &Pattern, value: Box<dyn Value>, bind: &mut Binder) -> Result<()> {
+		let pos = pattern.pos;
+		match &pattern.typ {
+			PatternType::List(patterns) => {
+				if value.get_type() != ValueType::Vector {
+					return ErrorList::run(format!("Cannot destructure {} as a vector", value.get_type()), pos).err();
+				}
+				let mut items = value.to_list(pos)?.borrow().clone();
+				let (fixed, rest) = split_rest(patterns);
+				if items.len() < fixed.len() || rest.is_none() && items.len() > fixed.len() {
+					let expected = if rest.is_some() { format!("at least {}", fixed.len()) } else { fixed.len().to_string() };
+					let noun = if fixed.len() == 1 { "item" } else { "items" };
+					return ErrorList::run(format!("Expected a vector with {} {}, found {}", expected, noun, items.len()), pos).err();
+				}
+				let extra = items.split_off(fixed.len());
+				for (pattern, item) in fixed.iter().zip(items) {
+					self.destructure(pattern, item, bind)?;
+				}
+				if let Some(rest) = rest {
+					self.destructure(rest, List::new(extra), bind)?;
+				}
+			}
+			PatternType::Object(fields, attrs) => {
+				if value.get_type() != ValueType::Object {
+					return ErrorList::run(format!("Cannot destructure {} as an object", value.get_type()), pos).err();
+				}
+				for attr in attrs {
+					if !value.has_attr(attr.get_id(), self) {
+						return ErrorList::run(format!("Expected an object that is {}", attr), attr.pos).err();
+					}
+				}
+				let map = value.to_obj(pos)?;
+				for (key, pattern) in fields {
+					let field = match map.get(key) {
+						Some(field) => field.borrow().clone(),
+						None => return ErrorList::run(format!("Property {} is undefined for object", key), pattern.pos).err(),
+					};
+					self.destructure(pattern, field, bind)?;
+				}
+			}
+			PatternType::Rest(pattern) => self.destructure(pattern, value, bind)?,
+			_ => if !self.match_pattern(pattern, value, bind)? {
+				return ErrorList::run("Value doesn't match the pattern".to_owned(), pos).err();
+			}
+		}
+		Ok(())
+	}
+
+	// the values a spread puts in its place, anything a for loop goes through can be spread
+	pub fn spread_items(&mut self, value: Box<dyn Value>, pos: SourcePos) -> Result<Vec<Box<dyn Value>>> {
+		if value.get_type() == ValueType::Vector {
+			return Ok(value.to_list(pos)?.borrow().clone());
+		}
+		let iter = value.to_iter(pos)?;
+		let mut items = Vec::new();
+		while let Some(item) = advance(&iter, self, pos)? { items.push(item); }
+		Ok(items)
+	}
+
+	// evaluates one link of a chain of calls, indexes and fields. an optional link whose head is none
+	// gives nothing, which skips the rest of the chain so it all ends up as none
+	fn link(&mut self, expr: Expression) -> Result<Option<Box<dyn Value>>> {
+		match expr.typ {
+			ExprType::Call(data) => self.call_link(data, expr.pos),
+			ExprType::Index(data) => self.index_link(data, expr.pos),
+			ExprType::FieldGet(data) => self.field_link(data, expr.pos),
+			_ => expr.accept(self).map(Some),
+		}
+	}
+
+	fn call_link(&mut self, data: CallData, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let calee_pos = data.calee.pos;
+		let Some(calee) = self.link(*data.calee)? else { return Ok(None) };
+		let calee = pass_msg!(calee);
+		if data.optional && calee.get_type() == ValueType::None { return Ok(None); }
+		let mut args = Vec::new();
+		for arg in data.args {
+			let arg_pos = arg.pos;
+			match arg.typ {
+				ExprType::Spread(expr) => {
+					let expr_pos = expr.pos;
+					let value = pass_msg!(expr.accept(self)?);
+					args.extend(self.spread_items(value, expr_pos)?.into_iter().map(|value| (value, arg_pos)));
+				}
+				_ => args.push((pass_msg!(arg.accept(self)?), arg_pos)),
+			}
+		}
+		let mut named = Vec::new();
+		for arg in data.named {
+			let value = pass_msg!(arg.expr.accept(self)?);
+			named.push((arg.name, value, arg.pos));
+		}
+		// the call holds its own reference to the function, so it stays alive even if the call reassigns the name it was bound to
+		let function = calee.to_callable(calee_pos)?;
+		if !named.is_empty() {
+			return function.call_named(calee_pos, self, args, named).map(Some);
+		}
+		function.check_arity(args.len(), pos)?;
+		function.call(calee_pos, self, args).map(Some)
+	}
+
+	fn index_link(&mut self, data: IndexData, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let (head_pos, index_pos) = (data.head.pos, data.index.pos);
+		let Some(head_val) = self.link(*data.head)? else { return Ok(None) };
+		let head_val = pass_msg!(head_val);
+		if data.optional && head_val.get_type() == ValueType::None { return Ok(None); }
+		if head_val.get_type() == ValueType::Map {
+			let key = pass_msg!(data.index.accept(self)?).to_key(index_pos)?;
+			let val = head_val.to_map(head_pos)?.borrow().get(&key);
+			return match val {
+				Some(val) => val.wrap(),
+				None => ErrorList::run("Key not found in map".to_owned(), index_pos).err(),
+			};
+		}
+		let index = pass_msg!(data.index.accept(self)?);
+		if index.get_type() == ValueType::Range {
+			return slice(head_val.as_ref(), &index.to_range(index_pos)?, head_pos, index_pos).map(Some);
+		}
+		let list = match head_val.get_type() {
+			ValueType::Vector => head_val.to_list(head_pos)?.borrow().clone(),
+			ValueType::Str => head_val.to_str(head_pos)?.chars().map(|c| Str::new(c.to_string())).collect(),
+			typ => return ErrorList::run(format!("Cannot index {}", typ), head_pos).err()
+		};
+		let index = get_index(index.to_num(index_pos)?, list.len(), index_pos)?;
+		list[index].clone().wrap()
+	}
+
+	fn field_link(&mut self, data: FieldData, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let Some(head) = self.link(*data.head)? else { return Ok(None) };
+		let head = pass_msg!(head);
+		if data.optional && head.get_type() == ValueType::None { return Ok(None); }
+		let field = head.get_field(&data.field, self, pos)?;
+		if field.borrow().get_type() == ValueType::Callable && head.get_type() != ValueType::Attribute {
+			let method = field.borrow().to_callable(pos)?;
+			let mut bound_method = method.cloned();
+			bound_method.bind(head);
+			return ValCallable::new(bound_method.wrap()).wrap()
+		};
+		field.clone().borrow().clone().wrap()
+	}
+
+	fn match_arm(&mut self, arm: MatchArm, value: Box<dyn Value>) -> Result<Option<Box<dyn Value>>> {
+		if !self.match_pattern(&arm.pattern, value, &mut |interpreter, name, value| interpreter.env.define(name, value))? {
+			return Ok(None);
+		}
+		if let Some(guard) = arm.guard {
+			let cond = guard.accept(self)?;
+			if cond.get_type() == ValueType::Messenger { return cond.wrap(); }
+			if !cond.is_truthy() { return Ok(None); }
+		}
+		arm.body.accept(self)?.wrap()
+	}
+
+	fn define_module(&mut self, module: Module) -> Result<()> {
+		if self.backend == Backend::Bytecode { return vm::run_module(self, module); }
+
+		if self.hook.is_some() {
+			let decls = module.env.values().cloned().collect::<Vec<_>>();
+			self.notify(|hook| hook.loaded(&decls, &[]));
+		}
+
+		// imports go first, so the other declarations can already refer to them
+		let (imports, decls): (Vec<_>, Vec<_>) = module.env.into_values().partition(|stmt| matches!(stmt.typ, StmtType::Import(_)));
+		for stmt in imports.into_iter().chain(decls) { stmt.accept(self)?; }
+		Ok(())
+	}
+
 	pub fn interpret_script(&mut self, module: Module, block: Block) -> Result<()> {
-		for stmt in module.env.values().cloned() { stmt.accept(self)?; }
-		self.execute_block(block)?;
+		self.define_module(module)?;
+		match self.backend {
+			Backend::TreeWalk => {
+				self.notify(|hook| hook.loaded(&[], &block));
+				self.execute_block(&block)?;
+			},
+			Backend::Bytecode => vm::run_block(self, block)?,
+		}
 		Ok(())
 	}
 
+	pub fn interpret_repl(&mut self, module: Module, block: Block) -> Result<Message> {
+		self.define_module(module)?;
+
+		let declared = block.iter().flat_map(|stmt| match &stmt.typ {
+			StmtType::Declaration(data) => vec![data.name.get_id()],
+			StmtType::Destructure(data) => data.pattern.bindings().iter().map(Identifier::get_id).collect(),
+			_ => vec![],
+		}).collect::<Vec<_>>();
+
+		let mut last_eval = Message::None;
+		for stmt in block {
+			let msg = match stmt.accept(self) {
+				Ok(msg) => msg,
+				Err(err) => {
+					// the resolver already knows about every declaration in this input, so the ones we didn't reach are bound to none
+					for id in declared {
+						if !self.env.has_id(id) { self.env.define_id(id, ValNone.wrap()); }
+					}
+					return Err(err);
+				}
+			};
+			match msg {
+				Message::None => continue,
+				Message::Eval(val) => last_eval = Message::Eval(unwrap_msg!(val)),
+				msg => return msg.wrap(),
+			}
+		}
+		last_eval.wrap()
+	}
+
 	pub fn interpret(&mut self, module: Module) -> Result<()> {
-		for stmt in module.env.values().cloned() { stmt.accept(self)?; }
-		Ok(())
+		self.define_module(module)
 	}
 
 	pub fn interpret_and_run(&mut self, module: Module, args: Vec<String>) -> Result<()> {
-		for stmt in module.env.values().cloned() {
-			stmt.accept(self)?;
-		}
+		let main_id = module.main_id.clone();
+		self.define_module(module)?;
 
-		if module.main_id.borrow().is_none() {
+		if main_id.borrow().is_none() {
 			return ErrorList::mod_run("Module did not contain a main function".to_owned()).err();
 		}
 
-		let main = self.env.get(module.main_id.borrow().unwrap().clone());
+		let main = self.env.get_id(main_id.borrow().unwrap());
 
 		let args = List::new(args.iter().map(|str| Str::new(str.clone())).collect());
-		let ret = castf!(fun main).borrow_mut().call(SourcePos::new(1, 1), self, vec![(args, SourcePos::new(0, 0))])?;
+		let ret = castf!(fun main).call(SourcePos::new(1, 1), self, vec![(args, SourcePos::new(0, 0))])?;
 
 		if let Ok(_) = ret.to_error(SourcePos::new(1, 1)) {
-			println!("{}", ret.to_string(self, SourcePos::new(1, 1)).unwrap());
+			println!("{}", ret.to_string(self, SourcePos::new(1, 1))?);
 		}
 
 		Ok(())
@@ -96,7 +500,7 @@ impl Interpreter {
 
 impl ExprVisitor<Box<dyn Value>> for Interpreter {
 
-	fn literal(&mut self, data: LiteralData, _pos: SourcePos) -> Result<Box<dyn Value>> {
+	fn literal(&mut self, data: LiteralData, pos: SourcePos) -> Result<Box<dyn Value>> {
 		match data {
 			LiteralData::None => ValNone.wrap(),
 			LiteralData::Str(s) => Str::new(s).wrap(),
@@ -111,19 +515,48 @@ impl ExprVisitor<Box<dyn Value>> for Interpreter {
 			},
 			LiteralData::List(exprs) => {
 				let mut values = Vec::new();
-				for expr in exprs { values.push(expr.accept(self)?) }
+				for expr in exprs {
+					match expr.typ {
+						ExprType::Spread(expr) => {
+							let expr_pos = expr.pos;
+							let value = pass_msg!(expr.accept(self)?);
+							values.extend(self.spread_items(value, expr_pos)?);
+						}
+						_ => values.push(expr.accept(self)?),
+					}
+				}
 				List::new(values).wrap()
 			},
-			LiteralData::Object(map, attrs) => {
+			LiteralData::Map(entries) => {
+				let mut map = MapData::default();
+				for (key, val) in entries {
+					let key_pos = key.pos;
+					let key = pass_msg!(key.accept(self)?).to_key(key_pos)?;
+					map.insert(key, pass_msg!(val.accept(self)?));
+				}
+				Map::new(map).wrap()
+			},
+			LiteralData::Object(map, attrs, spreads) => {
+				let mut attributes = attrs.iter().map(|i| i.get_id()).collect::<HashSet<_>>();
 				let mut value_map = HashMap::new();
-				for (key, expr) in map {
-					value_map.insert(key, expr.accept(self)?.wrap());
+
+				// later members replace the fields of earlier ones
+				let mut members = map.into_iter().map(|(key, expr)| (Some(key), expr)).chain(spreads.into_iter().map(|expr| (None, expr))).collect::<Vec<_>>();
+				members.sort_by_key(|(_, expr)| (expr.pos.lin, expr.pos.col));
+				for (key, expr) in members {
+					match (key, expr.typ) {
+						(None, ExprType::Spread(expr)) => {
+							let expr_pos = expr.pos;
+							let value = pass_msg!(expr.accept(self)?);
+							spread_object(value.as_ref(), expr_pos, &mut value_map, &mut attributes)?;
+						}
+						(key, typ) => { value_map.insert(key.unwrap_or_default(), typ.to_expr(expr.pos).accept(self)?.wrap()); }
+					}
 				}
 
-				let attributes = attrs.iter().map(|i| i.get_id()).collect::<HashSet<_>>();
 				Object::new(value_map, attributes).wrap()
 			}
-			LiteralData::Error(expr) => Error::new(pass_msg!(expr.accept(self)?)).wrap(),
+			LiteralData::Error(expr) => Error::at(pass_msg!(expr.accept(self)?), pos).wrap(),
 		}
 	}
 
@@ -158,11 +591,15 @@ impl ExprVisitor<Box<dyn Value>> for Interpreter {
 	}
 
 	fn logic(&mut self, data: LogicData, _pos: SourcePos) -> Result<Box<dyn Value>> {
-		let left = pass_msg!(data.lhs.accept(self)?).is_truthy();
-		Bool::new(match data.op {
-			LogicOperator::And => if left { pass_msg!(data.rhs.accept(self)?).is_truthy() } else { false },
-			LogicOperator::Or => if left { true } else { pass_msg!(data.rhs.accept(self)?).is_truthy() }
-		}).wrap()
+		let left = pass_msg!(data.lhs.accept(self)?);
+		match data.op {
+			LogicOperator::And => Bool::new(if left.is_truthy() { pass_msg!(data.rhs.accept(self)?).is_truthy() } else { false }).wrap(),
+			LogicOperator::Or => Bool::new(if left.is_truthy() { true } else { pass_msg!(data.rhs.accept(self)?).is_truthy() }).wrap(),
+			LogicOperator::Coalesce => match left.get_type() {
+				ValueType::None | ValueType::Error => data.rhs.accept(self),
+				_ => left.wrap(),
+			},
+		}
 	}
 
 	fn grouping(&mut self, data: Box<Expression>, _pos: SourcePos) -> Result<Box<dyn Value>> {
@@ -170,7 +607,7 @@ impl ExprVisitor<Box<dyn Value>> for Interpreter {
 	}
 
 	fn variable(&mut self, data: Identifier, _pos: SourcePos) -> Result<Box<dyn Value>> {
-		self.env.get(data.get_id()).wrap()
+		self.env.get(&data).wrap()
 	}
 
 	fn lambda(&mut self, data: LambdaData, _pos: SourcePos) -> Result<Box<dyn Value>> {
@@ -179,76 +616,26 @@ impl ExprVisitor<Box<dyn Value>> for Interpreter {
 	}
 
 	fn call(&mut self, data: CallData, pos: SourcePos) -> Result<Box<dyn Value>> {
-		let calee_pos = data.calee.pos;
-		let bound = match data.calee.typ {
-			ExprType::Variable(_) | ExprType::Index(_) | ExprType::FieldGet(_) => true,
-			_ => false,
-		};
-		let calee = pass_msg!(data.calee.accept(self)?);
-		let mut args = Vec::new();
-		for arg in data.args {
-			let arg_pos = arg.pos;
-			args.push((pass_msg!(arg.accept(self)?), arg_pos));
-		}
-		// We need a pointer to the callable because we need multiple mutable borrows of a shared reference
-		// We need a shared reference (Rc<RefCell<dyn Callable>>) to be able to handle closures and interior mutability
-		// We need multiple mutable borrows to handle recursive function calls
-		// This should not cause any issues since the function won't drop itself or it's environment!
-		// Actually... a function can reference the name it is bound too, and therefore can mutate it, causing it to be dropped
-		// A solution that could get rid of the 'unsafe' code (and solve this) could be:
-		// instead of mutating the function reference, we clone it, mutate it's local environemnt, and then assign it to itself after the call is done
-		// HOWEVER, we can only do this if the value is bound in the environment, if the calee is a lambda this would cause a segfault
-		// Additionaly, if a function isn't bound, it can't call itself recursively, so we don't need multiple mutable borrows either way
-		if bound {
-			unsafe {
-				let function = calee.to_callable(calee_pos)?.as_ptr();
-				function.as_ref().unwrap().check_arity(args.len(), pos)?;
-				let ret = function.as_mut().unwrap().call(calee_pos, self, args);
-				ret
-			}
-		} else {
-			let function = calee.to_callable(calee_pos)?;
-			function.borrow().check_arity(args.len(), pos)?;
-			let ret = function.borrow_mut().call(calee_pos, self, args);
-			ret
-		}
+		Ok(self.call_link(data, pos)?.unwrap_or_else(ValNone::new))
 	}
 
-	fn index(&mut self, data: IndexData, _pos: SourcePos) -> Result<Box<dyn Value>> {
-		let (head_pos, index_pos) = (data.head.pos, data.index.pos);
-		let head_val = pass_msg!(data.head.accept(self)?);
-		let list = match head_val.get_type() {
-			ValueType::Vector => head_val.to_list(head_pos)?.borrow().clone(),
-			ValueType::Str => head_val.to_str(head_pos)?.chars().map(|c| Str::new(c.to_string())).collect(),
-			typ => return ErrorList::run(format!("Cannot index {}", typ), head_pos).err()
-		};
-		let index = pass_msg!(data.index.accept(self)?).to_num(index_pos)?;
-		let index = get_index(index, list.len(), index_pos)?;
-		list[index].clone().wrap()
+	fn index(&mut self, data: IndexData, pos: SourcePos) -> Result<Box<dyn Value>> {
+		Ok(self.index_link(data, pos)?.unwrap_or_else(ValNone::new))
 	}
 
 	fn field(&mut self, data: FieldData, pos: SourcePos) -> Result<Box<dyn Value>> {
-		let head = pass_msg!(data.head.accept(self)?);
-		let field = head.get_field(&data.field, self, pos)?;
-		if field.borrow().get_type() == ValueType::Callable && head.get_type() != ValueType::Attribute {
-			let method = field.borrow().to_callable(pos)?;
-			let mut bound_method = method.borrow().cloned();
-			bound_method.bind(head);
-			return ValCallable::new(bound_method.wrap()).wrap()
-		};
-		field.clone().borrow().clone().wrap()
+		Ok(self.field_link(data, pos)?.unwrap_or_else(ValNone::new))
 	}
 
 	fn self_ref(&mut self, pos: SourcePos) -> Result<Box<dyn Value>> {
-		if self.env.has(SELF) {
-			self.env.get(SELF).wrap()
-		} else {
-			ErrorList::run("Unbound self".to_owned(), pos).err()
+		match self.env.receiver() {
+			Some(receiver) => receiver.wrap(),
+			None => ErrorList::run("Unbound self".to_owned(), pos).err(),
 		}
 	}
 
 	fn do_expr(&mut self, block: Block, _pos: SourcePos) -> Result<Box<dyn Value>> {
-		match self.execute_block(block)? {
+		match self.execute_block(&block)? {
 			Message::None => ValNone::new(),
 			Message::Eval(val) => pass_msg!(val),
 			msg => Messenger::new(msg),
@@ -259,11 +646,38 @@ impl ExprVisitor<Box<dyn Value>> for Interpreter {
 		let head = data.expr.accept(self)?;
 		let method_pos = data.method.pos;
 		let method = data.method.accept(self)?.to_callable(method_pos)?;
-		let mut bound_method = method.borrow().cloned();
+		let mut bound_method = method.cloned();
 		bound_method.bind(head);
 		ValCallable::new(bound_method.wrap()).wrap()
 	}
 
+	fn range(&mut self, data: RangeData, _pos: SourcePos) -> Result<Box<dyn Value>> {
+		let mut bound = |expr: Option<Box<Expression>>| -> Result<Option<(Box<dyn Value>, SourcePos)>> {
+			match expr {
+				Some(expr) => { let expr_pos = expr.pos; Ok(Some((expr.accept(self)?, expr_pos))) }
+				None => Ok(None),
+			}
+		};
+		let (start, end, step) = (bound(data.start)?, bound(data.end)?, bound(data.step)?);
+		Range::from_bounds(start, end, data.inclusive, step)
+	}
+
+	fn spread(&mut self, _expr: Box<Expression>, pos: SourcePos) -> Result<Box<dyn Value>> {
+		ErrorList::run("Spread syntax can only be used in lists, objects and calls".to_owned(), pos).err()
+	}
+
+	fn match_expr(&mut self, data: MatchData, _pos: SourcePos) -> Result<Box<dyn Value>> {
+		let value = pass_msg!(data.value.accept(self)?);
+		for arm in data.arms {
+			// each arm gets its own scope for the names its pattern binds
+			self.env.push_new();
+			let res = self.match_arm(arm, value.clone());
+			self.env.pop();
+			if let Some(val) = res? { return val.wrap(); }
+		}
+		ValNone.wrap()
+	}
+
 }
 
 impl StmtVisitor<Message> for Interpreter {
@@ -278,13 +692,19 @@ impl StmtVisitor<Message> for Interpreter {
 		// the resolver allows it (and it should), but here the name is only defined after the r-value is evaluated
 		// self.env.define(data.name.get_id(), ValNone.wrap()) // <- this could be a solution, assign none to the symbol, and after evaluating the r-value we re-assign it
 		let val = unwrap_msg!(data.expr.accept(self)?);
-		self.env.define(data.name.get_id(), val);
+		self.env.define(&data.name, val);
+		Message::None.wrap()
+	}
+
+	fn destructure(&mut self, data: DestructureData, _pos: SourcePos) -> Result<Message> {
+		let val = unwrap_msg!(data.expr.accept(self)?);
+		self.destructure(&data.pattern, val, &mut |interpreter, name, value| interpreter.env.define(name, value))?;
 		Message::None.wrap()
 	}
 
 	fn func_declaration(&mut self, data: FunctionData, _pos: SourcePos) -> Result<Message> {
 		let func = Function::new(data.name.get_name().wrap(), self.env.clone(), data.params, data.body);
-		self.env.define(data.name.get_id(), ValCallable::new(func.wrap()));
+		self.env.define(&data.name, ValCallable::new(func.wrap()));
 		Message::None.wrap()
 	}
 
@@ -302,7 +722,13 @@ impl StmtVisitor<Message> for Interpreter {
 
 		let attrs = data.attributes.iter().map(|i| i.get_id()).collect();
 
-		self.env.define(data.name.get_id(), Attribute::new(data.name, methods, fields, attrs));
+		self.env.define(&data.name.clone(), Attribute::new(data.name, methods, fields, attrs));
+		Message::None.wrap()
+	}
+
+	fn import(&mut self, data: ImportData, pos: SourcePos) -> Result<Message> {
+		let namespace = self.import_module(&data.path, pos)?;
+		self.env.define(&data.name, namespace);
 		Message::None.wrap()
 	}
 
@@ -311,25 +737,36 @@ impl StmtVisitor<Message> for Interpreter {
 		loop {
 			match data.head.typ {
 				ExprType::Variable(name) => {
-					self.env.assign(name.get_id(), val);
+					self.env.assign(&name, val);
 					return Message::None.wrap();
 				},
-				ExprType::Index(IndexData { head: ihead, index }) => {
+				ExprType::Index(IndexData { head: ihead, index, .. }) => {
 					let h_pos = ihead.pos;
 					let head = ihead.accept(self)?;
+					if head.get_type() == ValueType::Map {
+						let i_pos = index.pos;
+						let key = unwrap_msg!(index.accept(self)?).to_key(i_pos)?;
+						head.to_map(h_pos)?.borrow_mut().insert(key, val);
+						return Message::None.wrap();
+					}
 					let list = if let ValueType::Vector = head.get_type() {
 						castf!(vec head.clone())
 					} else {
 						return ErrorList::run("Invalid assignment target".to_owned(), h_pos).err()
 					};
 					let i_pos = index.pos;
-					let index = unwrap_msg!(index.accept(self)?).to_num(i_pos)?;
-					let index = get_index(index, list.borrow().len(), i_pos)?;
+					let index = unwrap_msg!(index.accept(self)?);
+					if index.get_type() == ValueType::Range {
+						let items = self.spread_items(val, i_pos)?;
+						assign_slice(&list, &index.to_range(i_pos)?, items, i_pos)?;
+						return Message::None.wrap();
+					}
+					let index = get_index(index.to_num(i_pos)?, list.borrow().len(), i_pos)?;
 					list.borrow_mut().remove(index);
 					list.borrow_mut().insert(index, val);
 					return Message::None.wrap();
 				},
-				ExprType::FieldGet(FieldData { head: fhead, field }) => {
+				ExprType::FieldGet(FieldData { head: fhead, field, .. }) => {
 					let h_pos = fhead.pos;
 					let map = fhead.accept(self)?.to_obj(h_pos)?;
 					if let Some(cur) = map.get(&field) {
@@ -344,19 +781,25 @@ impl StmtVisitor<Message> for Interpreter {
 		}
 	}
 
-	fn if_stmt(&mut self, data: IfData, _pos: SourcePos) -> Result<Message> {
+	fn if_stmt(&mut self, data: IfData, pos: SourcePos) -> Result<Message> {
 		if unwrap_msg!(data.cond.accept(self)?).is_truthy() {
-			self.execute_block(data.then_block)
+			self.notify(|hook| hook.branch(pos, Branch::Then));
+			self.execute_block(&data.then_block)
 		} else {
-			self.execute_block(data.else_block)
+			self.notify(|hook| hook.branch(pos, Branch::Else));
+			self.execute_block(&data.else_block)
 		}
 	}
 
-	fn loop_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<Message> {
+	fn loop_stmt(&mut self, block: Block, pos: SourcePos) -> Result<Message> {
 		loop {
-			match self.execute_block(block.clone())? {
-				Message::None | Message::Continue | Message::Eval(_) => continue,
-				Message::Break => return Message::None.wrap(),
+			gc::collect_if_needed();
+			match self.execute_block(&block)? {
+				Message::None | Message::Continue | Message::Eval(_) => self.notify(|hook| hook.branch(pos, Branch::Iteration)),
+				Message::Break => {
+					self.notify(|hook| hook.branch(pos, Branch::Exit));
+					return Message::None.wrap();
+				}
 				msg => return msg.wrap(),
 			}
 		}
@@ -376,7 +819,7 @@ impl StmtVisitor<Message> for Interpreter {
 	}
 
 	fn scoped_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<Message> {
-		self.execute_block(block)
+		self.execute_block(&block)
 	}
 
 }
@@ -0,0 +1,122 @@
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}};
+
+use crate::{ast::{module::Module, statement::StmtType}, lexer::{Lexer, LexerResult}, parser::Parser, semantics::{checker::Checker, optimizer::Optimizer, resolver::Resolver}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+use super::{Interpreter, value::{Value, primitives::object::Object}};
+
+#[derive(Debug, Default)]
+pub struct Modules {
+	next_id: usize,
+	loaded: HashMap<PathBuf, Box<dyn Value>>,
+	loading: Vec<PathBuf>,
+}
+
+impl Modules {
+
+	pub fn reserve(&mut self, max_id: usize) {
+		self.next_id = self.next_id.max(max_id + 1);
+	}
+
+	pub fn next_id(&self) -> usize {
+		self.next_id
+	}
+
+	// the file a program starts from is loading for as long as it runs, so a module importing it back is a cycle
+	pub fn enter(&mut self, path: &Path) {
+		if let Ok(path) = path.canonicalize() { self.loading.push(path); }
+	}
+
+	fn cycle(&self, path: &PathBuf) -> String {
+		let start = self.loading.iter().position(|p| p == path).unwrap_or(0);
+		self.loading[start..].iter().chain([path])
+			.map(|p| p.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default())
+			.collect::<Vec<_>>()
+			.join(" -> ")
+	}
+
+}
+
+fn compile(path: &Path, base: usize) -> Result<(Module, usize)> {
+	let lexer = Lexer::from_file(path.to_str().unwrap_or_default()).map_err(|err| ErrorList::sys(err.to_string()))?;
+	let LexerResult { tokens, directives, mut errors, .. } = lexer.scan_tokens();
+
+	if directives.contains("script") {
+		return ErrorList::mod_comp("Cannot import a script".to_owned()).err();
+	}
+
+	let mut module = Parser::new(tokens).module()?;
+
+	let mut resolver = Resolver::with_base(base);
+	errors.try_append(resolver.resolve(&module));
+	errors.try_append(Checker::new().check(&module));
+	errors.if_empty(())?;
+
+	Optimizer.optimize(&mut module)?;
+
+	(module, resolver.max_id()).wrap()
+}
+
+impl Interpreter {
+
+	// paths are relative to the module doing the import, or to the root when there's no file, like in the repl
+	fn module_path(&self, path: &str, pos: SourcePos) -> Result<PathBuf> {
+		let mut full = match self.modules.loading.last().and_then(|importer| importer.parent()) {
+			Some(dir) => dir.to_path_buf(),
+			None => self.root_path.clone(),
+		};
+		full.push(path);
+		if full.extension().is_none() { full.set_extension("mars"); }
+
+		match full.canonicalize() {
+			Ok(path) => path.wrap(),
+			Err(_) => ErrorList::run(format!("Module '{}' not found", path), pos).err(),
+		}
+	}
+
+	pub fn import_module(&mut self, path: &str, pos: SourcePos) -> Result<Box<dyn Value>> {
+		let full = self.module_path(path, pos)?;
+
+		if let Some(namespace) = self.modules.loaded.get(&full) {
+			return namespace.clone().wrap();
+		}
+
+		if self.modules.loading.contains(&full) {
+			return ErrorList::run(format!("Import cycle detected: {}", self.modules.cycle(&full)), pos).err();
+		}
+
+		let (module, max_id) = compile(&full, self.modules.next_id())
+			.map_err(|errors| {
+				// the original errors still point into the imported file
+				let mut list = ErrorList::run(format!("Could not import '{}'", path), pos);
+				list.append(errors);
+				list
+			})?;
+		self.modules.reserve(max_id);
+
+		let names = module.env.iter().filter_map(|(id, stmt)| match stmt.typ {
+			StmtType::FuncDeclaration(_) | StmtType::AttrDeclaration(_) => Some((id.get_name(), id.get_id())),
+			_ => None,
+		}).collect::<Vec<_>>();
+
+		self.modules.loading.push(full.clone());
+		let module_env = self.env.for_module();
+		let prev = std::mem::replace(&mut self.env, module_env);
+		let res = self.define_module(module);
+		let module_env = std::mem::replace(&mut self.env, prev);
+		self.modules.loading.pop();
+		res?;
+
+		let mut fields = HashMap::new();
+		for (name, id) in names {
+			let val = module_env.get_id(id);
+			// attributes are looked up by id from anywhere, so the module's declarations must live in the global scope
+			self.env.define_global(id, val.clone());
+			fields.insert(name, val.wrap());
+		}
+
+		let namespace = Object::new(fields, HashSet::new());
+		self.modules.loaded.insert(full, namespace.clone());
+		namespace.wrap()
+	}
+
+}
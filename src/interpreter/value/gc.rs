@@ -0,0 +1,264 @@
+use std::{cell::RefCell, collections::{HashMap, HashSet}, rc::{Rc, Weak}};
+
+use crate::interpreter::environment::Scope;
+
+use super::{Value, ValueRef, primitives::{callable::{Callable, CallableRef}, iterator::{IterRef, NativeIter}, list::ListData, map::{MapData, MapRef}, none::ValNone}};
+
+// Values are reference counted, which frees everything except reference cycles (an object holding itself,
+// a closure stored in a scope it captured, ...). Every shared allocation that can be part of a cycle is
+// tracked here, and collecting finds the ones that are only kept alive by other tracked allocations:
+// whatever holds more references than the tracked graph accounts for is referenced from outside of it
+// (the stack, the environment, a native), and anything those can't reach is garbage.
+
+pub type Tracer<'a> = dyn FnMut(Node) + 'a;
+
+#[derive(Clone)]
+pub enum Node {
+	List(ListData),
+	Map(MapRef),
+	Cell(ValueRef),
+	Scope(Scope),
+	Callable(CallableRef),
+	Iter(IterRef),
+}
+
+enum WeakNode {
+	List(Weak<RefCell<Vec<Box<dyn Value>>>>),
+	Map(Weak<RefCell<MapData>>),
+	Cell(Weak<RefCell<Box<dyn Value>>>),
+	Scope(Weak<RefCell<Vec<Box<dyn Value>>>>),
+	Callable(Weak<dyn Callable>),
+	Iter(Weak<RefCell<Box<dyn NativeIter>>>),
+}
+
+impl Node {
+	fn id(&self) -> usize {
+		match self {
+			Node::List(rc) => Rc::as_ptr(rc) as *const () as usize,
+			Node::Map(rc) => Rc::as_ptr(rc) as *const () as usize,
+			Node::Cell(rc) => Rc::as_ptr(rc) as *const () as usize,
+			Node::Scope(rc) => Rc::as_ptr(rc) as *const () as usize,
+			Node::Callable(rc) => Rc::as_ptr(rc) as *const () as usize,
+			Node::Iter(rc) => Rc::as_ptr(rc) as *const () as usize,
+		}
+	}
+
+	fn strong_count(&self) -> usize {
+		match self {
+			Node::List(rc) => Rc::strong_count(rc),
+			Node::Map(rc) => Rc::strong_count(rc),
+			Node::Cell(rc) => Rc::strong_count(rc),
+			Node::Scope(rc) => Rc::strong_count(rc),
+			Node::Callable(rc) => Rc::strong_count(rc),
+			Node::Iter(rc) => Rc::strong_count(rc),
+		}
+	}
+
+	fn downgrade(&self) -> WeakNode {
+		match self {
+			Node::List(rc) => WeakNode::List(Rc::downgrade(rc)),
+			Node::Map(rc) => WeakNode::Map(Rc::downgrade(rc)),
+			Node::Cell(rc) => WeakNode::Cell(Rc::downgrade(rc)),
+			Node::Scope(rc) => WeakNode::Scope(Rc::downgrade(rc)),
+			Node::Callable(rc) => WeakNode::Callable(Rc::downgrade(rc)),
+			Node::Iter(rc) => WeakNode::Iter(Rc::downgrade(rc)),
+		}
+	}
+
+	// hands every reference this node holds to `visit`, or returns false if it's in use and can't be looked into
+	fn trace(&self, visit: &mut Tracer) -> bool {
+		match self {
+			Node::List(rc) | Node::Scope(rc) => match rc.try_borrow() {
+				Ok(values) => values.iter().for_each(|val| val.trace(visit)),
+				Err(_) => return false,
+			},
+			Node::Map(rc) => match rc.try_borrow() {
+				Ok(map) => map.entries().iter().for_each(|(_, val)| val.trace(visit)),
+				Err(_) => return false,
+			},
+			Node::Cell(rc) => match rc.try_borrow() {
+				Ok(val) => val.trace(visit),
+				Err(_) => return false,
+			},
+			Node::Callable(rc) => rc.trace(visit),
+			Node::Iter(rc) => match rc.try_borrow() {
+				Ok(iter) => iter.trace(visit),
+				Err(_) => return false,
+			},
+		}
+		true
+	}
+
+	// empties a garbage node, which breaks the cycles it is part of; the contents are returned so they're dropped
+	// after collecting, once nothing is borrowed anymore
+	fn clear(&self) -> Vec<Box<dyn Value>> {
+		match self {
+			Node::List(rc) | Node::Scope(rc) => std::mem::take(&mut *rc.borrow_mut()),
+			Node::Map(rc) => std::mem::take(&mut *rc.borrow_mut()).into_values(),
+			Node::Cell(rc) => vec![std::mem::replace(&mut *rc.borrow_mut(), ValNone::new())],
+			Node::Callable(_) | Node::Iter(_) => Vec::new(),
+		}
+	}
+}
+
+impl WeakNode {
+	fn upgrade(&self) -> Option<Node> {
+		match self {
+			WeakNode::List(weak) => weak.upgrade().map(Node::List),
+			WeakNode::Map(weak) => weak.upgrade().map(Node::Map),
+			WeakNode::Cell(weak) => weak.upgrade().map(Node::Cell),
+			WeakNode::Scope(weak) => weak.upgrade().map(Node::Scope),
+			WeakNode::Callable(weak) => weak.upgrade().map(Node::Callable),
+			WeakNode::Iter(weak) => weak.upgrade().map(Node::Iter),
+		}
+	}
+
+	fn is_alive(&self) -> bool {
+		match self {
+			WeakNode::List(weak) => weak.strong_count() > 0,
+			WeakNode::Map(weak) => weak.strong_count() > 0,
+			WeakNode::Cell(weak) => weak.strong_count() > 0,
+			WeakNode::Scope(weak) => weak.strong_count() > 0,
+			WeakNode::Callable(weak) => weak.strong_count() > 0,
+			WeakNode::Iter(weak) => weak.strong_count() > 0,
+		}
+	}
+}
+
+// collections start once this many allocations were tracked since the last one
+const MIN_THRESHOLD: usize = 10_000;
+
+struct Heap {
+	tracked: Vec<WeakNode>,
+	since_last: usize,
+	threshold: usize,
+	collections: usize,
+	freed: usize,
+}
+
+thread_local! {
+	static HEAP: RefCell<Heap> = const { RefCell::new(Heap { tracked: Vec::new(), since_last: 0, threshold: MIN_THRESHOLD, collections: 0, freed: 0 }) };
+}
+
+pub fn track(node: Node) {
+	HEAP.with(|heap| {
+		let mut heap = heap.borrow_mut();
+		heap.tracked.push(node.downgrade());
+		heap.since_last += 1;
+	})
+}
+
+// called where the interpreters can safely stop for a moment, like loop iterations and calls
+pub fn collect_if_needed() {
+	let needed = HEAP.with(|heap| {
+		let heap = heap.borrow();
+		heap.since_last >= heap.threshold
+	});
+	if needed { collect(); }
+}
+
+// frees every allocation that's only reachable from reference cycles, and returns how many were freed
+pub fn collect() -> usize {
+	let tracked = HEAP.with(|heap| std::mem::take(&mut heap.borrow_mut().tracked));
+
+	// find everything reachable from the tracked allocations, and how they refer to each other
+	let mut nodes: HashMap<usize, Node> = HashMap::new();
+	let mut pending = Vec::new();
+	let mut kept = Vec::new();
+	for node in tracked.iter().filter_map(WeakNode::upgrade) {
+		let id = node.id();
+		if let std::collections::hash_map::Entry::Vacant(entry) = nodes.entry(id) {
+			kept.push(node.downgrade());
+			entry.insert(node);
+			pending.push(id);
+		}
+	}
+	drop(tracked);
+
+	let mut edges: HashMap<usize, Vec<usize>> = HashMap::new();
+	let mut opaque = HashSet::new();
+	while let Some(id) = pending.pop() {
+		let mut children = Vec::new();
+		let traced = nodes[&id].trace(&mut |child: Node| children.push(child));
+		if !traced { opaque.insert(id); }
+		for child in children {
+			let child_id = child.id();
+			edges.entry(id).or_default().push(child_id);
+			if let std::collections::hash_map::Entry::Vacant(entry) = nodes.entry(child_id) {
+				entry.insert(child);
+				pending.push(child_id);
+			}
+		}
+	}
+
+	let mut internal: HashMap<usize, usize> = HashMap::new();
+	for child in edges.values().flatten() { *internal.entry(*child).or_default() += 1; }
+
+	// `nodes` holds one reference to each of them, on top of the ones counted as internal
+	let mut live: HashSet<usize> = HashSet::new();
+	let mut roots: Vec<usize> = nodes.iter()
+		.filter(|(id, node)| opaque.contains(*id) || node.strong_count() - 1 > internal.get(*id).cloned().unwrap_or(0))
+		.map(|(id, _)| *id)
+		.collect();
+	while let Some(id) = roots.pop() {
+		if !live.insert(id) { continue; }
+		if let Some(children) = edges.get(&id) {
+			roots.extend(children.iter().filter(|child| !live.contains(*child)));
+		}
+	}
+
+	let mut contents = Vec::new();
+	let mut freed = 0;
+	for (id, node) in nodes.iter() {
+		if !live.contains(id) {
+			contents.append(&mut node.clear());
+			freed += 1;
+		}
+	}
+	drop(nodes);
+	drop(contents);
+
+	HEAP.with(|heap| {
+		let mut heap = heap.borrow_mut();
+		let mut tracked = kept;
+		tracked.retain(WeakNode::is_alive);
+		// anything tracked while collecting was pushed to the emptied list
+		tracked.append(&mut heap.tracked);
+		heap.threshold = MIN_THRESHOLD.max(tracked.len());
+		heap.tracked = tracked;
+		heap.since_last = 0;
+		heap.collections += 1;
+		heap.freed += freed;
+	});
+
+	freed
+}
+
+pub struct HeapStats {
+	pub lists: usize,
+	pub maps: usize,
+	pub fields: usize,
+	pub functions: usize,
+	pub collections: usize,
+	pub freed: usize,
+}
+
+pub fn stats() -> HeapStats {
+	HEAP.with(|heap| {
+		let heap = heap.borrow();
+		let mut stats = HeapStats { lists: 0, maps: 0, fields: 0, functions: 0, collections: heap.collections, freed: heap.freed };
+		// values created before the last collection can be tracked more than once
+		let mut seen = HashSet::new();
+		for node in heap.tracked.iter().filter_map(WeakNode::upgrade) {
+			if !seen.insert(node.id()) { continue; }
+			match node {
+				Node::List(_) => stats.lists += 1,
+				Node::Map(_) => stats.maps += 1,
+				Node::Cell(_) => stats.fields += 1,
+				Node::Callable(_) => stats.functions += 1,
+				Node::Scope(_) | Node::Iter(_) => (),
+			}
+		}
+		stats
+	})
+}
@@ -79,6 +79,13 @@ macro_rules! castf {
 			_ => panic!("Cannot cast value to vector")
 		}
 	}};
+	(map $val:expr) => {{
+		let bind = $val;
+		match bind.get_type() {
+			crate::interpreter::value::ValueType::Map => bind.to_map(SourcePos::new(0, 0)).unwrap(),
+			_ => panic!("Cannot cast value to map")
+		}
+	}};
 	(obj $val:expr) => {{
 		let bind = $val;
 		match bind.get_type() {
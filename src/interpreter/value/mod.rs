@@ -3,7 +3,7 @@ use std::{cell::RefCell, fmt::{Debug, Display}, rc::Rc};
 
 use crate::{interpreter::value::macros::castf, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
 
-use self::primitives::{attribute::Attribute, callable::Callable, object::ObjectMap, list::ListData};
+use self::{gc::Tracer, primitives::{attribute::Attribute, error::Error, callable::CallableRef, object::ObjectMap, list::ListData, map::{MapKey, MapRef}, iterator::IterRef, range::Range}};
 
 use super::{Interpreter, Message};
 
@@ -11,15 +11,17 @@ pub mod macros;
 
 pub mod primitives;
 pub mod messenger;
+pub mod gc;
 
 pub type ValueRef = Rc<RefCell<Box<dyn Value>>>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValueType {
 	None, Num, Str, Bool, 
-	Vector, Object, Callable,
+	Vector, Map, Object, Callable,
 	Error, Messenger,
 	Attribute,
+	Iterator, Range, Done,
 }
 
 impl Display for ValueType {
@@ -30,11 +32,15 @@ impl Display for ValueType {
 			ValueType::Str       => write!(f, "string"),
 			ValueType::Bool      => write!(f, "bool"),
 			ValueType::Vector    => write!(f, "vector"),
+			ValueType::Map       => write!(f, "map"),
 			ValueType::Object    => write!(f, "object"),
 			ValueType::Callable  => write!(f, "callable"),
 			ValueType::Error     => write!(f, "error"),
 			ValueType::Messenger => write!(f, "messenger"),
 			ValueType::Attribute => write!(f, "attribute"),
+			ValueType::Iterator  => write!(f, "iterator"),
+			ValueType::Range     => write!(f, "range"),
+			ValueType::Done      => write!(f, "done"),
 		}
 	}
 }
@@ -46,12 +52,17 @@ pub trait Value : Debug {
 	fn to_num(&self, pos: SourcePos) -> Result<f64> { ErrorList::run("Cannot cast value to number".to_owned(), pos).err() }
 	fn to_str(&self, pos: SourcePos) -> Result<String> { ErrorList::run("Cannot cast value to string".to_owned(), pos).err() }
 	fn to_list(&self, pos: SourcePos) -> Result<ListData> { ErrorList::run("Cannot cast value to vector".to_owned(), pos).err() }
+	fn to_map(&self, pos: SourcePos) -> Result<MapRef> { ErrorList::run("Cannot cast value to map".to_owned(), pos).err() }
 	fn to_obj(&self, pos: SourcePos) -> Result<ObjectMap> { ErrorList::run("Cannot cast value to object".to_owned(), pos).err() }
-	fn to_callable(&self, pos: SourcePos) -> Result<Rc<RefCell<Box<dyn Callable>>>> { ErrorList::run("Cannot cast value to callable".to_owned(), pos).err() }
-	fn to_error(&self, pos: SourcePos) -> Result<Box<dyn Value>> { ErrorList::run("Cannot cast value to error".to_owned(), pos).err() }
+	fn to_callable(&self, pos: SourcePos) -> Result<CallableRef> { ErrorList::run("Cannot cast value to callable".to_owned(), pos).err() }
+	fn to_error(&self, pos: SourcePos) -> Result<Error> { ErrorList::run("Cannot cast value to error".to_owned(), pos).err() }
 	fn to_attr(&self, pos: SourcePos) -> Result<Attribute> { ErrorList::run("Cannot cast value to attribute".to_owned(), pos).err() }
+	fn to_range(&self, pos: SourcePos) -> Result<Range> { ErrorList::run("Cannot cast value to range".to_owned(), pos).err() }
 	
 	fn to_message(&self) -> Message { panic!("Cannot cast value to messenger") }
+
+	fn to_key(&self, pos: SourcePos) -> Result<MapKey> { ErrorList::run(format!("Cannot use {} as a map key", self.get_type()), pos).err() }
+	fn to_iter(&self, pos: SourcePos) -> Result<IterRef> { ErrorList::run(format!("Cannot iterate over {}", self.get_type()), pos).err() }
 	
 	fn is_truthy(&self) -> bool { true }
 	
@@ -63,7 +74,7 @@ pub trait Value : Debug {
 		let attrs = self.get_attributes();
 		let mut cur = attrs.as_slice();
 		while let [ rest @ .., top ] = cur {
-			let attr = interpreter.env.get(*top);
+			let attr = interpreter.env.get_id(*top);
 			match castf!(attr attr).get(field) {
 				Some(method) => return method.wrap(),
 				None => cur = rest,
@@ -93,6 +104,9 @@ pub trait Value : Debug {
 		self.get_attributes().contains(&attr)
 	}
 
+	// hands the collector every shared allocation this value holds on to
+	fn trace(&self, _visit: &mut Tracer) { }
+
 }
 
 impl Clone for Box<dyn Value> {
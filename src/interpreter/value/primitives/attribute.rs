@@ -1,7 +1,7 @@
 
 use std::{cell::RefCell, collections::HashSet, rc::Rc};
 
-use crate::{interpreter::{Interpreter, value::{Value, ValueType, macros::castf}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}, ast::identifier::Identifier};
+use crate::{interpreter::{Interpreter, value::{Value, ValueType, macros::castf, gc::{Node, Tracer}}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}, ast::identifier::Identifier};
 
 use super::object::ObjectMap;
 
@@ -29,12 +29,24 @@ impl Attribute {
 	pub fn super_attrs(&self) -> HashSet<usize> {
 		self.attributes.clone()
 	}
+
+	pub fn method_names(&self) -> Vec<String> {
+		self.methods.keys().cloned().collect()
+	}
+
+	pub fn field_names(&self) -> Vec<String> {
+		self.fields.keys().cloned().collect()
+	}
 }
 
 impl Value for Attribute {
 	fn get_type(&self) -> ValueType { ValueType::Attribute }
 	
 	fn to_attr(&self, _pos: SourcePos) -> Result<Attribute> { self.clone().wrap() }
+
+	fn trace(&self, visit: &mut Tracer) {
+		self.methods.values().chain(self.fields.values()).for_each(|field| visit(Node::Cell(field.clone())));
+	}
 	
 	fn cloned(&self) -> Box<dyn Value> {
 		self.clone().wrap()
@@ -68,7 +80,7 @@ impl Value for Attribute {
 			let mut cur = v.as_slice();
 			while let [ rest @ .., top ] = cur {
 				if *top == attr { return true; }
-				let val = interpreter.env.get(*top);
+				let val = interpreter.env.get_id(*top);
 				let val = castf!(attr val);
 				if find(val.super_attrs(), attr, interpreter) { return true; }
 				cur = rest;
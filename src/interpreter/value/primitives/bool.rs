@@ -1,7 +1,7 @@
 
 use crate::{interpreter::{Interpreter, value::ValueType}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
 
-use super::super::Value;
+use super::{super::Value, map::MapKey};
 
 #[derive(Debug, Clone)]
 pub struct Bool {
@@ -18,6 +18,8 @@ impl Value for Bool {
 	fn get_type(&self) -> ValueType { ValueType::Bool }
 	
 	fn is_truthy(&self) -> bool { self.data }
+
+	fn to_key(&self, _pos: SourcePos) -> Result<MapKey> { MapKey::Bool(self.data).wrap() }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
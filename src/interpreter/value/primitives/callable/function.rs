@@ -1,67 +1,110 @@
+use std::rc::Rc;
 
-use crate::{ast::{identifier::Identifier, Block}, interpreter::{Interpreter, Message, environment::Environment, value::{Value, primitives::none::ValNone, messenger::Messenger}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
+use crate::{ast::{expression::Param, Block}, interpreter::{Interpreter, Message, environment::Environment, value::{Value, ValueType, gc::{self, Tracer}, primitives::none::ValNone, messenger::Messenger}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
 
-use super::Callable;
-
-pub const SELF: usize = 0;
+use super::{Callable, NamedValue, Params};
 
 #[derive(Debug, Clone)]
 pub struct Function {
 	pub name: Option<String>,
 	pub env: Environment,
-	pub params: Vec<Identifier>,
-	pub body: Block,
+	pub params: Vec<Param>,
+	pub signature: Params,
+	// shared between the copies made when binding methods, and only borrowed when called
+	pub body: Rc<Block>,
 }
 
 impl Function {
-	pub fn new(name: Option<String>, env: Environment, params: Vec<Identifier>, body: Block) -> Self {
-		Self { name, env, params, body }
+	pub fn new(name: Option<String>, env: Environment, params: Vec<Param>, body: Block) -> Self {
+		let signature = Params::new(&params);
+		Self { name, env, params, signature, body: Rc::new(body) }
 	}
-}
 
-impl Callable for Function {
-	fn cloned(&self) -> Box<dyn Callable> {
-		Box::new(Function::new(self.name.clone(), self.env.cloned(), self.params.clone(), self.body.clone()))
+	// defaults run in order inside the new scope, so they can use the parameters before them and the names they destructure into
+	fn define_params(&self, interpreter: &mut Interpreter, values: Vec<Option<Box<dyn Value>>>) -> Result<Option<Message>> {
+		for (param, value) in self.params.iter().zip(values) {
+			let value = match (value, &param.default) {
+				(Some(value), _) => value,
+				(None, Some(default)) => default.clone().accept(interpreter)?,
+				(None, None) => ValNone::new(),
+			};
+			if value.get_type() == ValueType::Messenger { return Ok(Some(value.to_message())); }
+			if let Some(ref pattern) = param.pattern {
+				interpreter.destructure(pattern, value.clone(), &mut |interpreter, name, value| interpreter.env.define(name, value))?;
+			}
+			interpreter.env.define(&param.name, value);
+		}
+		Ok(None)
 	}
+}
 
+impl Callable for Function {
 	fn display(&self) -> String {
 		if let Some(ref name) = self.name {
-			format!("<function {}>", name)	
+			format!("<function {}>", name)
 		} else {
 			"<lambda>".to_owned()
 		}
 	}
 
 	fn arity(&self) -> usize {
-		self.params.len()
+		self.signature.arity()
+	}
+
+	fn optional(&self) -> usize {
+		self.signature.optional
 	}
-	
+
+	fn variadic(&self) -> bool {
+		self.signature.rest
+	}
+
 	fn bind(&mut self, binding: Box<dyn Value>) {
-		self.env.define(SELF, binding);
+		self.env.bind(binding);
 	}
-	
-	fn call(&mut self, _pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
-		
+
+	fn trace(&self, visit: &mut Tracer) { self.env.trace(visit) }
+
+	fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		self.call_named(pos, interpreter, args, Vec::new())
+	}
+
+	fn call_named(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>, named: Vec<NamedValue>) -> Result<Box<dyn Value>> {
+
+		gc::collect_if_needed();
+
+		let display = self.display();
+		let values = if named.is_empty() && self.signature.is_plain(args.len()) {
+			args.into_iter().map(|(val, _)| Some(val)).collect()
+		} else {
+			self.signature.bind(&display, args, named, pos)?
+		};
+
 		let prev = interpreter.env.clone();
 		interpreter.env = self.env.clone();
-		
+
 		interpreter.env.push_new();
-		
-		for (iden, (val, _)) in self.params.iter().zip(args.iter()) {
-			interpreter.env.define(iden.get_id(), val.clone())
-		}
-		
-		let ret = match interpreter.execute_block(self.body.clone())? {
+
+		let body = self.body.first().map(|stmt| stmt.pos);
+		interpreter.notify(|hook| hook.enter(&display, body));
+		interpreter.call_stack.push((display, pos));
+		let res = match self.define_params(interpreter, values) {
+			Ok(Some(msg)) => Ok(msg),
+			Ok(None) => interpreter.execute_block(&self.body),
+			Err(err) => Err(err),
+		};
+		let res = interpreter.traced(res);
+		interpreter.call_stack.pop();
+		interpreter.notify(|hook| hook.leave());
+
+		interpreter.env = prev;
+
+		let ret = match res? {
 			Message::Return(val) => val,
 			Message::Halt => Messenger::new(Message::Halt),
 			_ => ValNone.wrap()
 		};
-		
-		interpreter.env.pop();
-		
-		// self.env = interpreter.env.clone();
-		interpreter.env = prev;
-		
+
 		ret.wrap()
 	}
 }
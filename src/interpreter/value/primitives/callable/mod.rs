@@ -2,29 +2,137 @@
 pub mod function;
 pub mod nativefn;
 
-use std::{cell::RefCell, fmt::{Debug, Display}, rc::Rc};
+use std::{fmt::{Debug, Display}, rc::Rc};
 
-use crate::{interpreter::{Interpreter, value::ValueType}, utils::{result::*, source_pos::SourcePos, wrap::Wrap}};
+use crate::{ast::expression::Param, interpreter::{Interpreter, value::{ValueType, gc::{self, Node, Tracer}}}, vm::closure::Closure, utils::{result::*, source_pos::SourcePos, wrap::Wrap}};
 
-use super::super::Value;
+use super::{super::Value, list::List};
 
-pub trait Callable : Debug {
-	fn cloned(&self) -> Box<dyn Callable> { panic!(); }
+// functions are shared and never change once they're created, anything a single call needs lives in its own frame
+pub type CallableRef = Rc<dyn Callable>;
+
+// lets any callable be copied behind a box, so a copy can be bound to a receiver without touching the shared one
+pub trait CallableClone {
+	fn clone_box(&self) -> Box<dyn Callable>;
+}
+
+impl <T : Callable + Clone + 'static> CallableClone for T {
+	fn clone_box(&self) -> Box<dyn Callable> { Box::new(self.clone()) }
+}
+
+pub trait Callable : Debug + CallableClone {
+	fn cloned(&self) -> Box<dyn Callable> { self.clone_box() }
 	fn display(&self) -> String { "<function>".to_owned() }
 
 	fn arity(&self) -> usize { 0 }
+
+	// how many of the last arguments can be left out
+	fn optional(&self) -> usize { 0 }
+
+	// whether any number of arguments can follow the ones in the arity
+	fn variadic(&self) -> bool { false }
 	
 	fn check_arity(&self, args_in: usize, pos: SourcePos) -> Result<()> {
-		if self.arity() == args_in {
-			Ok(())
-		} else {
-			ErrorList::run(format!("Expected {} arguments, but got {}", self.arity(), args_in), pos).err()
+		let (min, max) = (self.arity() - self.optional(), self.arity());
+		if args_in >= min && (args_in <= max || self.variadic()) {
+			return Ok(());
 		}
+		ErrorList::run(arity_message(min, max, self.variadic(), args_in), pos).err()
 	}
 	
 	fn bind(&mut self, _binding: Box<dyn Value>) { }
+
+	// compiled functions are run directly by the vm instead of going through call
+	fn closure(&self) -> Option<Closure> { None }
+
+	fn trace(&self, _visit: &mut Tracer) { }
 	
-	fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>>;
+	fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>>;
+
+	// only mars functions know the names of their parameters
+	fn call_named(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>, named: Vec<NamedValue>) -> Result<Box<dyn Value>> {
+		if let Some((_, _, arg_pos)) = named.first() {
+			return ErrorList::run(format!("{} doesn't take named arguments", self.display()), *arg_pos).err();
+		}
+		self.check_arity(args.len(), pos)?;
+		self.call(pos, interpreter, args)
+	}
+}
+
+// the type checker reports calls with the wrong number of arguments the same way
+pub fn arity_message(min: usize, max: usize, variadic: bool, args_in: usize) -> String {
+	let expected = if variadic {
+		format!("at least {}", min)
+	} else if min == max {
+		min.to_string()
+	} else if min + 1 == max {
+		format!("{} or {}", min, max)
+	} else {
+		format!("{} to {}", min, max)
+	};
+	format!("Expected {} arguments, but got {}", expected, args_in)
+}
+
+pub type NamedValue = (String, Box<dyn Value>, SourcePos);
+
+// the parameters of a mars function, both backends use them to match the arguments of a call
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+	pub names: Vec<String>,
+	pub labels: Vec<String>,
+	pub optional: usize,
+	pub rest: bool,
+}
+
+impl Params {
+
+	pub fn new(params: &[Param]) -> Self {
+		Self {
+			names: params.iter().map(|param| param.name.get_name()).collect(),
+			labels: params.iter().map(Param::label).collect(),
+			optional: params.iter().filter(|param| param.default.is_some()).count(),
+			rest: params.last().is_some_and(|param| param.rest),
+		}
+	}
+
+	// the parameters that aren't the rest one
+	pub fn arity(&self) -> usize {
+		self.names.len() - self.rest as usize
+	}
+
+	// a call that passes every parameter in order needs no matching
+	pub fn is_plain(&self, args_in: usize) -> bool {
+		!self.rest && args_in == self.names.len()
+	}
+
+	// the value of every parameter, none for the ones left out that take their default, the rest one always gets a list
+	pub fn bind(&self, display: &str, args: Vec<(Box<dyn Value>, SourcePos)>, named: Vec<NamedValue>, pos: SourcePos) -> Result<Vec<Option<Box<dyn Value>>>> {
+		let arity = self.arity();
+		if args.len() > arity && !self.rest {
+			return ErrorList::run(arity_message(arity - self.optional, arity, false, args.len()), pos).err();
+		}
+
+		let mut values = args.into_iter().map(|(value, _)| Some(value)).collect::<Vec<_>>();
+		let extra = if values.len() > arity { values.split_off(arity) } else { Vec::new() };
+		values.resize_with(arity, || None);
+
+		for (name, value, arg_pos) in named {
+			match self.names[..arity].iter().position(|param| *param == name) {
+				Some(i) if values[i].is_some() => return ErrorList::run(format!("Argument '{}' was already passed", name), arg_pos).err(),
+				Some(i) => values[i] = Some(value),
+				None => return ErrorList::run(format!("{} has no parameter named '{}'", display, name), arg_pos).err(),
+			}
+		}
+
+		if let Some(i) = values[..arity - self.optional].iter().position(Option::is_none) {
+			return ErrorList::run(format!("Missing argument for parameter {} in call to {}", self.labels[i], display), pos).err();
+		}
+		if self.rest {
+			values.push(Some(List::new(extra.into_iter().flatten().collect())));
+		}
+		Ok(values)
+	}
+
 }
 
 impl Display for dyn Callable {
@@ -33,16 +141,12 @@ impl Display for dyn Callable {
 	}
 }
 
-impl <T : Callable + 'static> Wrap<Rc<RefCell<dyn Callable>>> for T {
-	fn wrap(self) -> Rc<RefCell<(dyn Callable + 'static)>> { Rc::new(RefCell::new(self)) }
+impl <T : Callable + 'static> Wrap<CallableRef> for T {
+	fn wrap(self) -> CallableRef { Rc::new(self) }
 }
 
-// impl <T : Callable + 'static> Wrap<Rc<RefCell<Box<dyn Callable>>>> for Box<T> {
-// 	fn wrap(self) -> Rc<RefCell<Box<(dyn Callable + 'static)>>> { Rc::new(RefCell::new(self)) }
-// }
-
-impl <T : Callable + 'static> Wrap<Rc<RefCell<Box<dyn Callable>>>> for T {
-	fn wrap(self) -> Rc<RefCell<Box<(dyn Callable + 'static)>>> { Rc::new(RefCell::new(Box::new(self))) }
+impl Wrap<CallableRef> for Box<dyn Callable> {
+	fn wrap(self) -> CallableRef { Rc::from(self) }
 }
 
 impl Clone for Box<dyn Callable> {
@@ -51,15 +155,16 @@ impl Clone for Box<dyn Callable> {
 
 #[derive(Debug, Clone)]
 pub struct ValCallable {
-	data: Rc<RefCell<Box<dyn Callable>>>
+	data: CallableRef
 }
 
 impl ValCallable {
-	pub fn create(data: Rc<RefCell<Box<dyn Callable>>>) -> Self {
+	pub fn create(data: CallableRef) -> Self {
+		gc::track(Node::Callable(data.clone()));
 		Self { data }
 	}
 	
-	pub fn new(data: Rc<RefCell<Box<dyn Callable>>>) -> Box<dyn Value> {
+	pub fn new(data: CallableRef) -> Box<dyn Value> {
 		Self::create(data).wrap()
 	}
 }
@@ -67,12 +172,14 @@ impl ValCallable {
 impl Value for ValCallable {
 	fn get_type(&self) -> ValueType { ValueType::Callable }
 	
-	fn to_callable(&self, _pos: SourcePos) -> Result<Rc<RefCell<Box<(dyn Callable + 'static)>>>> { self.data.clone().wrap() }
+	fn to_callable(&self, _pos: SourcePos) -> Result<CallableRef> { self.data.clone().wrap() }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::Callable(self.data.clone())) }
 	
 	fn to_string(&self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<String> {
-		self.data.borrow().to_string().wrap()
+		self.data.to_string().wrap()
 	}
 	
 	fn equ(&self, _other: Box<dyn Value>, _other_pos: SourcePos, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<bool> { false.wrap() }
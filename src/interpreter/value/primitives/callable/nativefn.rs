@@ -1,36 +1,41 @@
-
-use std::{rc::Rc, cell::RefCell};
-
 use crate::{utils::{result::Result, source_pos::SourcePos, wrap::Wrap}, interpreter::{Interpreter, value::Value}};
 
-use super::{Callable, ValCallable};
+use super::{Callable, CallableRef, ValCallable};
 
 #[derive(Debug, Clone)]
 pub struct NativeFn {
-	function: Rc<RefCell<Box<dyn Callable>>>
+	function: CallableRef
 }
 
 impl NativeFn {
-	pub fn new(function: Rc<RefCell<Box<dyn Callable>>>) -> Self {
+	pub fn new(function: CallableRef) -> Self {
 		NativeFn { function }
 	}
 
-	pub fn create(function: Rc<RefCell<Box<dyn Callable>>>) -> Box<dyn Value> {
+	pub fn create(function: CallableRef) -> Box<dyn Value> {
 		ValCallable::new(Self::new(function).wrap())
 	}
 }
 
 impl Callable for NativeFn {
-	fn cloned(&self) -> Box<dyn Callable> { Box::new(self.clone()) }
 	fn display(&self) -> String { "<native function>".to_owned() }
 	
-	fn arity(&self) -> usize { self.function.borrow().arity() }
+	fn arity(&self) -> usize { self.function.arity() }
+
+	fn optional(&self) -> usize { self.function.optional() }
 
-	fn check_arity(&self, args_in: usize, pos: SourcePos) -> Result<()> { self.function.borrow().check_arity(args_in, pos) }
+	fn variadic(&self) -> bool { self.function.variadic() }
 
-	fn bind(&mut self, binding: Box<dyn Value>) { self.function.borrow_mut().bind(binding) }
+	fn check_arity(&self, args_in: usize, pos: SourcePos) -> Result<()> { self.function.check_arity(args_in, pos) }
+
+	// the shared native is left alone, this one gets its own bound copy
+	fn bind(&mut self, binding: Box<dyn Value>) {
+		let mut function = self.function.cloned();
+		function.bind(binding);
+		self.function = function.wrap();
+	}
 
-	fn call(&mut self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
-		self.function.borrow_mut().call(pos, interpreter, args)
+	fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		self.function.call(pos, interpreter, args)
 	}
 }
@@ -1,31 +1,77 @@
+use crate::{interpreter::{Interpreter, value::{ValueType, gc::Tracer}, globals::attributes::error::ERROR_ATTR}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
 
-use crate::{interpreter::{Interpreter, value::ValueType, globals::attributes::error::ERROR_ATTR}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
-
-use super::super::Value;
+use super::{super::Value, string::Str};
 
 #[derive(Debug, Clone)]
 pub struct Error {
+	// the id of the attribute that says what went wrong, Error itself for plain errors
+	kind: usize,
 	data: Box<dyn Value>,
+	cause: Option<Box<dyn Value>>,
+	pos: Option<SourcePos>,
 }
 
 impl Error {
 	pub fn new(data: Box<dyn Value>) -> Box<dyn Value> {
-		Self { data }.wrap()
+		Self { kind: global_id(ERROR_ATTR), data, cause: None, pos: None }.wrap()
+	}
+
+	pub fn at(data: Box<dyn Value>, pos: SourcePos) -> Box<dyn Value> {
+		Self { kind: global_id(ERROR_ATTR), data, cause: None, pos: Some(pos) }.wrap()
 	}
+
+	// errors raised by natives, with one of the builtin kinds
+	pub fn of_kind(kind: &str, msg: String, pos: SourcePos) -> Box<dyn Value> {
+		Self { kind: global_id(kind), data: Str::new(msg), cause: None, pos: Some(pos) }.wrap()
+	}
+
+	pub fn create(kind: usize, data: Box<dyn Value>, cause: Option<Box<dyn Value>>, pos: SourcePos) -> Box<dyn Value> {
+		Self { kind, data, cause, pos: Some(pos) }.wrap()
+	}
+
+	pub fn kind(&self) -> usize { self.kind }
+
+	pub fn data(&self) -> Box<dyn Value> { self.data.clone() }
+
+	pub fn cause(&self) -> Option<Box<dyn Value>> { self.cause.clone() }
+
+	pub fn pos(&self) -> Option<SourcePos> { self.pos }
 }
 
 impl Value for Error {
 	fn get_type(&self) -> ValueType { ValueType::Error }
-	
-	fn to_error(&self, _pos: SourcePos) -> Result<Box<dyn Value>> { self.data.clone().wrap() }
-	
+
+	fn to_error(&self, _pos: SourcePos) -> Result<Error> { self.clone().wrap() }
+
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
-	
-	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ERROR_ATTR)] }
-	
+
+	fn trace(&self, visit: &mut Tracer) {
+		self.data.trace(visit);
+		if let Some(ref cause) = self.cause { cause.trace(visit); }
+	}
+
+	fn get_attributes(&self) -> Vec<usize> {
+		// methods of a custom kind are found after the ones every error has
+		if self.kind == global_id(ERROR_ATTR) { vec![self.kind] } else { vec![self.kind, global_id(ERROR_ATTR)] }
+	}
+
+	fn has_attr(&self, attr: usize, interpreter: &mut Interpreter) -> bool {
+		if attr == global_id(ERROR_ATTR) || attr == self.kind { return true; }
+		interpreter.env.get_id(self.kind).has_attr(attr, interpreter)
+	}
+
 	fn to_string(&self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<String> {
-		format!("{}: {}", ansi_term::Color::Red.paint("error"), self.data.to_string(interpreter, pos)?).wrap()
+		let kind = if self.kind == global_id(ERROR_ATTR) {
+			"error".to_owned()
+		} else {
+			interpreter.env.get_id(self.kind).to_string(interpreter, pos)?
+		};
+		let mut str = format!("{}: {}", ansi_term::Color::Red.paint(kind), self.data.to_string(interpreter, pos)?);
+		if let Some(ref cause) = self.cause {
+			str = format!("{}, caused by {}", str, cause.to_string(interpreter, pos)?);
+		}
+		str.wrap()
 	}
-	
+
 	fn equ(&self, _other: Box<dyn Value>, _other_pos: SourcePos, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<bool> { false.wrap() }
-}
\ No newline at end of file
+}
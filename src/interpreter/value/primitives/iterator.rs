@@ -0,0 +1,161 @@
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use crate::{interpreter::{Interpreter, globals::attributes::iter::ITER_ATTR, value::{ValueRef, ValueType, macros::castf, gc::{Node, Tracer}, primitives::{callable::{Callable, nativefn::NativeFn}, list::{List, ListData}, map::MapRef, string::Str}}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
+
+use super::super::Value;
+
+// anything that can be stepped through, returning none once it is exhausted
+pub trait NativeIter : Debug {
+	fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>>;
+
+	fn trace(&self, _visit: &mut Tracer) { }
+}
+
+pub type IterRef = Rc<RefCell<Box<dyn NativeIter>>>;
+
+impl <T : NativeIter + 'static> Wrap<IterRef> for T {
+	fn wrap(self) -> IterRef { Rc::new(RefCell::new(Box::new(self))) }
+}
+
+impl <T : NativeIter + 'static> Wrap<Result<IterRef>> for T {
+	fn wrap(self) -> Result<IterRef> { Ok(Rc::new(RefCell::new(Box::new(self)))) }
+}
+
+pub fn advance(iter: &IterRef, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+	match iter.try_borrow_mut() {
+		Ok(mut iter) => iter.next(interpreter, pos),
+		Err(_) => ErrorList::run("Iterator was advanced while it was already running".to_owned(), pos).err(),
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Iter {
+	data: IterRef,
+}
+
+impl Iter {
+	pub fn new(data: IterRef) -> Box<dyn Value> {
+		Self { data }.wrap()
+	}
+}
+
+impl Value for Iter {
+	fn get_type(&self) -> ValueType { ValueType::Iterator }
+
+	fn to_iter(&self, _pos: SourcePos) -> Result<IterRef> { self.data.clone().wrap() }
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::Iter(self.data.clone())) }
+
+	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
+
+	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ITER_ATTR)] }
+
+	fn get_field(&self, field: &str, interpreter: &mut Interpreter, pos: SourcePos) -> Result<ValueRef> {
+		#[derive(Clone, Debug)] struct Next(IterRef);
+
+		impl Callable for Next {
+			fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, _args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+				advance(&self.0, interpreter, pos)?.unwrap_or_else(Done::new).wrap()
+			}
+		}
+
+		// native iterators are the only values that can be stepped by hand
+		if field == "next" { return NativeFn::create(Next(self.data.clone()).wrap()).wrap(); }
+
+		let attr = interpreter.env.get_id(global_id(ITER_ATTR));
+		match castf!(attr attr).get(field) {
+			Some(method) => method.wrap(),
+			None => ErrorList::run(format!("Property {} is undefined for {}", field, self.get_type()), pos).err(),
+		}
+	}
+
+	fn to_string(&self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<String> {
+		"<iterator>".to_owned().wrap()
+	}
+
+	fn equ(&self, other: Box<dyn Value>, other_pos: SourcePos, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<bool> {
+		Rc::ptr_eq(&self.data, &other.to_iter(other_pos)?).wrap()
+	}
+}
+
+// returned by next once an iterator has nothing left
+#[derive(Debug, Clone)]
+pub struct Done;
+
+impl Done {
+	pub fn new() -> Box<dyn Value> { Self.wrap() }
+}
+
+impl Value for Done {
+	fn get_type(&self) -> ValueType { ValueType::Done }
+
+	fn is_truthy(&self) -> bool { false }
+
+	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
+
+	fn to_string(&self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<String> {
+		"done".to_owned().wrap()
+	}
+
+	fn equ(&self, other: Box<dyn Value>, _other_pos: SourcePos, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<bool> {
+		(other.get_type() == ValueType::Done).wrap()
+	}
+}
+
+#[derive(Debug)]
+pub struct ListIter { pub list: ListData, pub i: usize }
+
+impl NativeIter for ListIter {
+	fn next(&mut self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let val = self.list.borrow().get(self.i).cloned();
+		self.i += 1;
+		val.wrap()
+	}
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::List(self.list.clone())) }
+}
+
+#[derive(Debug)]
+pub struct StrIter { pub chars: Vec<char>, pub i: usize }
+
+impl NativeIter for StrIter {
+	fn next(&mut self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let val = self.chars.get(self.i).map(|c| Str::new(c.to_string()));
+		self.i += 1;
+		val.wrap()
+	}
+}
+
+// maps are stepped through as [key, value] pairs, in insertion order
+#[derive(Debug)]
+pub struct MapIter { pub map: MapRef, pub i: usize }
+
+impl NativeIter for MapIter {
+	fn next(&mut self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let val = self.map.borrow().entries().get(self.i).map(|(key, value)| List::new(vec![key.to_value(), value.clone()]));
+		self.i += 1;
+		val.wrap()
+	}
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::Map(self.map.clone())) }
+}
+
+// objects are iterated by calling their own next method until it returns done
+#[derive(Debug)]
+pub struct ObjIter { pub obj: Box<dyn Value> }
+
+impl NativeIter for ObjIter {
+	fn next(&mut self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let field = self.obj.get_field("next", interpreter, pos)?;
+		let mut method = field.borrow().to_callable(pos)?.cloned();
+		method.check_arity(0, pos)?;
+		method.bind(self.obj.clone());
+		let val = method.call(pos, interpreter, vec![])?;
+		match val.get_type() {
+			ValueType::Done => None,
+			_ => Some(val),
+		}.wrap()
+	}
+
+	fn trace(&self, visit: &mut Tracer) { self.obj.trace(visit) }
+}
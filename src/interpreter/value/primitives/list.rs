@@ -1,9 +1,9 @@
 
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{interpreter::{Interpreter, globals::attributes::list::LIST_ATTR, value::{ValueType, macros::castf}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
+use crate::{interpreter::{Interpreter, globals::attributes::{list::LIST_ATTR, iter::ITER_ATTR}, value::{ValueType, macros::castf}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
 
-use super::super::Value;
+use super::{super::{Value, gc::{self, Node, Tracer}}, iterator::{IterRef, ListIter}};
 
 pub type ListData = Rc<RefCell<Vec<Box<dyn Value>>>>;
 
@@ -14,7 +14,9 @@ pub struct List {
 
 impl List {
 	pub fn new(data: Vec<Box<dyn Value>>) -> Box<dyn Value> {
-		Self { data: data.wrap() }.wrap()
+		let data: ListData = data.wrap();
+		gc::track(Node::List(data.clone()));
+		Self { data }.wrap()
 	}
 }
 
@@ -22,10 +24,14 @@ impl Value for List {
 	fn get_type(&self) -> ValueType { ValueType::Vector }
 	
 	fn to_list(&self, _pos: SourcePos) -> Result<ListData> { self.data.clone().wrap() } 
+
+	fn to_iter(&self, _pos: SourcePos) -> Result<IterRef> { ListIter { list: self.data.clone(), i: 0 }.wrap() }
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::List(self.data.clone())) }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
-	fn get_attributes(&self) -> Vec<usize> { vec![global_id(LIST_ATTR)] }
+	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ITER_ATTR), global_id(LIST_ATTR)] }
 
 	fn to_string(&self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<String> {
 		let mut str = String::from("[");
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{interpreter::{Interpreter, globals::attributes::{map::MAP_ATTR, iter::ITER_ATTR}, value::{ValueType, macros::castf, primitives::{bool::Bool, none::ValNone, number::Number, string::Str}}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
+
+use super::{super::{Value, gc::{self, Node, Tracer}}, iterator::{IterRef, MapIter}};
+
+// only values that can't change under the map can be used as its keys
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+	None,
+	Num(u64),
+	Str(String),
+	Bool(bool),
+}
+
+impl MapKey {
+	pub fn num(n: f64) -> Self {
+		// 0 and -0 are the same key
+		MapKey::Num(if n == 0.0 { 0f64.to_bits() } else { n.to_bits() })
+	}
+
+	pub fn to_value(&self) -> Box<dyn Value> {
+		match self {
+			MapKey::None => ValNone::new(),
+			MapKey::Num(bits) => Number::new(f64::from_bits(*bits)),
+			MapKey::Str(s) => Str::new(s.clone()),
+			MapKey::Bool(b) => Bool::new(*b),
+		}
+	}
+}
+
+// entries are kept in the order they were first inserted
+#[derive(Debug, Clone, Default)]
+pub struct MapData {
+	index: HashMap<MapKey, usize>,
+	entries: Vec<(MapKey, Box<dyn Value>)>,
+}
+
+impl MapData {
+	pub fn get(&self, key: &MapKey) -> Option<Box<dyn Value>> {
+		self.index.get(key).map(|i| self.entries[*i].1.clone())
+	}
+
+	pub fn insert(&mut self, key: MapKey, value: Box<dyn Value>) {
+		match self.index.get(&key) {
+			Some(i) => self.entries[*i].1 = value,
+			None => {
+				self.index.insert(key.clone(), self.entries.len());
+				self.entries.push((key, value));
+			}
+		}
+	}
+
+	pub fn remove(&mut self, key: &MapKey) -> Option<Box<dyn Value>> {
+		let i = self.index.remove(key)?;
+		let (_, value) = self.entries.remove(i);
+		for pos in self.index.values_mut().filter(|pos| **pos > i) { *pos -= 1; }
+		value.wrap()
+	}
+
+	pub fn contains(&self, key: &MapKey) -> bool {
+		self.index.contains_key(key)
+	}
+
+	pub fn entries(&self) -> &[(MapKey, Box<dyn Value>)] {
+		&self.entries
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn into_values(self) -> Vec<Box<dyn Value>> {
+		self.entries.into_iter().map(|(_, value)| value).collect()
+	}
+}
+
+pub type MapRef = Rc<RefCell<MapData>>;
+
+#[derive(Debug, Clone)]
+pub struct Map {
+	data: MapRef,
+}
+
+impl Map {
+	pub fn new(data: MapData) -> Box<dyn Value> {
+		let data: MapRef = data.wrap();
+		gc::track(Node::Map(data.clone()));
+		Self { data }.wrap()
+	}
+}
+
+impl Value for Map {
+	fn get_type(&self) -> ValueType { ValueType::Map }
+
+	fn to_map(&self, _pos: SourcePos) -> Result<MapRef> { self.data.clone().wrap() }
+
+	fn trace(&self, visit: &mut Tracer) { visit(Node::Map(self.data.clone())) }
+
+	fn to_iter(&self, _pos: SourcePos) -> Result<IterRef> { MapIter { map: self.data.clone(), i: 0 }.wrap() }
+
+	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
+
+	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ITER_ATTR), global_id(MAP_ATTR)] }
+
+	fn to_string(&self, interpreter: &mut Interpreter, pos: SourcePos) -> Result<String> {
+		let entries = self.data.borrow().entries().to_vec();
+		if entries.is_empty() { return "[:]".to_owned().wrap(); }
+		let mut strs = Vec::new();
+		for (key, value) in entries {
+			strs.push(format!("{}: {}", key.to_value().to_string(interpreter, pos)?, value.to_string(interpreter, pos)?));
+		}
+		format!("[{}]", strs.join(", ")).wrap()
+	}
+
+	fn equ(&self, other: Box<dyn Value>, other_pos: SourcePos, interpreter: &mut Interpreter, pos: SourcePos) -> Result<bool> {
+		let other = castf!(map other);
+		if Rc::ptr_eq(&self.data, &other) { return true.wrap(); }
+
+		let (entries, other) = (self.data.borrow().clone(), other.borrow().clone());
+		if entries.len() != other.len() { return false.wrap(); }
+		for (key, value) in entries.entries() {
+			let equal = match other.get(key) {
+				Some(other_value) => value.equals(other_value, other_pos, interpreter, pos)?,
+				None => false,
+			};
+			if !equal { return false.wrap(); }
+		}
+		true.wrap()
+	}
+}
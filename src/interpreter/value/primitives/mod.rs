@@ -4,8 +4,11 @@ pub mod number;
 pub mod string;
 pub mod bool;
 pub mod list;
+pub mod map;
 pub mod object;
 pub mod callable;
 pub mod error;
+pub mod iterator;
+pub mod range;
 
 pub mod attribute;
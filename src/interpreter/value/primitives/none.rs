@@ -1,7 +1,7 @@
 
 use crate::{interpreter::{Interpreter, value::ValueType}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap}};
 
-use super::super::Value;
+use super::{super::Value, map::MapKey};
 
 #[derive(Debug, Clone)]
 pub struct ValNone;
@@ -14,6 +14,8 @@ impl Value for ValNone {
 	fn get_type(&self) -> ValueType { ValueType::None }
 	
 	fn is_truthy(&self) -> bool { false }
+
+	fn to_key(&self, _pos: SourcePos) -> Result<MapKey> { MapKey::None.wrap() }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
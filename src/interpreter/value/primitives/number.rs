@@ -1,7 +1,7 @@
 
 use crate::{interpreter::{Interpreter, value::{ValueType, macros::castf}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
 
-use super::super::Value;
+use super::{super::Value, map::MapKey};
 
 #[derive(Debug, Clone)]
 pub struct Number {
@@ -18,6 +18,8 @@ impl Value for Number {
 	fn get_type(&self) -> ValueType { ValueType::Num }
 	
 	fn to_num(&self, _pos: SourcePos) -> Result<f64> { self.data.wrap() }
+
+	fn to_key(&self, _pos: SourcePos) -> Result<MapKey> { MapKey::num(self.data).wrap() }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 
 use crate::{interpreter::{Interpreter, value::{ValueRef, ValueType, primitives::string::Str, macros::castf}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
 
-use super::super::Value;
+use super::{super::{Value, gc::{self, Node, Tracer}}, iterator::{IterRef, ObjIter}};
 
 pub type ObjectMap = HashMap<String, ValueRef>;
 
@@ -15,22 +15,16 @@ pub struct Object {
 
 impl Object {
 	pub fn new(data: ObjectMap, attributes: HashSet<usize>) -> Box<dyn Value> {
+		data.values().for_each(|field| gc::track(Node::Cell(field.clone())));
 		Self { data, attributes }.wrap()
 	}
 	
 	fn method_call(&self, method: &str, interpreter: &mut Interpreter, pos: SourcePos, args: Vec<(Box<dyn Value>, SourcePos)>, default: Result<Box<dyn Value>>) -> Result<Box<dyn Value>> {
 		if let Ok(field) = self.get_field(method, interpreter, pos) {
-			// let callable = field.borrow().clone().to_callable(pos)?;
-			// callable.borrow_mut().bind(self.cloned());
-			// let res = callable.borrow_mut().call(pos, interpreter, args);
-			// res
-			unsafe {
-				let callable = field.borrow().to_callable(pos)?.as_ptr();
-				callable.as_ref().unwrap().check_arity(args.len(), pos)?;
-				callable.as_mut().unwrap().bind(self.cloned());
-				let ret = callable.as_mut().unwrap().call(pos, interpreter, args);
-				ret
-			}
+			let mut method = field.borrow().to_callable(pos)?.cloned();
+			method.check_arity(args.len(), pos)?;
+			method.bind(self.cloned());
+			method.call(pos, interpreter, args)
 		} else {
 			default
 		}
@@ -41,6 +35,12 @@ impl Value for Object {
 	fn get_type(&self) -> ValueType { ValueType::Object }
 	
 	fn to_obj(&self, _pos: SourcePos) -> Result<ObjectMap> { self.data.clone().wrap() }
+
+	fn to_iter(&self, _pos: SourcePos) -> Result<IterRef> { ObjIter { obj: self.cloned() }.wrap() }
+
+	fn get_attributes(&self) -> Vec<usize> { self.attributes.iter().cloned().collect() }
+
+	fn trace(&self, visit: &mut Tracer) { self.data.values().for_each(|field| visit(Node::Cell(field.clone()))) }
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
@@ -52,7 +52,7 @@ impl Value for Object {
 				let v = attributes.clone().iter().cloned().collect::<Vec<_>>();
 				let mut cur = v.as_slice();
 				while let [ rest @ .., top ] = cur {
-					let attr = interpreter.env.get(*top);
+					let attr = interpreter.env.get_id(*top);
 					let attr = castf!(attr attr);
 					match attr.get(field) {
 						Some(method) => return method.wrap(),
@@ -104,7 +104,7 @@ impl Value for Object {
 			let mut cur = v.as_slice();
 			while let [ rest @ .., top ] = cur {
 				if *top == attr { return true; }
-				let val = interpreter.env.get(*top);
+				let val = interpreter.env.get_id(*top);
 				let val = castf!(attr val);
 				if find(val.super_attrs(), attr, interpreter) { return true; }
 				cur = rest;
@@ -0,0 +1,77 @@
+use crate::{interpreter::{Interpreter, globals::attributes::iter::ITER_ATTR, value::{ValueType, primitives::number::Number}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
+
+use super::{super::Value, iterator::{IterRef, NativeIter}};
+
+// the numbers from start up to end, or down to it with a negative step.
+// the bounds can be left out when slicing, where they mean the ends of the sliced value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+	pub start: Option<f64>,
+	pub end: Option<f64>,
+	pub inclusive: bool,
+	pub step: f64,
+}
+
+impl Range {
+	// builds a range out of the values of its bounds, along with where each one was written
+	pub fn from_bounds(start: Option<(Box<dyn Value>, SourcePos)>, end: Option<(Box<dyn Value>, SourcePos)>, inclusive: bool, step: Option<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		let start = match start { Some((val, pos)) => Some(val.to_num(pos)?), None => None };
+		let end = match end { Some((val, pos)) => Some(val.to_num(pos)?), None => None };
+		let step = match step {
+			Some((val, pos)) => {
+				let step = val.to_num(pos)?;
+				if step == 0.0 { return ErrorList::run("The step of a range can't be 0".to_owned(), pos).err(); }
+				step
+			}
+			None => 1.0,
+		};
+		Range { start, end, inclusive, step }.wrap()
+	}
+}
+
+impl Value for Range {
+	fn get_type(&self) -> ValueType { ValueType::Range }
+
+	fn to_range(&self, _pos: SourcePos) -> Result<Range> { self.clone().wrap() }
+
+	fn to_iter(&self, pos: SourcePos) -> Result<IterRef> {
+		match self.start {
+			Some(start) => RangeIter { start, k: 0, range: self.clone() }.wrap(),
+			None => ErrorList::run("Cannot iterate over a range without a start".to_owned(), pos).err(),
+		}
+	}
+
+	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
+
+	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ITER_ATTR)] }
+
+	fn to_string(&self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<String> {
+		let bound = |n: Option<f64>| n.map(|n| n.to_string()).unwrap_or_default();
+		let mut str = format!("{}{}{}", bound(self.start), if self.inclusive { "..=" } else { ".." }, bound(self.end));
+		if self.step != 1.0 { str.push_str(&format!(" step {}", self.step)); }
+		str.wrap()
+	}
+
+	fn equ(&self, other: Box<dyn Value>, other_pos: SourcePos, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<bool> {
+		(*self == other.to_range(other_pos)?).wrap()
+	}
+}
+
+// each number is worked out from the start, adding up the step would pile up rounding errors
+#[derive(Debug)]
+pub struct RangeIter { start: f64, k: u64, range: Range }
+
+impl NativeIter for RangeIter {
+	fn next(&mut self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<Option<Box<dyn Value>>> {
+		let Range { end, inclusive, step, .. } = self.range;
+		let cur = self.start + self.k as f64 * step;
+		let done = match end {
+			Some(end) if step > 0.0 => if inclusive { cur > end } else { cur >= end },
+			Some(end) => if inclusive { cur < end } else { cur <= end },
+			None => false,
+		};
+		if done { return None.wrap(); }
+		self.k += 1;
+		Some(Number::new(cur)).wrap()
+	}
+}
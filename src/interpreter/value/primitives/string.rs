@@ -1,7 +1,7 @@
 
-use crate::{interpreter::{Interpreter, globals::attributes::string::STRING_ATTR, value::{ValueType, macros::castf}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
+use crate::{interpreter::{Interpreter, globals::attributes::{string::STRING_ATTR, iter::ITER_ATTR}, value::{ValueType, macros::castf}}, utils::{result::Result, source_pos::SourcePos, wrap::Wrap, global_ids::global_id}};
 
-use super::super::Value;
+use super::{super::Value, map::MapKey, iterator::{IterRef, StrIter}};
 
 #[derive(Debug, Clone)]
 pub struct Str {
@@ -23,9 +23,13 @@ impl Value for Str {
 	
 	fn cloned(&self) -> Box<dyn Value> { self.clone().wrap() }
 	
-	fn get_attributes(&self) -> Vec<usize> { vec![global_id(STRING_ATTR)] }
+	fn get_attributes(&self) -> Vec<usize> { vec![global_id(ITER_ATTR), global_id(STRING_ATTR)] }
 	
 	fn to_str(&self, _pos: SourcePos) -> Result<String> { self.data.clone().wrap() }
+
+	fn to_key(&self, _pos: SourcePos) -> Result<MapKey> { MapKey::Str(self.data.clone()).wrap() }
+
+	fn to_iter(&self, _pos: SourcePos) -> Result<IterRef> { StrIter { chars: self.data.chars().collect(), i: 0 }.wrap() }
 	
 	fn to_string(&self, _interpreter: &mut Interpreter, _pos: SourcePos) -> Result<String> {
 		self.data.clone().wrap()
@@ -1,9 +1,9 @@
 
 pub mod token;
 
-use std::{iter::Peekable, vec::IntoIter, collections::HashSet};
+use std::collections::HashSet;
 
-use crate::utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap};
+use crate::utils::{result::{ErrorList, Result}, source_pos::SourcePos, sources::{add_source, replace_source, set_source}, wrap::Wrap};
 
 use self::token::{Keyword, LiteralType::*, Symbol::{self, *}, Token, TokenType::*};
 
@@ -12,53 +12,81 @@ type TokenResult = Result<Option<Token>>;
 pub struct LexerResult {
 	pub tokens: Vec<Token>,
 	pub directives: HashSet<String>,
+	pub comments: Vec<Comment>,
 	pub errors: ErrorList,
 }
 
+// comments don't take part in parsing, but tools like the formatter need them back
+#[derive(Debug, Clone)]
+pub struct Comment {
+	pub text: String,
+	pub pos: SourcePos,
+}
+
 #[derive(Debug, Clone)]
 pub struct Lexer {
-	source: Peekable<IntoIter<char>>,
+	source: Vec<char>,
+	current: usize,
 	cursor: SourcePos,
 	next_cursor: SourcePos,
 	directives: HashSet<String>,
+	comments: Vec<Comment>,
 }
 
 impl Lexer {
 
-	pub fn from_text(text: &str) -> Self {
+	pub fn from_text(name: &str, text: &str) -> Self {
+		Self::in_file(text, add_source(name, text))
+	}
+
+	// lexes a new version of a file that was already lexed before
+	pub fn replace_text(name: &str, text: &str) -> Self {
+		Self::in_file(text, replace_source(name, text))
+	}
+
+	// lexes a new text for a file registered before, keeping its id
+	pub fn reuse_file(file: usize, text: &str) -> Self {
+		set_source(file, text);
+		Self::in_file(text, file)
+	}
+
+	fn in_file(text: &str, file: usize) -> Self {
 		Self {
-			source: text.chars().collect::<Vec<_>>().into_iter().peekable(),
-			cursor: SourcePos::new(1, 1),
-			next_cursor: SourcePos::new(1, 1),
+			source: text.chars().collect(),
+			current: 0,
+			cursor: SourcePos::in_file(1, 1, file),
+			next_cursor: SourcePos::in_file(1, 1, file),
 			directives: HashSet::new(),
+			comments: Vec::new(),
 		}
 	}
 
 	pub fn from_file(path: &str) -> std::io::Result<Self> {
 		let text = std::fs::read_to_string(path)?;
-		Ok(Self::from_text(&text))
+		Ok(Self::from_text(path, &text))
+	}
+
+	// the character `ahead` places after the next one
+	fn peek_char(&self, ahead: usize) -> Option<char> {
+		self.source.get(self.current + ahead).copied()
 	}
 
 	fn next_char(&mut self) -> Option<char> {
-		let next = self.source.next();
-		match next {
-			Some('\n') => {
-				self.cursor = self.next_cursor;
-				self.next_cursor.lin += 1;
-				self.next_cursor.col = 1;
-			}
-			Some(_) => {
-				self.cursor = self.next_cursor;
-				self.next_cursor.col += 1;
-			}
-			None => (),
+		let next = self.peek_char(0);
+		if let Some(c) = next {
+			self.current += 1;
+			self.cursor = self.next_cursor;
+			self.next_cursor = match c {
+				'\n' => SourcePos::in_file(self.cursor.lin + 1, 1, self.cursor.file),
+				_ => SourcePos::in_file(self.cursor.lin, self.cursor.col + 1, self.cursor.file),
+			};
 		}
 		next
 	}
 
 	fn next_match(&mut self, expected: char) -> bool {
-		match self.source.peek() {
-			Some(&c) if c == expected => {
+		match self.peek_char(0) {
+			Some(c) if c == expected => {
 				self.next_char();
 				true
 			}
@@ -68,8 +96,8 @@ impl Lexer {
 
 	fn scan_raw_while(&mut self, buf: &mut String, pred: fn(char) -> bool) -> Result<()> {
 		loop {
-			match self.source.peek() {
-				Some(&c) if pred(c) => {
+			match self.peek_char(0) {
+				Some(c) if pred(c) => {
 					buf.push(c);
 					self.next_char();
 				}
@@ -82,21 +110,30 @@ impl Lexer {
 	fn symbol(&mut self, symbol: Symbol) -> TokenResult { Token::new(Symbol(symbol), self.cursor).wrap() }
 
 	fn scan_comment(&mut self) -> TokenResult {
+		let pos = self.cursor;
 		let mut str = String::new();
 		let dir = self.next_match('!');
 		let _ = self.scan_raw_while(&mut str, |c| c != '\n');
 		if dir {
 			for dir in str.split(',') { self.directives.insert(dir.trim().to_owned()); }
 		}
+		let text = format!("{}{}", if dir { "#!" } else { "#" }, str.trim_end());
+		self.comments.push(Comment { text, pos });
 		return Ok(None);
 	}
 
 	fn scan_block_comment(&mut self) -> TokenResult {
-		let pos = self.cursor;
+		// the opening '(#' was already consumed
+		let pos = SourcePos::in_file(self.cursor.lin, self.cursor.col - 1, self.cursor.file);
+		let mut text = String::from("(#");
 		loop {
 			match self.next_char() {
-				Some('#') if self.next_match(')') => return Ok(None),
-				Some(_) => continue,
+				Some('#') if self.next_match(')') => {
+					text.push_str("#)");
+					self.comments.push(Comment { text, pos });
+					return Ok(None)
+				},
+				Some(c) => text.push(c),
 				None => return ErrorList::comp("Block comment left open".to_owned(), pos).err()
 			}
 		}
@@ -112,8 +149,10 @@ impl Lexer {
 
 	fn scan_number(&mut self, first_digit: char) -> TokenResult {
 		let mut value = String::from(first_digit);
-		while let Some(&c) = self.source.peek() {
-			if !(c.is_ascii_digit() || (c == '.' && !value.contains('.'))) { break }
+		while let Some(c) = self.peek_char(0) {
+			// a dot only belongs to the number when a digit follows, so `1..3` is a range
+			let fraction = c == '.' && !value.contains('.') && self.peek_char(1).is_some_and(|c| c.is_ascii_digit());
+			if !(c.is_ascii_digit() || fraction) { break }
 			value.push(c);
 			self.next_char();
 		}
@@ -149,7 +188,7 @@ impl Lexer {
 							Some('}') => break,
 							Some(c) if c == '\n' => { errors.add_comp("Illegal EOL inside string template term".to_owned(), self.cursor); return errors.err() },
 							Some(c) if c.is_whitespace() => continue,
-							Some(c) => match self.scan_token(c) {
+							Some(c) => match self.scan_spanned(c) {
 								Ok(Some(token)) => tokens.push(token),
 								Ok(None) => continue,
 								Err(err) => errors.append(err),
@@ -201,6 +240,13 @@ impl Lexer {
 			'<' => self.symbol(OpenAng),
 			'>' if self.next_match('=') => self.symbol(CloseAngEquals),
 			'>' => self.symbol(CloseAng),
+			'.' if self.next_match('.') => if self.next_match('.') {
+				self.symbol(Ellipsis)
+			} else if self.next_match('=') {
+				self.symbol(DoubleDotEquals)
+			} else {
+				self.symbol(DoubleDot)
+			},
 			'.' => self.symbol(Dot),
 			',' => self.symbol(Comma),
 			';' => Token::new(EOL, self.cursor).wrap(),
@@ -220,6 +266,8 @@ impl Lexer {
 			'=' if self.next_match('=') => self.symbol(DoubleEquals),
 			'=' => self.symbol(Equals),
 			'|'  if self.next_match('>') => self.symbol(BarCloseAng),
+			'?' if self.next_match('.') => self.symbol(QuestionDot),
+			'?' if self.next_match('?') => self.symbol(DoubleQuestion),
 			'?' => self.symbol(Question),
 			'\'' => self.scan_str_template(),
 			'"' => return self.scan_string(),
@@ -228,6 +276,13 @@ impl Lexer {
 		}
 	}
 
+	// tokens span from their first to their last character
+	fn scan_spanned(&mut self, first_char: char) -> TokenResult {
+		let start = self.cursor;
+		let token = self.scan_token(first_char)?;
+		Ok(token.map(|token| Token::new(token.typ, start.to(self.cursor))))
+	}
+
 	pub fn scan_tokens(mut self) -> LexerResult {
 		let mut tokens = Vec::new();
 		let mut errors = ErrorList::new();
@@ -236,7 +291,7 @@ impl Lexer {
 			match self.next_char() {
 				Some(c) if c == '\n' => tokens.push(Token::new(EOL, self.cursor)),
 				Some(c) if c.is_whitespace() => continue,
-				Some(c) => match self.scan_token(c) {
+				Some(c) => match self.scan_spanned(c) {
 					Ok(Some(token)) if token.typ == Symbol(BarCloseAng) => {
 						// allows function piping operator '|>' to be put in the line after an expression
 						if let Some(token) = tokens.last() { if token.typ == EOL { tokens.pop(); } }
@@ -255,6 +310,7 @@ impl Lexer {
 		LexerResult {
 			tokens,
 			directives: self.directives,
+			comments: self.comments,
 			errors
 		}
 	}
@@ -21,6 +21,8 @@ pub enum Keyword {
 	For, In,
 	Function, Return,
 	_Self, Attr, Static, Is,
+	Import, As,
+	Match,
 }
 
 impl Keyword {
@@ -49,6 +51,9 @@ impl Keyword {
 			"attr" => Attr,
 			"static" => Static,
 			"is" => Is,
+			"import" => Import,
+			"as" => As,
+			"match" => Match,
 			_ => return None,
 		}.wrap()
 	}
@@ -57,12 +62,12 @@ impl Keyword {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Symbol {
 	OpenPar, ClosePar, OpenSqr, CloseSqr, OpenBracket, CloseBracket, OpenAng, CloseAng,
-	Dot, Comma, Colon, DoubleColon,
+	Dot, DoubleDot, DoubleDotEquals, Ellipsis, Comma, Colon, DoubleColon,
 	Plus, Minus, Star, Slash, Exclam,
 	Equals, PlusEquals, MinusEquals, StarEquals, SlashEquals,
 	DoubleEquals, ExclamEquals, OpenAngEquals, CloseAngEquals,
 	HashtagOpenBracket, EqualsCloseAng, BarCloseAng,
-	Question,
+	Question, QuestionDot, DoubleQuestion,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,24 @@
+// the language itself, shared by the rover binary and any program embedding mars
+// only the items exported below are meant to be stable, the modules are public so the binary and tooling can reach into them
+pub mod utils;
+pub mod lexer;
+pub mod ast;
+pub mod parser;
+pub mod semantics;
+pub mod interpreter;
+pub mod compiler;
+pub mod vm;
+
+// the tools behind the rover commands
+pub mod repl;
+pub mod lsp;
+pub mod formatter;
+pub mod tester;
+pub mod debugger;
+pub mod profiler;
+
+mod engine;
+
+pub use engine::{Engine, FromValue, IntoReturn, IntoValue, NativeFunction};
+pub use interpreter::value::Value;
+pub use utils::result::{ErrorList, Result};
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use crate::{ast::{identifier::Identifier, expression::*, pattern::{Pattern, PatternType}, statement::*, types::Type, Block, module::Module}, lexer::{Lexer, LexerResult}, parser::Parser, semantics::{checker::Checker, resolver::Resolver}, utils::{result::Result, source_pos::SourcePos}};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefKind {
+	Function,
+	Attribute,
+	Method,
+	Import,
+	Variable,
+	Constant,
+	Parameter,
+	Binding,
+}
+
+#[derive(Debug, Clone)]
+pub struct Definition {
+	pub name: String,
+	pub kind: DefKind,
+	pub pos: SourcePos,
+	// how the declaration reads in source, shown on hover
+	pub detail: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AttrInfo {
+	pub methods: Vec<String>,
+	pub fields: Vec<String>,
+}
+
+// everything the language server knows about a single file
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+	pub errors: Vec<(String, Option<SourcePos>)>,
+	pub definitions: Vec<Definition>,
+	// every place a declared name is written, including its declaration, and the definition it refers to
+	pub references: Vec<(SourcePos, usize)>,
+	pub attributes: HashMap<String, AttrInfo>,
+	// whether the text got past the parser, a text that didn't has nothing indexed
+	pub parsed: bool,
+}
+
+impl Analysis {
+
+	pub fn new(name: &str, text: &str) -> Self {
+		let mut analysis = Analysis::default();
+		if let Err(errors) = analysis.analyze(name, text) {
+			analysis.errors.extend(errors.entries().map(|(msg, pos)| (msg.to_owned(), pos)));
+		}
+		analysis
+	}
+
+	// the parser gives up on the first broken declaration, everything after it still reports its errors
+	fn analyze(&mut self, name: &str, text: &str) -> Result<()> {
+		let LexerResult { tokens, directives, mut errors, .. } = Lexer::replace_text(name, text).scan_tokens();
+
+		let mut resolver = Resolver::new();
+		let mut checker = Checker::new();
+		let mut indexer = Indexer { scopes: Vec::new(), analysis: self };
+
+		if directives.contains("script") {
+			let (module, block) = Parser::new(tokens).script()?;
+			indexer.analysis.parsed = true;
+			errors.try_append(resolver.resolve(&module));
+			errors.try_append(resolver.resolve_block(&block));
+			errors.try_append(checker.check(&module));
+			errors.try_append(checker.check_block(&block));
+			indexer.module(&module);
+			indexer.block(block);
+		} else {
+			let module = Parser::new(tokens).module()?;
+			indexer.analysis.parsed = true;
+			errors.try_append(resolver.resolve(&module));
+			errors.try_append(checker.check(&module));
+			indexer.module(&module);
+		}
+
+		self.references.sort_by_key(|(pos, _)| (pos.lin, pos.col));
+		// desugared assignments like `a += 1` write the same name twice
+		self.references.dedup_by_key(|(pos, _)| (pos.lin, pos.col));
+
+		errors.if_empty(())
+	}
+
+	pub fn reference_at(&self, lin: u32, col: u32) -> Option<usize> {
+		self.references.iter()
+			.find(|(pos, _)| pos.lin == lin && pos.col <= col && col <= pos.end_col.max(pos.col))
+			.map(|(_, def)| *def)
+	}
+
+	pub fn references_to(&self, def: usize) -> impl Iterator<Item = SourcePos> + '_ {
+		self.references.iter().filter(move |(_, d)| *d == def).map(|(pos, _)| *pos)
+	}
+
+}
+
+fn annotated(name: &str, typ: &Option<Type>) -> String {
+	match typ {
+		Some(typ) => format!("{}: {}", name, typ),
+		None => name.to_owned(),
+	}
+}
+
+fn pattern(pattern: &Pattern) -> String {
+	match &pattern.typ {
+		PatternType::Wildcard => "_".to_owned(),
+		PatternType::Binding(name) => name.name.clone(),
+		PatternType::Literal(LiteralData::Str(str)) => format!("'{}'", str),
+		PatternType::Literal(LiteralData::Num(n)) => n.to_string(),
+		PatternType::Literal(LiteralData::Bool(b)) => b.to_string(),
+		PatternType::Literal(_) => "none".to_owned(),
+		PatternType::Attribute(attr) => format!("is {}", attr.name),
+		PatternType::Rest(inner) if matches!(inner.typ, PatternType::Wildcard) => "...".to_owned(),
+		PatternType::Rest(inner) => format!("...{}", self::pattern(inner)),
+		PatternType::List(items) => format!("[{}]", items.iter().map(self::pattern).collect::<Vec<_>>().join(", ")),
+		PatternType::Object(fields, attrs) => {
+			let attrs = attrs.iter().map(|attr| format!("is {}", attr.name));
+			let fields = fields.iter().map(|(key, value)| match &value.typ {
+				PatternType::Binding(name) if &name.name == key => key.clone(),
+				_ => format!("{} = {}", key, self::pattern(value)),
+			});
+			format!("{{ {} }}", attrs.chain(fields).collect::<Vec<_>>().join(", "))
+		}
+	}
+}
+
+fn parameter(param: &Param) -> String {
+	let name = match param.pattern {
+		Some(ref pattern) => annotated(&self::pattern(pattern), &param.typ),
+		None => annotated(&param.name.name, &param.typ),
+	};
+	match (param.rest, &param.default) {
+		(true, _) => format!("...{}", name),
+		(false, Some(_)) => format!("{} = ...", name),
+		(false, None) => name,
+	}
+}
+
+fn signature(name: &str, params: &[Param], ret: &Option<Type>) -> String {
+	let params: Vec<_> = params.iter().map(parameter).collect();
+	let sig = format!("{}({})", name, params.join(", "));
+	match ret {
+		Some(ret) => format!("{}: {}", sig, ret),
+		None => sig,
+	}
+}
+
+// walks the resolved tree, the resolver reuses the ids of closed scopes so they are looked up in a scope stack
+struct Indexer<'a> {
+	scopes: Vec<HashMap<usize, usize>>,
+	analysis: &'a mut Analysis,
+}
+
+impl Indexer<'_> {
+
+	fn module(&mut self, module: &Module) {
+		self.scopes.push(HashMap::new());
+		for (id, stmt) in module.env.iter() {
+			let (kind, detail) = match &stmt.typ {
+				StmtType::FuncDeclaration(data) => (DefKind::Function, format!("function {}", signature(&id.name, &data.params, &data.ret))),
+				StmtType::AttrDeclaration(_) => (DefKind::Attribute, format!("attr {}", id.name)),
+				StmtType::Import(data) => (DefKind::Import, format!("import \"{}\" as {}", data.path, id.name)),
+				_ => (DefKind::Constant, id.name.clone()),
+			};
+			self.declare(id, kind, detail);
+		}
+		for stmt in module.env.values() {
+			let _ = stmt.clone().accept(self);
+		}
+	}
+
+	fn block(&mut self, block: Block) {
+		self.scopes.push(HashMap::new());
+		for stmt in block {
+			let _ = stmt.accept(self);
+		}
+		self.scopes.pop();
+	}
+
+	fn define(&mut self, iden: &Identifier, kind: DefKind, detail: String) -> usize {
+		let def = self.analysis.definitions.len();
+		self.analysis.definitions.push(Definition { name: iden.get_name(), kind, pos: iden.pos, detail });
+		self.analysis.references.push((iden.pos, def));
+		def
+	}
+
+	fn declare(&mut self, iden: &Identifier, kind: DefKind, detail: String) {
+		// names starting with '$' come from desugaring and can't be written
+		if iden.name.starts_with('$') { return; }
+		let def = self.define(iden, kind, detail);
+		self.scopes.last_mut().unwrap().insert(iden.get_id(), def);
+	}
+
+	fn reference(&mut self, iden: &Identifier) {
+		let id = iden.get_id();
+		if let Some(def) = self.scopes.iter().rev().find_map(|scope| scope.get(&id)) {
+			self.analysis.references.push((iden.pos, *def));
+		}
+	}
+
+	fn function(&mut self, params: Vec<Param>, body: Block) {
+		self.scopes.push(HashMap::new());
+		for param in params {
+			let detail = format!("(parameter) {}", parameter(&param));
+			if let Some(default) = param.default { self.expr(default); }
+			self.declare(&param.name, DefKind::Parameter, detail);
+			for name in param.pattern.iter().flat_map(Pattern::bindings) {
+				self.declare(&name, DefKind::Parameter, format!("(parameter) {}", name));
+			}
+		}
+		self.block(body);
+		self.scopes.pop();
+	}
+
+	fn expr(&mut self, expr: Expression) {
+		let _ = expr.accept(self);
+	}
+
+}
+
+impl ExprVisitor<()> for Indexer<'_> {
+
+	fn literal(&mut self, data: LiteralData, _pos: SourcePos) -> Result<()> {
+		match data {
+			LiteralData::Template(exprs) | LiteralData::List(exprs) => exprs.into_iter().for_each(|expr| self.expr(expr)),
+			LiteralData::Map(entries) => entries.into_iter().for_each(|(key, val)| { self.expr(key); self.expr(val); }),
+			LiteralData::Object(fields, attrs, spreads) => {
+				fields.into_values().chain(spreads).for_each(|expr| self.expr(expr));
+				attrs.iter().for_each(|attr| self.reference(attr));
+			}
+			LiteralData::Error(expr) => self.expr(*expr),
+			_ => (),
+		}
+		Ok(())
+	}
+
+	fn binary(&mut self, data: BinaryData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.lhs);
+		self.expr(*data.rhs);
+		Ok(())
+	}
+
+	fn unary(&mut self, data: UnaryData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.expr);
+		Ok(())
+	}
+
+	fn logic(&mut self, data: LogicData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.lhs);
+		self.expr(*data.rhs);
+		Ok(())
+	}
+
+	fn grouping(&mut self, data: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		self.expr(*data);
+		Ok(())
+	}
+
+	fn variable(&mut self, data: Identifier, _pos: SourcePos) -> Result<()> {
+		self.reference(&data);
+		Ok(())
+	}
+
+	fn lambda(&mut self, data: LambdaData, _pos: SourcePos) -> Result<()> {
+		self.function(data.params, data.body);
+		Ok(())
+	}
+
+	fn call(&mut self, data: CallData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.calee);
+		data.args.into_iter().for_each(|arg| self.expr(arg));
+		data.named.into_iter().for_each(|arg| self.expr(arg.expr));
+		Ok(())
+	}
+
+	fn index(&mut self, data: IndexData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.head);
+		self.expr(*data.index);
+		Ok(())
+	}
+
+	fn field(&mut self, data: FieldData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.head);
+		Ok(())
+	}
+
+	fn self_ref(&mut self, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn do_expr(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(block);
+		Ok(())
+	}
+
+	fn bind_expr(&mut self, data: BindData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.expr);
+		self.expr(*data.method);
+		Ok(())
+	}
+
+	fn match_expr(&mut self, data: MatchData, _pos: SourcePos) -> Result<()> {
+		self.expr(*data.value);
+		for arm in data.arms {
+			self.scopes.push(HashMap::new());
+			for name in arm.pattern.bindings() {
+				self.declare(&name, DefKind::Binding, format!("(binding) {}", name));
+			}
+			if let Some(guard) = arm.guard { self.expr(guard); }
+			self.expr(arm.body);
+			self.scopes.pop();
+		}
+		Ok(())
+	}
+
+	fn spread(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		self.expr(*expr);
+		Ok(())
+	}
+
+	fn range(&mut self, data: RangeData, _pos: SourcePos) -> Result<()> {
+		for bound in data.start.into_iter().chain(data.end).chain(data.step) { self.expr(*bound); }
+		Ok(())
+	}
+
+}
+
+impl StmtVisitor<()> for Indexer<'_> {
+
+	fn expr(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		Indexer::expr(self, *expr);
+		Ok(())
+	}
+
+	fn declaration(&mut self, data: DeclarationData, _pos: SourcePos) -> Result<()> {
+		let (kind, keyword) = if data.constant { (DefKind::Constant, "const") } else { (DefKind::Variable, "let") };
+		let detail = format!("{} {}", keyword, annotated(&data.name.name, &data.typ));
+		// the same order the resolver declares names in, so lambdas and objects can refer to themselves
+		match data.expr.typ {
+			ExprType::Lambda(_) | ExprType::Literal(LiteralData::Object(..)) => {
+				self.declare(&data.name, kind, detail);
+				Indexer::expr(self, *data.expr);
+			}
+			_ => {
+				Indexer::expr(self, *data.expr);
+				self.declare(&data.name, kind, detail);
+			}
+		}
+		Ok(())
+	}
+
+	fn destructure(&mut self, data: DestructureData, _pos: SourcePos) -> Result<()> {
+		let (kind, keyword) = if data.constant { (DefKind::Constant, "const") } else { (DefKind::Variable, "let") };
+		Indexer::expr(self, *data.expr);
+		for name in data.pattern.bindings() {
+			self.declare(&name, kind, format!("{} {}", keyword, name));
+		}
+		Ok(())
+	}
+
+	fn func_declaration(&mut self, data: FunctionData, _pos: SourcePos) -> Result<()> {
+		self.function(data.params, data.body);
+		Ok(())
+	}
+
+	fn attr_declaration(&mut self, data: AttrDeclarationData, _pos: SourcePos) -> Result<()> {
+		let mut info = AttrInfo::default();
+		for method in data.methods {
+			info.methods.push(method.name.get_name());
+			let detail = format!("(method of {}) {}", data.name, signature(&method.name.name, &method.params, &method.ret));
+			// methods are looked up by name at runtime, so nothing refers to them by id
+			self.define(&method.name, DefKind::Method, detail);
+			self.function(method.params, method.body);
+		}
+		for (name, expr) in data.fields {
+			info.fields.push(name);
+			Indexer::expr(self, expr);
+		}
+		data.attributes.iter().for_each(|attr| self.reference(attr));
+		self.analysis.attributes.insert(data.name.get_name(), info);
+		Ok(())
+	}
+
+	fn import(&mut self, _data: ImportData, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn assignment(&mut self, data: AssignData, _pos: SourcePos) -> Result<()> {
+		Indexer::expr(self, *data.head);
+		Indexer::expr(self, *data.expr);
+		Ok(())
+	}
+
+	fn if_stmt(&mut self, data: IfData, _pos: SourcePos) -> Result<()> {
+		Indexer::expr(self, *data.cond);
+		self.block(data.then_block);
+		self.block(data.else_block);
+		Ok(())
+	}
+
+	fn loop_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(block);
+		Ok(())
+	}
+
+	fn break_stmt(&mut self, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn continue_stmt(&mut self, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn return_stmt(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		Indexer::expr(self, *expr);
+		Ok(())
+	}
+
+	fn scoped_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(block);
+		Ok(())
+	}
+
+}
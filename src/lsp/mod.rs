@@ -0,0 +1,320 @@
+mod analysis;
+
+use std::{collections::HashMap, io::{self, BufRead, Write}};
+
+use serde_json::{json, Value as Json};
+
+use crate::{interpreter::globals::init_globals, utils::{global_ids::GLOBAL_IDS, result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+use self::analysis::{Analysis, DefKind};
+
+// LSP error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+type Response<T = Json> = std::result::Result<T, (i64, String)>;
+
+struct Document {
+	text: String,
+	analysis: Analysis,
+	// the last version that parsed, names are looked up in it while this one doesn't
+	fallback: Option<Box<Document>>,
+}
+
+impl Document {
+	fn new(uri: &str, text: String) -> Self {
+		let analysis = Analysis::new(uri, &text);
+		Self { text, analysis, fallback: None }
+	}
+
+	fn indexed(&self) -> &Document {
+		self.fallback.as_deref().unwrap_or(self)
+	}
+
+	// LSP columns count UTF-16 code units from 0, ours count chars from 1
+	fn to_pos(&self, position: &Json) -> Option<(u32, u32)> {
+		let line = position["line"].as_u64()? as usize;
+		let character = position["character"].as_u64()? as usize;
+		let text = self.text.lines().nth(line).unwrap_or("");
+		let mut units = 0;
+		let col = text.chars().take_while(|c| { units += c.len_utf16(); units <= character }).count();
+		Some((line as u32 + 1, col as u32 + 1))
+	}
+
+	fn to_position(&self, lin: u32, col: u32) -> Json {
+		let line = lin.saturating_sub(1);
+		let text = self.text.lines().nth(line as usize).unwrap_or("");
+		let character: usize = text.chars().take(col.saturating_sub(1) as usize).map(char::len_utf16).sum();
+		json!({ "line": line, "character": character })
+	}
+
+	fn to_range(&self, pos: SourcePos) -> Json {
+		// positions are inclusive on both ends, LSP ranges exclude their end
+		let (end_lin, end_col) = if pos.end_lin < pos.lin || (pos.end_lin == pos.lin && pos.end_col < pos.col) { (pos.lin, pos.col) } else { (pos.end_lin, pos.end_col) };
+		json!({ "start": self.to_position(pos.lin, pos.col), "end": self.to_position(end_lin, end_col + 1) })
+	}
+}
+
+// the members of every native attribute and module, offered after a dot
+fn native_members() -> Vec<(String, Vec<String>)> {
+	let globals = init_globals();
+	GLOBAL_IDS.iter().enumerate().filter_map(|(i, name)| {
+		let value = globals.get(&(i + 1))?;
+		let pos = SourcePos::new(0, 0);
+		let members = match (value.to_attr(pos), value.to_obj(pos)) {
+			(Ok(attr), _) => attr.method_names().into_iter().chain(attr.field_names()).collect(),
+			(_, Ok(obj)) => obj.keys().cloned().collect(),
+			_ => return None,
+		};
+		Some((name.to_string(), members))
+	}).collect()
+}
+
+struct Server {
+	documents: HashMap<String, Document>,
+	natives: Vec<(String, Vec<String>)>,
+	shutdown: bool,
+}
+
+impl Server {
+
+	fn new() -> Self {
+		Self { documents: HashMap::new(), natives: native_members(), shutdown: false }
+	}
+
+	fn handle(&mut self, msg: Json, out: &mut impl Write) -> io::Result<bool> {
+		let method = msg["method"].as_str().unwrap_or_default();
+		let params = &msg["params"];
+
+		let Some(id) = msg.get("id") else {
+			match method {
+				"textDocument/didOpen" => {
+					let doc = &params["textDocument"];
+					self.open(doc["uri"].as_str().unwrap_or_default(), doc["text"].as_str().unwrap_or_default(), out)?;
+				}
+				"textDocument/didChange" => {
+					// the whole text is sent on every change
+					if let Some(text) = params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str()) {
+						self.open(params["textDocument"]["uri"].as_str().unwrap_or_default(), text, out)?;
+					}
+				}
+				"textDocument/didClose" => {
+					let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+					self.documents.remove(uri);
+					notify(out, "textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] }))?;
+				}
+				"exit" => return Ok(false),
+				_ => (),
+			}
+			return Ok(true);
+		};
+
+		let response = match method {
+			"initialize" => Ok(json!({
+				"capabilities": {
+					"textDocumentSync": 1,
+					"definitionProvider": true,
+					"referencesProvider": true,
+					"hoverProvider": true,
+					"completionProvider": { "triggerCharacters": ["."] },
+				},
+				"serverInfo": { "name": "rover", "version": env!("CARGO_PKG_VERSION") },
+			})),
+			"shutdown" => { self.shutdown = true; Ok(Json::Null) }
+			"textDocument/definition" => self.definition(params),
+			"textDocument/references" => self.references(params),
+			"textDocument/hover" => self.hover(params),
+			"textDocument/completion" => self.completion(params),
+			_ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+		};
+
+		let response = match response {
+			Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+			Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+		};
+		send(out, &response)?;
+		Ok(true)
+	}
+
+	fn open(&mut self, uri: &str, text: &str, out: &mut impl Write) -> io::Result<()> {
+		let mut doc = Document::new(uri, text.to_owned());
+		if !doc.analysis.parsed {
+			doc.fallback = self.documents.remove(uri).and_then(|prev| if prev.analysis.parsed { Some(Box::new(prev)) } else { prev.fallback });
+		}
+
+		let diagnostics: Vec<_> = doc.analysis.errors.iter().map(|(msg, pos)| json!({
+			"range": doc.to_range(pos.unwrap_or(SourcePos::new(1, 1))),
+			"severity": 1,
+			"source": "rover",
+			"message": msg,
+		})).collect();
+
+		self.documents.insert(uri.to_owned(), doc);
+		notify(out, "textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": diagnostics }))
+	}
+
+	// the document and the definition of the name under the cursor
+	fn lookup<'a>(&'a self, params: &'a Json) -> Response<(&'a str, &'a Document, Option<usize>)> {
+		let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+		let doc = self.document(uri)?.indexed();
+		let (lin, col) = doc.to_pos(&params["position"]).ok_or((INVALID_PARAMS, "Invalid position".to_owned()))?;
+		Ok((uri, doc, doc.analysis.reference_at(lin, col)))
+	}
+
+	fn document(&self, uri: &str) -> Response<&Document> {
+		self.documents.get(uri).ok_or((INVALID_PARAMS, format!("Unknown document '{}'", uri)))
+	}
+
+	fn definition(&self, params: &Json) -> Response {
+		let (uri, doc, def) = self.lookup(params)?;
+		Ok(match def {
+			Some(def) => json!({ "uri": uri, "range": doc.to_range(doc.analysis.definitions[def].pos) }),
+			None => Json::Null,
+		})
+	}
+
+	fn references(&self, params: &Json) -> Response {
+		let (uri, doc, def) = self.lookup(params)?;
+		let Some(def) = def else { return Ok(json!([])) };
+		let with_decl = params["context"]["includeDeclaration"].as_bool().unwrap_or(true);
+		let decl = doc.analysis.definitions[def].pos;
+		Ok(doc.analysis.references_to(def)
+			.filter(|pos| with_decl || *pos != decl)
+			.map(|pos| json!({ "uri": uri, "range": doc.to_range(pos) }))
+			.collect())
+	}
+
+	fn hover(&self, params: &Json) -> Response {
+		let (_, doc, def) = self.lookup(params)?;
+		let Some(def) = def else { return Ok(Json::Null) };
+		let def = &doc.analysis.definitions[def];
+		let kind = match def.kind {
+			DefKind::Function => "function",
+			DefKind::Attribute => "attribute",
+			DefKind::Method => "method",
+			DefKind::Import => "module",
+			DefKind::Variable => "variable",
+			DefKind::Constant => "constant",
+			DefKind::Parameter => "parameter",
+			DefKind::Binding => "pattern binding",
+		};
+		let mut text = format!("```mars\n{}\n```\n{}", def.detail, kind);
+		if let Some(info) = doc.analysis.attributes.get(&def.name).filter(|_| def.kind == DefKind::Attribute) {
+			if !info.methods.is_empty() { text.push_str(&format!("\n\nmethods: {}", info.methods.join(", "))); }
+			if !info.fields.is_empty() { text.push_str(&format!("\n\nstatic: {}", info.fields.join(", "))); }
+		}
+		Ok(json!({ "contents": { "kind": "markdown", "value": text } }))
+	}
+
+	fn completion(&self, params: &Json) -> Response {
+		// the text being typed is read from the current version, even when the names come from an older one
+		let doc = self.document(params["textDocument"]["uri"].as_str().unwrap_or_default())?;
+		let (lin, col) = doc.to_pos(&params["position"]).unwrap_or((1, 1));
+		let analysis = &doc.indexed().analysis;
+
+		// the text typed before the cursor decides whether members or names are completed
+		let line: String = doc.text.lines().nth(lin as usize - 1).unwrap_or("").chars().take(col as usize - 1).collect();
+		let prefix = line.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+
+		// CompletionItemKind values
+		const METHOD: u8 = 2; const FUNCTION: u8 = 3; const FIELD: u8 = 5; const VARIABLE: u8 = 6; const CLASS: u8 = 7; const MODULE: u8 = 9; const CONSTANT: u8 = 21;
+		let mut items = Vec::new();
+		let mut add = |label: &str, kind: u8, detail: &str| {
+			if !items.iter().any(|item: &Json| item["label"] == label) {
+				items.push(json!({ "label": label, "kind": kind, "detail": detail }));
+			}
+		};
+
+		if let Some(head) = prefix.strip_suffix('.') {
+			let head: String = head.chars().rev().take_while(|c| c.is_alphanumeric() || *c == '_').collect::<Vec<_>>().into_iter().rev().collect();
+			let native = self.natives.iter().find(|(name, _)| *name == head);
+			if let Some(info) = analysis.attributes.get(&head) {
+				info.fields.iter().for_each(|field| add(field, FIELD, &format!("static of {}", head)));
+				info.methods.iter().for_each(|method| add(method, METHOD, &format!("method of {}", head)));
+			} else if let Some((name, members)) = native {
+				members.iter().for_each(|member| add(member, METHOD, name));
+			} else {
+				// without types the receiver is unknown, so every method could apply
+				for (name, info) in analysis.attributes.iter() {
+					info.methods.iter().for_each(|method| add(method, METHOD, &format!("method of {}", name)));
+				}
+				for (name, members) in self.natives.iter().filter(|(name, _)| name.starts_with(char::is_uppercase)) {
+					members.iter().for_each(|member| add(member, METHOD, &format!("method of {}", name)));
+				}
+			}
+		} else {
+			for name in GLOBAL_IDS.iter().filter(|name| !name.starts_with('$')) {
+				let kind = match self.natives.iter().find(|(native, _)| native == name) {
+					Some(_) if name.starts_with(char::is_uppercase) => CLASS,
+					Some(_) => MODULE,
+					None => FUNCTION,
+				};
+				add(name, kind, "global");
+			}
+			// names declared before the cursor, locals of other functions are offered too since scopes aren't tracked here
+			for def in analysis.definitions.iter().filter(|def| def.kind != DefKind::Method && (def.pos.lin, def.pos.col) < (lin, col)) {
+				let kind = match def.kind {
+					DefKind::Function => FUNCTION,
+					DefKind::Attribute => CLASS,
+					DefKind::Import => MODULE,
+					DefKind::Constant => CONSTANT,
+					_ => VARIABLE,
+				};
+				add(&def.name, kind, &def.detail);
+			}
+			// module level names can be used before they are declared
+			for def in analysis.definitions.iter().filter(|def| matches!(def.kind, DefKind::Function | DefKind::Attribute | DefKind::Import)) {
+				let kind = match def.kind { DefKind::Function => FUNCTION, DefKind::Attribute => CLASS, _ => MODULE };
+				add(&def.name, kind, &def.detail);
+			}
+		}
+
+		Ok(Json::Array(items))
+	}
+
+}
+
+pub(crate) fn send(out: &mut impl Write, msg: &Json) -> io::Result<()> {
+	let body = msg.to_string();
+	write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+	out.flush()
+}
+
+fn notify(out: &mut impl Write, method: &str, params: Json) -> io::Result<()> {
+	send(out, &json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+}
+
+// messages are a set of headers, an empty line and a json body of Content-Length bytes
+pub(crate) fn receive(input: &mut impl BufRead) -> io::Result<Option<Json>> {
+	let mut length = None;
+	loop {
+		let mut header = String::new();
+		if input.read_line(&mut header)? == 0 { return Ok(None); }
+		let header = header.trim_end();
+		if header.is_empty() { break; }
+		if let Some(value) = header.strip_prefix("Content-Length:") {
+			length = value.trim().parse::<usize>().ok();
+		}
+	}
+	let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+	let mut body = vec![0; length];
+	input.read_exact(&mut body)?;
+	serde_json::from_slice(&body).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// speaks the language server protocol over stdin and stdout until the client exits
+pub fn run() -> Result<()> {
+	let stdin = io::stdin();
+	let mut input = stdin.lock();
+	let mut out = io::stdout();
+	let mut server = Server::new();
+
+	let io_err = |err: io::Error| ErrorList::sys(err.to_string());
+	while let Some(msg) = receive(&mut input).map_err(io_err)? {
+		if !server.handle(msg, &mut out).map_err(io_err)? { break; }
+	}
+
+	// exiting without a shutdown request first is an error for the client
+	if !server.shutdown { std::process::exit(1); }
+	().wrap()
+}
@@ -1,83 +1,181 @@
+use std::path::{Path, PathBuf};
 
-mod utils;
-mod lexer;
-mod ast;
-mod parser;
-mod semantics;
-mod interpreter;
-
-use std::path::Path;
-
-use interpreter::Interpreter;
-use lexer::{Lexer, LexerResult};
-use parser::Parser;
-use semantics::{resolver::Resolver, optimizer::Optimizer};
-use utils::result::{Result, ErrorList};
+use rover::{debugger, formatter, lsp, tester};
+use rover::interpreter::{Backend, Interpreter};
+use rover::profiler::Recorder;
+use rover::lexer::{Lexer, LexerResult};
+use rover::parser::Parser;
+use rover::repl::Repl;
+use rover::semantics::{resolver::Resolver, checker::Checker, optimizer::Optimizer};
+use rover::utils::result::{Result, ErrorList};
 
 fn main() {
-	let mut args = std::env::args().skip(1);
+	let mut args = std::env::args().skip(1).peekable();
 
-	if cfg!(windows) {
+	#[cfg(windows)]
+	{
 		ansi_term::enable_ansi_support().unwrap_or_else(|err| eprintln!("{}", ansi_term::Color::Red.paint(format!("[os error {}]", err))));
 	}
 
+	let mut backend = Backend::TreeWalk;
+	let mut check_only = false;
+	let mut profile = false;
+	let mut coverage = false;
+	let mut lcov_path = None;
+	while let Some(flag) = args.next_if(|arg| arg.starts_with("--")) {
+		match flag.as_str() {
+			"--vm" => backend = Backend::Bytecode,
+			"--check" => check_only = true,
+			"--profile" => profile = true,
+			"--coverage" => coverage = true,
+			_ if flag.starts_with("--coverage=") => {
+				coverage = true;
+				lcov_path = Some(PathBuf::from(&flag["--coverage=".len()..]));
+			}
+			_ => return eprintln!("{}: unknown flag '{}'", ansi_term::Color::Red.paint("error"), flag),
+		}
+	}
+
 	match args.next() {
-		Some(path) => run_file(&path, args.collect()).unwrap_or_else(|errors| errors.report(&path)),
-		None => eprintln!("{}: {}", ansi_term::Color::Red.paint("error"), "No file path specified"),
+		Some(cmd) if cmd == "repl" => Repl::new().run(),
+		Some(cmd) if cmd == "lsp" => lsp::run().unwrap_or_else(|errors| errors.report_source("lsp", "")),
+		Some(cmd) if cmd == "dap" => debugger::dap::run().unwrap_or_else(|errors| errors.report_source("dap", "")),
+		Some(cmd) if cmd == "debug" => match args.next() {
+			Some(path) => debugger::cli::run(&path, args.collect()).unwrap_or_else(|errors| errors.report(&path)),
+			None => eprintln!("{}: expected the path of the file to debug", ansi_term::Color::Red.paint("error")),
+		},
+		Some(cmd) if cmd == "test" => if !tester::run(args.collect(), backend) { std::process::exit(1) },
+		Some(cmd) if cmd == "fmt" => if !format_files(args.collect(), check_only) { std::process::exit(1) },
+		// only checking is meant for scripts and CI, so a failed check sets the exit code
+		Some(path) if check_only => if let Err(errors) = check_file(&path) {
+			errors.report(&path);
+			std::process::exit(1);
+		},
+		// the bytecode backend doesn't run through the interpreter's hooks
+		Some(_) if (profile || coverage) && backend == Backend::Bytecode => eprintln!("{}: --profile and --coverage only work without --vm", ansi_term::Color::Red.paint("error")),
+		Some(path) if profile || coverage => {
+			// unless told where, the lcov report goes next to the program
+			let lcov_path = coverage.then(|| lcov_path.unwrap_or_else(|| Path::new(&path).with_file_name("lcov.info")));
+			let recorder = Recorder::new(profile, lcov_path);
+			run_file(&path, backend, args.collect(), Some(recorder.clone())).unwrap_or_else(|errors| errors.report(&path));
+			recorder.report();
+		}
+		Some(path) => run_file(&path, backend, args.collect(), None).unwrap_or_else(|errors| errors.report(&path)),
+		None => Repl::new().run(),
 	}
 }
 
-fn run_file(path: &str, args: Vec<String>) -> Result<()> {
+fn run_file(path: &str, backend: Backend, args: Vec<String>, recorder: Option<Recorder>) -> Result<()> {
 	let lexer = Lexer::from_file(&path).map_err(|err| ErrorList::sys(err.to_string()))?;
 
 	let lexer_res = lexer.scan_tokens();
 
 	if lexer_res.directives.contains("script") {
-		run_script(path, lexer_res, args)
+		run_script(path, lexer_res, backend, args, recorder)
 	} else {
-		run_module(path, lexer_res, args)
+		run_module(path, lexer_res, backend, args, recorder)
 	}
 }
 
-fn run_module(path: &str, lexer_res: LexerResult, args: Vec<String>) -> Result<()> {
-	let LexerResult { tokens, directives: _, mut errors } = lexer_res;
+fn check_file(path: &str) -> Result<()> {
+	let lexer = Lexer::from_file(path).map_err(|err| ErrorList::sys(err.to_string()))?;
+
+	let LexerResult { tokens, directives, mut errors, .. } = lexer.scan_tokens();
+
+	let mut resolver = Resolver::new();
+	let mut checker = Checker::new();
+	if directives.contains("script") {
+		let (module, block) = Parser::new(tokens).script()?;
+		errors.try_append(resolver.resolve(&module));
+		errors.try_append(resolver.resolve_block(&block));
+		errors.try_append(checker.check(&module));
+		errors.try_append(checker.check_block(&block));
+	} else {
+		let module = Parser::new(tokens).module()?;
+		errors.try_append(resolver.resolve(&module));
+		errors.try_append(checker.check(&module));
+	}
+
+	errors.if_empty(())
+}
+
+// rewrites every file in place, or with check_only just lists the ones that aren't formatted
+fn format_files(paths: Vec<String>, check_only: bool) -> bool {
+	if paths.is_empty() {
+		eprintln!("{}: expected the paths of the files to format", ansi_term::Color::Red.paint("error"));
+		return false;
+	}
+	let mut ok = true;
+	for path in paths {
+		let res = std::fs::read_to_string(&path).map_err(|err| ErrorList::sys(err.to_string())).and_then(|text| {
+			let formatted = formatter::format(&path, &text)?;
+			if formatted == text { return Ok(true); }
+			if check_only { return Ok(false); }
+			std::fs::write(&path, formatted).map_err(|err| ErrorList::sys(err.to_string()))?;
+			Ok(true)
+		});
+		match res {
+			Ok(true) => (),
+			Ok(false) => { println!("{}", path); ok = false }
+			Err(errors) => { errors.report(&path); ok = false }
+		}
+	}
+	ok
+}
+
+fn run_module(path: &str, lexer_res: LexerResult, backend: Backend, args: Vec<String>, recorder: Option<Recorder>) -> Result<()> {
+	let LexerResult { tokens, mut errors, .. } = lexer_res;
 
 	let mut module = Parser::new(tokens).module()?;
 
-	errors.try_append(Resolver::new().resolve(&module));
+	let mut resolver = Resolver::new();
+	errors.try_append(resolver.resolve(&module));
+	errors.try_append(Checker::new().check(&module));
 
 	errors.if_empty(())?;
 
-	Optimizer.optimize(&mut module).unwrap();
+	Optimizer.optimize(&mut module)?;
 
 	let mut pathbuf = Path::new(path).to_path_buf();
 	pathbuf.pop();
 
 	let mut interpreter = Interpreter::new(pathbuf);
+	interpreter.backend = backend;
+	interpreter.modules.reserve(resolver.max_id());
+	interpreter.modules.enter(Path::new(path));
+	if let Some(recorder) = recorder { interpreter.hook = Some(Box::new(recorder)); }
 
 	interpreter.interpret_and_run(module, args)?;
 
 	Ok(())
 }
 
-fn run_script(path: &str, lexer_res: LexerResult, _args: Vec<String>) -> Result<()> {
-	let LexerResult { tokens, directives: _, mut errors } = lexer_res;
+fn run_script(path: &str, lexer_res: LexerResult, backend: Backend, _args: Vec<String>, recorder: Option<Recorder>) -> Result<()> {
+	let LexerResult { tokens, mut errors, .. } = lexer_res;
 
 	let (mut module, block) = Parser::new(tokens).script()?;
 
 	let mut resolver = Resolver::new();
 	errors.try_append(resolver.resolve(&module));
 	errors.try_append(resolver.resolve_block(&block));
+
+	let mut checker = Checker::new();
+	errors.try_append(checker.check(&module));
+	errors.try_append(checker.check_block(&block));
 	
 	errors.if_empty(())?;
 
-	Optimizer.optimize(&mut module).unwrap();
-	let block = Optimizer.optimize_block(block).unwrap();
+	Optimizer.optimize(&mut module)?;
+	let block = Optimizer.optimize_block(block)?;
 
 	let mut pathbuf = Path::new(path).to_path_buf();
 	pathbuf.pop();
 
 	let mut interpreter = Interpreter::new(pathbuf);
+	interpreter.backend = backend;
+	interpreter.modules.reserve(resolver.max_id());
+	interpreter.modules.enter(Path::new(path));
+	if let Some(recorder) = recorder { interpreter.hook = Some(Box::new(recorder)); }
 
 	interpreter.interpret_script(module, block)?;
 
@@ -1,7 +1,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::{ast::{identifier::Identifier, expression::{*, BinaryOperator::{self, *}, ExprType::{self, *}, UnaryOperator::{self, *}}, Block, statement::{DeclarationData, IfData, StmtType}}, lexer::token::{Keyword::*, LiteralType, Symbol::*, Token, TokenType::{*, self}}, utils::{result::{ErrorList, Result, append}, source_pos::SourcePos, wrap::Wrap}};
+use crate::{ast::{identifier::Identifier, expression::{*, BinaryOperator::{self, *}, ExprType::{self, *}, UnaryOperator::{self, *}}, pattern::{Pattern, PatternType}, types::Type, Block, statement::{DeclarationData, IfData, StmtType}}, lexer::token::{Keyword::{*, Match}, LiteralType, Symbol::*, Token, TokenType::{*, self}}, utils::{result::{ErrorList, Result, append}, source_pos::SourcePos, wrap::Wrap}};
 
 use super::Parser;
 
@@ -44,17 +44,17 @@ fn lg_operator_for_token(token: &Token) -> LogicOperator {
 
 fn err_handler(expr: Expression, handler: Block, pos: SourcePos) -> Expression {
 	ExprType::DoExpr(vec![
-		StmtType::Declaration(DeclarationData { constant: true, name: Identifier::new("$res".to_owned()), expr: expr.wrap() }).to_stmt(pos),
+		StmtType::Declaration(DeclarationData { constant: true, name: Identifier::new("$res".to_owned(), pos), typ: None, expr: expr.wrap() }).to_stmt(pos),
 		StmtType::If(IfData {
 			cond: ExprType::Binary(BinaryData { 
-				lhs: ExprType::Variable(Identifier::new("$res".to_owned())).to_expr(pos).wrap(),
+				lhs: ExprType::Variable(Identifier::new("$res".to_owned(), pos)).to_expr(pos).wrap(),
 				op: BinaryOperator::Typ,
-				rhs: ExprType::Variable(Identifier::new("Error".to_owned())).to_expr(pos).wrap(),
+				rhs: ExprType::Variable(Identifier::new("Error".to_owned(), pos)).to_expr(pos).wrap(),
 			}).to_expr(pos).wrap(),
 			then_block: handler,
 			else_block: vec![],
 		}).to_stmt(pos),
-		StmtType::Expr(ExprType::Variable(Identifier::new("$res".to_owned())).to_expr(pos).wrap()).to_stmt(pos),
+		StmtType::Expr(ExprType::Variable(Identifier::new("$res".to_owned(), pos)).to_expr(pos).wrap()).to_stmt(pos),
 	]).to_expr(pos)
 }
 	
@@ -76,12 +76,12 @@ impl Parser {
 		fn access(parser: &mut Parser) -> ExprResult {
 			let Token { typ, pos } = parser.next();
 			if let Identifier(name) = typ {
-				let mut head = ExprType::Variable(Identifier::new(name)).to_expr(pos);
+				let mut head = ExprType::Variable(Identifier::new(name, pos)).to_expr(pos);
 				loop {
 					let peek = parser.peek();
 					head = match peek.typ {
-						Symbol(OpenSqr) => parser.index(head)?,
-						Symbol(Dot) => parser.field(head)?,
+						Symbol(OpenSqr) => parser.index(head, false)?,
+						Symbol(Dot) => { parser.next(); parser.field(head, false)? }
 						_ => return head.wrap(),
 					}
 				}
@@ -90,26 +90,38 @@ impl Parser {
 			}
 		}
 		
-		let mut expr = self.logic()?;
+		let mut expr = self.coalesce()?;
 		
-		if let Some(Token { pos, .. }) = self.optional(Symbol(DoubleColon)) {
+		if self.optional(Symbol(DoubleColon)).is_some() {
 			let method = access(self)?;
+			let pos = expr.pos.to(method.pos);
 			expr = ExprType::Binding(BindData { expr: Box::new(expr), method: Box::new(method) }).to_expr(pos);
 		}
 
 		if self.next_match(Symbol(OpenPar)) {
-			expr = self.function_call(expr)?;
+			expr = self.function_call(expr, false)?;
 		}
 		
 		expr.wrap()
 	}
 	
+	fn coalesce(&mut self) -> ExprResult {
+		let mut left = self.logic()?;
+		while self.optional(Symbol(DoubleQuestion)).is_some() {
+			let right = self.logic()?;
+			let pos = left.pos.to(right.pos);
+			left = Logic(LogicData { lhs: Box::new(left), op: LogicOperator::Coalesce, rhs: Box::new(right) }).to_expr(pos);
+		}
+		left.wrap()
+	}
+
 	fn logic(&mut self) -> ExprResult {
 		let mut left = self.equality()?;
 		while let Some(token) = self.optional_any(&[Keyword(And), Keyword(Or)]) {
 			let op = lg_operator_for_token(&token);
 			let right = self.equality()?;
-			left = Logic(LogicData { lhs: Box::new(left), op, rhs: Box::new(right) }).to_expr(token.pos);
+			let pos = left.pos.to(right.pos);
+			left = Logic(LogicData { lhs: Box::new(left), op, rhs: Box::new(right) }).to_expr(pos);
 		}
 		left.wrap()
 	}
@@ -119,7 +131,8 @@ impl Parser {
 		while let Some(token) = self.optional_any(operators) {
 			let op = bin_operation_for_token(&token);
 			let right = operand(self)?;
-			left = Binary(BinaryData { lhs: Box::new(left), op, rhs: Box::new(right) }).to_expr(token.pos);
+			let pos = left.pos.to(right.pos);
+			left = Binary(BinaryData { lhs: Box::new(left), op, rhs: Box::new(right) }).to_expr(pos);
 		}
 		left.wrap()
 	}
@@ -129,7 +142,32 @@ impl Parser {
 	}
 	
 	fn comparison(&mut self) -> ExprResult {
-		self.binary(|parser| parser.term(), &[Symbol(CloseAng), Symbol(CloseAngEquals), Symbol(OpenAng), Symbol(OpenAngEquals), Keyword(Is)])
+		self.binary(|parser| parser.range(), &[Symbol(CloseAng), Symbol(CloseAngEquals), Symbol(OpenAng), Symbol(OpenAngEquals), Keyword(Is)])
+	}
+
+	// `a..b` or `a..=b`, followed by an optional `step n`. the bounds can be left out for slicing, like in `xs[1..]`
+	fn range(&mut self) -> ExprResult {
+		let start = match self.peek().typ {
+			Symbol(DoubleDot) | Symbol(DoubleDotEquals) => None,
+			_ => Some(self.term()?),
+		};
+		let Some(token) = self.optional_any(&[Symbol(DoubleDot), Symbol(DoubleDotEquals)]) else {
+			return start.expect("the start is only left out before a range operator").wrap();
+		};
+		let inclusive = token.typ == Symbol(DoubleDotEquals);
+		let end = match self.peek().typ {
+			Symbol(CloseSqr) | Symbol(ClosePar) | Symbol(OpenBracket) | Symbol(CloseBracket) | Symbol(Comma) | EOL | EOF if !inclusive => None,
+			_ => Some(self.term()?),
+		};
+		let step = match self.peek().typ {
+			Identifier(name) if name == "step" && end.is_some() => {
+				self.next();
+				Some(self.term()?)
+			}
+			_ => None,
+		};
+		let pos = start.as_ref().map_or(token.pos, |start| start.pos).to(self.last);
+		Range(RangeData { start: start.map(Box::new), end: end.map(Box::new), inclusive, step: step.map(Box::new) }).to_expr(pos).wrap()
 	}
 	
 	fn term(&mut self) -> ExprResult {
@@ -144,7 +182,8 @@ impl Parser {
 		if let Some(token) = self.optional_any(&[Symbol(Exclam), Symbol(Minus), Symbol(Plus)]) {
 			let op = un_operator_for_token(&token);
 			let expr = self.unary()?;
-			Unary(UnaryData { op, expr: Box::new(expr) }).to_expr(token.pos).wrap()
+			let pos = token.pos.to(expr.pos);
+			Unary(UnaryData { op, expr: Box::new(expr) }).to_expr(pos).wrap()
 		} else {
 			self.pipe_infix()
 		}
@@ -153,9 +192,10 @@ impl Parser {
 	fn pipe_infix(&mut self) -> ExprResult {
 		let mut expr = self.postfix()?;
 		while let Symbol(BarCloseAng) = self.peek().typ {
-			let Token { pos, .. } = self.next();
+			self.next();
 			let calee = self.postfix()?;
-			expr = Call(CallData { calee: Box::new(calee), args: vec![expr] }).to_expr(pos);
+			let pos = expr.pos.to(calee.pos);
+			expr = Call(CallData { calee: Box::new(calee), args: vec![expr], named: vec![], optional: false }).to_expr(pos);
 		}
 		expr.wrap()
 	}
@@ -164,18 +204,29 @@ impl Parser {
 		let mut expr = self.primary()?;
 		loop {
 			expr = match self.peek().typ {
-				Symbol(OpenPar) => self.function_call(expr)?,
-				Symbol(OpenSqr) => self.index(expr)?,
-				Symbol(Dot) => self.field(expr)?,
+				Symbol(OpenPar) => self.function_call(expr, false)?,
+				Symbol(OpenSqr) => self.index(expr, false)?,
+				Symbol(Dot) => { self.next(); self.field(expr, false)? }
+				// `?.` goes before the field name, the arguments of a call or an index
+				Symbol(QuestionDot) => {
+					self.next();
+					match self.peek().typ {
+						Symbol(OpenPar) => self.function_call(expr, true)?,
+						Symbol(OpenSqr) => self.index(expr, true)?,
+						_ => self.field(expr, true)?,
+					}
+				}
 				Symbol(Question) => {
-					let Token { pos, .. } = self.next();
-					err_handler(expr, vec![StmtType::Return(ExprType::Variable(Identifier::new("$res".to_owned())).to_expr(pos).wrap()).to_stmt(pos)], pos)
+					let pos = expr.pos.to(self.next().pos);
+					err_handler(expr, vec![StmtType::Return(ExprType::Variable(Identifier::new("$res".to_owned(), pos)).to_expr(pos).wrap()).to_stmt(pos)], pos)
 				}
 				Symbol(Exclam) => {
-					let Token { pos, .. } = self.next();
+					let pos = expr.pos.to(self.next().pos);
 					err_handler(expr, vec![StmtType::Expr(ExprType::Call(CallData {
-						calee: ExprType::Variable(Identifier::new("abort".to_owned())).to_expr(pos).wrap(),
-						args: vec![ExprType::Variable(Identifier::new("$res".to_owned())).to_expr(pos)],
+						calee: ExprType::Variable(Identifier::new("abort".to_owned(), pos)).to_expr(pos).wrap(),
+						args: vec![ExprType::Variable(Identifier::new("$res".to_owned(), pos)).to_expr(pos)],
+						named: vec![],
+						optional: false,
 					}).to_expr(pos).wrap()).to_stmt(pos)], pos)
 				}
 				_ => return expr.wrap(),
@@ -183,6 +234,17 @@ impl Parser {
 		}
 	}
 	
+	// an expression, or a `...` spreading the items of one in its place
+	fn item(&mut self) -> Result<Expression> {
+		match self.optional(Symbol(Ellipsis)) {
+			Some(token) => {
+				let expr = self.expression()?;
+				ExprType::Spread(Box::new(expr)).to_expr(token.pos.to(self.last)).wrap()
+			}
+			None => self.expression(),
+		}
+	}
+
 	fn expr_list(&mut self, stop: TokenType) -> Result<Vec<Expression>> {
 		let mut exprs = Vec::new();
 		let mut errors = ErrorList::new();
@@ -193,7 +255,7 @@ impl Parser {
 				EOF => append!(ret comp "Unexpected EOF".to_owned(), peek.pos; to errors),
 				typ if typ == stop => break,
 				_ => {
-					match self.expression() {
+					match self.item() {
 						Ok(expr) => exprs.push(expr),
 						Err(err) => errors.append(err),
 					}
@@ -208,28 +270,63 @@ impl Parser {
 		errors.if_empty(exprs)
 	}
 	
-	fn function_call(&mut self, calee: Expression) -> ExprResult {
-		let Token { pos, .. } = self.next();
-		let args = self.expr_list(Symbol(ClosePar))?;
-		self.expect(Symbol(ClosePar))?;
-		Call(CallData { calee: Box::new(calee), args }).to_expr(pos).wrap()
+	// like expr_list, but arguments written as `name = value` are passed by name
+	fn call_args(&mut self) -> Result<(Vec<Expression>, Vec<NamedArg>)> {
+		let mut args = Vec::new();
+		let mut named = Vec::new();
+		let mut errors = ErrorList::new();
+		loop {
+			self.skip_new_lines();
+			let peek = self.peek();
+			match peek.typ {
+				EOF => append!(ret comp "Unexpected EOF".to_owned(), peek.pos; to errors),
+				Symbol(ClosePar) => break,
+				_ => {
+					match self.item() {
+						Ok(Expression { typ: Variable(name), pos }) if self.optional(Symbol(Equals)).is_some() => match self.expression() {
+							Ok(expr) => named.push(NamedArg { name: name.name, pos, expr }),
+							Err(err) => errors.append(err),
+						},
+						Ok(expr) if !named.is_empty() => errors.add_comp("Positional arguments can't follow named ones".to_owned(), expr.pos),
+						Ok(expr) => args.push(expr),
+						Err(err) => errors.append(err),
+					}
+					if self.peek().typ == Symbol(ClosePar) { continue; }
+					if let Err(err) = self.expect_any(&[Symbol(Comma), EOL]) {
+						errors.append(err);
+						self.synchronize_complex(&[Symbol(Comma)], &[Symbol(ClosePar)]);
+					}
+				}
+			}
+		}
+		errors.if_empty((args, named))
+	}
+	
+	fn function_call(&mut self, calee: Expression, optional: bool) -> ExprResult {
+		self.next();
+		let (args, named) = self.call_args()?;
+		let end = self.expect(Symbol(ClosePar))?;
+		let pos = calee.pos.to(end.pos);
+		Call(CallData { calee: Box::new(calee), args, named, optional }).to_expr(pos).wrap()
 	}
 	
-	fn index(&mut self, head: Expression) -> ExprResult {
-		let Token { pos, .. } = self.next();
+	fn index(&mut self, head: Expression, optional: bool) -> ExprResult {
+		self.next();
 		let index = self.expression()?;
-		self.expect(Symbol(CloseSqr))?;
-		Index(IndexData { head: Box::new(head), index: Box::new(index) }).to_expr(pos).wrap()
+		let end = self.expect(Symbol(CloseSqr))?;
+		let pos = head.pos.to(end.pos);
+		Index(IndexData { head: Box::new(head), index: Box::new(index), optional }).to_expr(pos).wrap()
 	}
 	
-	fn field(&mut self, head: Expression) -> ExprResult {
-		let Token { pos, .. } = self.next();
+	// the name of a field, right after its dot
+	fn field(&mut self, head: Expression, optional: bool) -> ExprResult {
 		let next = self.next();
 		let field = match next.typ {
 			Identifier(name) => name,
 			_ => return ErrorList::comp(format!("Expected identifier, found {}", next), next.pos).err()
 		};
-		ExprType::FieldGet(FieldData { head: Box::new(head), field }).to_expr(pos).wrap()
+		let pos = head.pos.to(next.pos);
+		ExprType::FieldGet(FieldData { head: Box::new(head), field, optional }).to_expr(pos).wrap()
 	}
 	
 	fn primary(&mut self) -> ExprResult {
@@ -241,6 +338,7 @@ impl Parser {
 			Keyword(Function) => self.lambda()?,
 			Keyword(_Self) => SelfRef,
 			Keyword(Do) => DoExpr(self.block()?),
+			Keyword(Match) => self.match_expr()?,
 			Keyword(Error) => ExprType::Literal(LiteralData::Error(Box::new(self.expression_or_none()?))),
 			TokenType::Literal(lit) => match lit {
 				LiteralType::Num(n) => ExprType::Literal(LiteralData::Num(n)),
@@ -253,18 +351,51 @@ impl Parser {
 			}
 			Symbol(OpenSqr) => self.list_literal()?,
 			Symbol(OpenBracket) => self.obj_literal()?,
-			Identifier(name) => Variable(Identifier::new(name)),
+			Identifier(name) => Variable(Identifier::new(name, token.pos)),
 			Template(tokens) => self.str_template(tokens)?,
 			_ => return ErrorList::comp(format!("Expected expression, found {}", token), token.pos).err()
-		}.to_expr(token.pos).wrap()
+		}.to_expr(token.pos.to(self.last)).wrap()
 	}
 	
 	fn list_literal(&mut self) -> Result<ExprType> {
+		self.skip_new_lines();
+		// `[:]` is an empty map, and a list whose first item is followed by a colon is a map
+		if self.optional(Symbol(Colon)).is_some() {
+			self.expect(Symbol(CloseSqr))?;
+			return ExprType::Literal(LiteralData::Map(Vec::new())).wrap();
+		}
+		let mut exprs = Vec::new();
+		if !self.next_match(Symbol(CloseSqr)) {
+			let first = self.item()?;
+			if !matches!(first.typ, ExprType::Spread(_)) && self.optional(Symbol(Colon)).is_some() {
+				return self.map_literal(first);
+			}
+			exprs.push(first);
+			if !self.next_match(Symbol(CloseSqr)) {
+				self.expect_any(&[Symbol(Comma), EOL])?;
+			}
+		}
+
 		let mut errors = ErrorList::new();
-		let exprs = append!(self.expr_list(Symbol(CloseSqr)); to errors; dummy vec![]);
+		exprs.append(&mut append!(self.expr_list(Symbol(CloseSqr)); to errors; dummy vec![]));
 		errors.try_append(self.expect(Symbol(CloseSqr)));
 		errors.if_empty(ExprType::Literal(LiteralData::List(exprs)))
 	}
+
+	fn map_literal(&mut self, first: Expression) -> Result<ExprType> {
+		let mut entries = vec![(first, self.expression()?)];
+		loop {
+			self.skip_new_lines();
+			if self.optional(Symbol(CloseSqr)).is_some() { break; }
+			self.expect_any(&[Symbol(Comma), EOL])?;
+			self.skip_new_lines();
+			if self.optional(Symbol(CloseSqr)).is_some() { break; }
+			let key = self.expression()?;
+			self.expect(Symbol(Colon))?;
+			entries.push((key, self.expression()?));
+		}
+		ExprType::Literal(LiteralData::Map(entries)).wrap()
+	}
 	
 	pub(super) fn obj_field(&mut self) -> Result<(String, Expression)> {
 		let next = self.next();
@@ -272,7 +403,7 @@ impl Parser {
 			let expr = if self.optional(Symbol(Equals)).is_some() {
 				self.expression()?
 			} else {
-				ExprType::Variable(Identifier::new(name.clone())).to_expr(next.pos)
+				ExprType::Variable(Identifier::new(name.clone(), next.pos)).to_expr(next.pos)
 			};
 			(name, expr).wrap()
 		} else {
@@ -284,6 +415,7 @@ impl Parser {
 		let mut errors = ErrorList::new();
 		let mut map = HashMap::new();
 		let mut attrs = HashSet::new();
+		let mut spreads = Vec::new();
 		loop {
 			self.skip_new_lines();
 			let peek = self.peek();
@@ -294,13 +426,21 @@ impl Parser {
 				},
 				Symbol(CloseBracket) => {
 					self.next();
-					return errors.if_empty(ExprType::Literal(LiteralData::Object(map, attrs)));
+					return errors.if_empty(ExprType::Literal(LiteralData::Object(map, attrs, spreads)));
+				},
+				Symbol(Ellipsis) => {
+					match self.item() {
+						Ok(expr) => spreads.push(expr),
+						Err(err) => { errors.append(err); continue },
+					}
+					if self.next_match(Symbol(CloseBracket)) { continue; }
+					errors.try_append(self.expect_any_or_sync(&[Symbol(Comma), EOL]));
 				},
 				Keyword(Is) => {
 					self.next();
 					let next = self.next();
 					match next.typ {
-						Identifier(name) => { attrs.insert(Identifier::new(name)); },
+						Identifier(name) => { attrs.insert(Identifier::new(name, next.pos)); },
 						typ => { errors.add_comp(format!("Expected identifier, found {}", typ), next.pos); self.synchronize() }
 					}
 					if self.next_match(Symbol(CloseBracket)) { continue; }
@@ -359,16 +499,22 @@ impl Parser {
 					return errors.err();
 				},
 				Symbol(ClosePar) => { self.next(); break; }
-				Identifier(name) if params.is_empty() => { self.next(); params.push(Identifier::new(name)); },
+				Identifier(_) | Symbol(Ellipsis) | Symbol(OpenSqr) | Symbol(OpenBracket) if params.is_empty() => match self.param(0) {
+					Ok(param) => params.push(param),
+					Err(err) => {
+						errors.append(err);
+						self.synchronize_until_any(&[Symbol(Comma), Symbol(ClosePar)]);
+					}
+				},
 				Symbol(Comma) => {
 					self.next();
 					self.skip_new_lines();
-					let next = self.next();
-					if let Identifier(name) = next.typ {
-						params.push(Identifier::new(name))
-					} else {
-						errors.add_comp(format!("Expected identifier, found {}", next), next.pos);
-						self.synchronize_until_any(&[Symbol(Comma), Symbol(ClosePar)]);
+					match self.param(params.len()) {
+						Ok(param) => params.push(param),
+						Err(err) => {
+							errors.append(err);
+							self.synchronize_until_any(&[Symbol(Comma), Symbol(ClosePar)]);
+						}
 					}
 				},
 				_ => {
@@ -381,6 +527,8 @@ impl Parser {
 				}
 			}
 		}
+
+		let ret = append!(self.annotation(); to errors; dummy None);
 		
 		let body = if let Some(Token { pos, .. }) = self.optional(Symbol(EqualsCloseAng)) {
 			let expr = append!(self.expression(); to errors);
@@ -389,12 +537,191 @@ impl Parser {
 			append!(self.block(); to errors)
 		};
 		
-		errors.if_empty(LambdaData { params, body })
+		errors.if_empty(LambdaData { params, ret, body })
+	}
+
+	// `name`, `name = default` or `...name`, any of them with a type annotation
+	fn param(&mut self, index: usize) -> Result<Param> {
+		let rest = self.optional(Symbol(Ellipsis)).is_some();
+		let next = self.peek();
+		let (name, pattern) = match next.typ {
+			Identifier(name) => {
+				self.next();
+				(Identifier::new(name, next.pos), None)
+			}
+			Symbol(OpenSqr) | Symbol(OpenBracket) => (Identifier::new(format!("${}", index), next.pos), Some(self.pattern()?)),
+			_ => return ErrorList::comp(format!("Expected identifier, found {}", self.next()), next.pos).err(),
+		};
+		let typ = self.annotation()?;
+		let default = match self.optional(Symbol(Equals)) {
+			Some(_) => Some(self.expression()?),
+			None => None,
+		};
+		Param { name, typ, default, rest, pattern }.wrap()
+	}
+
+	// an optional `: type` annotation, a trailing `?` also allows none
+	pub(super) fn annotation(&mut self) -> Result<Option<Type>> {
+		if self.optional(Symbol(Colon)).is_none() {
+			return None.wrap();
+		}
+		let next = self.next();
+		let typ = match next.typ {
+			Identifier(name) => Type::from_name(&name),
+			Keyword(_None) => Type::None,
+			Keyword(Function) => Type::Function,
+			Keyword(Error) => Type::Error,
+			typ => return ErrorList::comp(format!("Expected type, found {}", typ), next.pos).err(),
+		};
+		if self.optional(Symbol(Question)).is_some() {
+			typ.optional().wrap()
+		} else {
+			typ.wrap()
+		}
 	}
 	
 	fn lambda(&mut self) -> Result<ExprType> {
 		self.lambda_data().map(|data| ExprType::Lambda(data))
 	}
 	
+	fn match_expr(&mut self) -> Result<ExprType> {
+		let value = self.expression()?;
+		self.skip_new_lines();
+		self.expect(Symbol(OpenBracket))?;
+
+		let mut arms = Vec::new();
+		let mut errors = ErrorList::new();
+		loop {
+			self.skip_new_lines();
+			let peek = self.peek();
+			match peek.typ {
+				EOF => append!(ret comp "Unexpected EOF".to_owned(), peek.pos; to errors),
+				Symbol(CloseBracket) => { self.next(); break; }
+				_ => {
+					match self.match_arm() {
+						Ok(arm) => arms.push(arm),
+						Err(err) => {
+							errors.append(err);
+							self.synchronize_until_any(&[EOL, Symbol(Comma), Symbol(CloseBracket)]);
+						}
+					}
+					if self.next_match(Symbol(CloseBracket)) { continue; }
+					errors.try_append(self.expect_any_or_sync(&[Symbol(Comma), EOL]));
+				}
+			}
+		}
+
+		errors.if_empty(ExprType::Match(MatchData { value: value.wrap(), arms }))
+	}
+
+	fn match_arm(&mut self) -> Result<MatchArm> {
+		let pattern = self.pattern()?;
+		let guard = if self.optional(Keyword(If)).is_some() { Some(self.expression()?) } else { None };
+		self.expect(Symbol(EqualsCloseAng))?;
+		let body = self.expression()?;
+		MatchArm { pattern, guard, body }.wrap()
+	}
+
+	pub(super) fn pattern(&mut self) -> Result<Pattern> {
+		let token = self.next();
+		match token.typ {
+			Identifier(name) if name == "_" => PatternType::Wildcard,
+			Identifier(name) => PatternType::Binding(Identifier::new(name, token.pos)),
+			Keyword(Is) => {
+				let next = self.next();
+				match next.typ {
+					Identifier(name) => PatternType::Attribute(Identifier::new(name, next.pos)),
+					_ => return ErrorList::comp(format!("Expected identifier, found {}", next), next.pos).err(),
+				}
+			}
+			Keyword(False) => PatternType::Literal(LiteralData::Bool(false)),
+			Keyword(True) => PatternType::Literal(LiteralData::Bool(true)),
+			Keyword(_None) => PatternType::Literal(LiteralData::None),
+			TokenType::Literal(LiteralType::Num(n)) => PatternType::Literal(LiteralData::Num(n)),
+			TokenType::Literal(LiteralType::Str(s)) => PatternType::Literal(LiteralData::Str(s)),
+			Symbol(Minus) => {
+				let next = self.next();
+				match next.typ {
+					TokenType::Literal(LiteralType::Num(n)) => PatternType::Literal(LiteralData::Num(-n)),
+					_ => return ErrorList::comp(format!("Expected number, found {}", next), next.pos).err(),
+				}
+			}
+			// templates without any expressions in them are just strings
+			Template(tokens) => {
+				let mut text = String::new();
+				for part in tokens {
+					match part.typ {
+						TokenType::Literal(LiteralType::Str(s)) => text.push_str(&s),
+						EOF => break,
+						_ => return ErrorList::comp("String templates cannot be used as patterns".to_owned(), token.pos).err(),
+					}
+				}
+				PatternType::Literal(LiteralData::Str(text))
+			}
+			Symbol(OpenSqr) => self.list_pattern()?,
+			Symbol(OpenBracket) => self.obj_pattern()?,
+			_ => return ErrorList::comp(format!("Expected pattern, found {}", token), token.pos).err(),
+		}.to_pattern(token.pos.to(self.last)).wrap()
+	}
+
+	fn list_pattern(&mut self) -> Result<PatternType> {
+		let mut patterns = Vec::new();
+		loop {
+			self.skip_new_lines();
+			if self.optional(Symbol(CloseSqr)).is_some() { break; }
+			if let Some(token) = self.optional(Symbol(Ellipsis)) {
+				// a bare `...` ignores the items left
+				let pattern = match self.peek().typ {
+					Symbol(CloseSqr) | Symbol(Comma) | EOL => PatternType::Wildcard.to_pattern(token.pos),
+					_ => self.pattern()?,
+				};
+				patterns.push(PatternType::Rest(Box::new(pattern)).to_pattern(token.pos.to(self.last)));
+				self.skip_new_lines();
+				self.optional(Symbol(Comma));
+				self.skip_new_lines();
+				if self.optional(Symbol(CloseSqr)).is_none() {
+					return ErrorList::comp("A rest pattern must be the last one in its list".to_owned(), self.peek().pos).err();
+				}
+				break;
+			}
+			patterns.push(self.pattern()?);
+			self.skip_new_lines();
+			if self.optional(Symbol(CloseSqr)).is_some() { break; }
+			self.expect(Symbol(Comma))?;
+		}
+		PatternType::List(patterns).wrap()
+	}
+
+	fn obj_pattern(&mut self) -> Result<PatternType> {
+		let mut fields = Vec::new();
+		let mut attrs = Vec::new();
+		loop {
+			self.skip_new_lines();
+			if self.optional(Symbol(CloseBracket)).is_some() { break; }
+			let next = self.next();
+			match next.typ {
+				Keyword(Is) => {
+					let next = self.next();
+					match next.typ {
+						Identifier(name) => attrs.push(Identifier::new(name, next.pos)),
+						_ => return ErrorList::comp(format!("Expected identifier, found {}", next), next.pos).err(),
+					}
+				}
+				Identifier(name) => {
+					// a field without a pattern binds its value to a variable of the same name
+					let pattern = if self.optional(Symbol(Equals)).is_some() {
+						self.pattern()?
+					} else {
+						PatternType::Binding(Identifier::new(name.clone(), next.pos)).to_pattern(next.pos)
+					};
+					fields.push((name, pattern));
+				}
+				_ => return ErrorList::comp(format!("Expected identifier, found {}", next), next.pos).err(),
+			}
+			if self.optional(Symbol(CloseBracket)).is_some() { break; }
+			self.expect_any(&[Symbol(Comma), EOL])?;
+		}
+		PatternType::Object(fields, attrs).wrap()
+	}
+
 }
-	
\ No newline at end of file
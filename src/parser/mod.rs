@@ -4,12 +4,14 @@ pub mod statement;
 
 use std::{iter::Peekable, vec::IntoIter};
 
-use crate::{lexer::token::{Token, TokenType::{self, *}, Symbol}, utils::{result::{Result, ErrorList, append}, wrap::Wrap}, ast::{Block, module::Module}};
+use crate::{lexer::token::{Token, TokenType::{self, *}, Symbol}, utils::{result::{Result, ErrorList, append}, source_pos::SourcePos, wrap::Wrap}, ast::{Block, module::Module}};
 
 #[derive(Debug, Clone)]
 pub struct Parser {
 	tokens: Peekable<IntoIter<Token>>,
 	module: Module,
+	// the position of the last consumed token, where the span of the node being parsed ends
+	last: SourcePos,
 }
 
 impl Parser {
@@ -18,6 +20,7 @@ impl Parser {
 		Self {
 			tokens: tokens.into_iter().peekable(),
 			module: Module::new(),
+			last: SourcePos::new(1, 1),
 		}
 	}
 	
@@ -90,10 +93,12 @@ impl Parser {
 	
 	fn next(&mut self) -> Token {
 		let peek = self.peek();
-		match peek.typ {
+		let token = match peek.typ {
 			EOF => peek,
 			_ => self.tokens.next().unwrap(),
-		}
+		};
+		self.last = token.pos;
+		token
 	}
 	
 	fn expect(&mut self, expected: TokenType) -> Result<Token> {
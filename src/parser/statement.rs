@@ -1,7 +1,7 @@
 
-use std::collections::{HashMap, HashSet};
+use std::{collections::{HashMap, HashSet}, path::Path};
 
-use crate::{ast::{Block, expression::*, identifier::Identifier, statement::*}, lexer::token::{Keyword::*, Token, TokenType::{self, *}, Symbol::*}, utils::{result::{ErrorList, Result, append, throw}, wrap::Wrap}};
+use crate::{ast::{Block, expression::*, identifier::Identifier, statement::*}, lexer::token::{Keyword::*, LiteralType, Token, TokenType::{self, *}, Symbol::*}, utils::{result::{ErrorList, Result, append, throw}, source_pos::SourcePos, wrap::Wrap}};
 
 use super::Parser;
 
@@ -13,6 +13,7 @@ impl Parser {
 		match self.peek().typ {
 			Keyword(Attr) => self.attr_declaration(),
 			Keyword(Function) => self.func_declaration(),
+			Keyword(Import) => self.import_declaration(),
 			_ => ErrorList::comp("Expected a declaration".to_owned(), self.next().pos).err()
 		}
 	}
@@ -22,10 +23,10 @@ impl Parser {
 		let next = self.next();
 		match next.typ {
 			Identifier(name) => {
-				let id = Identifier::new(name);
+				let id = Identifier::new(name, next.pos);
 
-				let LambdaData { params, body } = self.lambda_data()?;
-				let decl = StmtType::FuncDeclaration(FunctionData { name: id.clone(), params, body }).to_stmt(pos);
+				let LambdaData { params, ret, body } = self.lambda_data()?;
+				let decl = StmtType::FuncDeclaration(FunctionData { name: id.clone(), params, ret, body }).to_stmt(pos);
 
 				self.module.add(id.clone(), decl, pos)?;
 
@@ -40,8 +41,8 @@ impl Parser {
 		let Token { pos, .. } = self.next();
 		let next = self.next();
 		let id = match next.typ { 
-			TokenType::Identifier(name) => Identifier::new(name),
-			typ => append!(ErrorList::comp(format!("Expected identifier, found {}", typ), next.pos).err(); to errors; dummy Identifier::new("".to_string())),
+			TokenType::Identifier(name) => Identifier::new(name, next.pos),
+			typ => append!(ErrorList::comp(format!("Expected identifier, found {}", typ), next.pos).err(); to errors; dummy Identifier::new("".to_string(), next.pos)),
 		};
 		self.skip_new_lines();
 		errors.try_append(self.expect(Symbol(OpenBracket)));
@@ -63,15 +64,15 @@ impl Parser {
 				Keyword(Is) => {
 					let next = self.next();
 					match next.typ {
-						Identifier(name) => { attributes.insert(Identifier::new(name)); },
+						Identifier(name) => { attributes.insert(Identifier::new(name, next.pos)); },
 						typ => { errors.add_comp(format!("Expected identifier, found {}", typ), next.pos); self.synchronize() },
 					}
 					if self.next_match(Symbol(CloseBracket)) { continue; }
 					errors.try_append(self.expect_eol());
 				}
 				Identifier(name) => {
-					let LambdaData { params, body } = self.lambda_data()?;
-					methods.push(FunctionData { name: Identifier::new(name), params, body });
+					let LambdaData { params, ret, body } = self.lambda_data()?;
+					methods.push(FunctionData { name: Identifier::new(name, next.pos), params, ret, body });
 					errors.try_append(self.expect_eol());
 				},
 				typ => append!(ret comp format!("Expected Identifier or CLOSE_BRACKET, found {}", typ), pos; to errors),
@@ -85,6 +86,35 @@ impl Parser {
 		Ok(())
 	}
 
+	fn import_declaration(&mut self) -> Result<()> {
+		let Token { pos, .. } = self.next();
+		let next = self.next();
+		let path = match next.typ {
+			Literal(LiteralType::Str(path)) => path,
+			typ => return ErrorList::comp(format!("Expected module path, found {}", typ), next.pos).err(),
+		};
+
+		let (name, name_pos) = if self.optional(Keyword(As)).is_some() {
+			let next = self.next();
+			match next.typ {
+				Identifier(name) => (name, next.pos),
+				typ => return ErrorList::comp(format!("Expected identifier, found {}", typ), next.pos).err(),
+			}
+		} else {
+			match Path::new(&path).file_stem().and_then(|stem| stem.to_str()) {
+				Some(stem) if stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !stem.starts_with(|c: char| c.is_ascii_digit()) => (stem.to_owned(), next.pos),
+				_ => return ErrorList::comp(format!("Cannot infer a module name from '{}', use 'as' to name it", path), next.pos).err(),
+			}
+		};
+
+		self.expect_eol()?;
+
+		let id = Identifier::new(name, name_pos);
+		let decl = StmtType::Import(ImportData { name: id.clone(), path }).to_stmt(pos);
+
+		self.module.add(id, decl, pos)
+	}
+
 	pub(super) fn statement(&mut self, accept_decl: bool) -> Result<Option<Statement>> {
 		match self.peek().typ {
 			Keyword(Let) => self.declaration(),
@@ -94,8 +124,9 @@ impl Parser {
 			Keyword(Break) => self.break_stmt(),
 			Keyword(Continue) => self.continue_stmt(),
 			Keyword(Return) => self.return_stmt(),
-			Keyword(Attr) => return if accept_decl	{ self.func_declaration()?; None.wrap() } else { ErrorList::comp("Attribute declarations are only allowed in the top level".to_owned(), self.next().pos).err() },
+			Keyword(Attr) => return if accept_decl	{ self.attr_declaration()?; None.wrap() } else { ErrorList::comp("Attribute declarations are only allowed in the top level".to_owned(), self.next().pos).err() },
 			Keyword(Function) => return if accept_decl	{ self.func_declaration()?; None.wrap() } else { ErrorList::comp("Function declarations are only allowed in the top level".to_owned(), self.next().pos).err() },
+			Keyword(Import) => return if accept_decl	{ self.import_declaration()?; None.wrap() } else { ErrorList::comp("Import declarations are only allowed in the top level".to_owned(), self.next().pos).err() },
 			_ => self.assignment_or_expression(),
 		}?.wrap()
 	}
@@ -105,7 +136,7 @@ impl Parser {
 		let l_pos = left.pos;
 		if let Some(token) = self.optional_any(ASSIGN_OPS) {
 			match left.typ {
-				ExprType::Variable(_) | ExprType::Index(_) | ExprType::FieldGet(_) => {
+				ExprType::Variable(_) | ExprType::Index(IndexData { optional: false, .. }) | ExprType::FieldGet(FieldData { optional: false, .. }) => {
 					let right = if let Symbol(Equals) = token.typ {
 						self.expression()?
 					} else {
@@ -136,21 +167,33 @@ impl Parser {
 		let Token { pos, .. } = self.next();
 		let mut errors = ErrorList::new();
 		let constant = self.optional(Keyword(Const)).is_some();
+		if let Symbol(OpenSqr) | Symbol(OpenBracket) = self.peek().typ {
+			return self.destructure(constant, pos);
+		}
 		let next = self.next();
 		let name = match next.typ { 
-			TokenType::Identifier(name) => Identifier::new(name),
-			typ => append!(ErrorList::comp(format!("Expected identifier, found {}", typ), next.pos).err(); to errors; dummy Identifier::new("".to_string())),
+			TokenType::Identifier(name) => Identifier::new(name, next.pos),
+			typ => append!(ErrorList::comp(format!("Expected identifier, found {}", typ), next.pos).err(); to errors; dummy Identifier::new("".to_string(), next.pos)),
 		};
+		let typ = append!(self.annotation(); to errors; dummy None);
 		let expr = match self.optional(Symbol(Equals)) {
 			Some(_) => append!(self.expression(); to errors),
 			None => ExprType::Literal(LiteralData::None).to_expr(next.pos),
 		};
 		errors.try_append(self.expect_eol());
 		errors.if_empty(
-			StmtType::Declaration(DeclarationData { constant, name, expr: Box::new(expr) }).to_stmt(pos)
+			StmtType::Declaration(DeclarationData { constant, name, typ, expr: Box::new(expr) }).to_stmt(pos)
 		)
 	}
 
+	fn destructure(&mut self, constant: bool, pos: SourcePos) -> Result<Statement> {
+		let pattern = self.pattern()?;
+		self.expect(Symbol(Equals))?;
+		let expr = self.expression()?;
+		self.expect_eol()?;
+		StmtType::Destructure(DestructureData { constant, pattern, expr: Box::new(expr) }).to_stmt(pos).wrap()
+	}
+
 	fn if_stmt(&mut self) -> Result<Statement> {
 		let Token { pos, .. } = self.next();
 		let mut errors = ErrorList::new();
@@ -176,62 +219,70 @@ impl Parser {
 		let Token { pos, .. } = self.next();
 		let mut errors = ErrorList::new();
 
-		let next = self.next();
-		if let Identifier(name) = next.typ {
-			errors.try_append(self.expect(Keyword(In)));
-			let list = append!(self.expression(); to errors; with {
-				self.synchronize_until(Symbol(OpenBracket));
-				append!(self.block(); to errors);
-			});
-			let body = append!(self.block(); to errors);
-
-			StmtType::Scoped(vec![
-				StmtType::Declaration(DeclarationData { constant: false, name: Identifier::new("$i".to_owned()), expr: ExprType::Literal(LiteralData::Num(-1.0)).to_expr(pos).wrap() }).to_stmt(pos),
-				StmtType::Declaration(DeclarationData { constant: true, name: Identifier::new("$list".to_owned()), expr: list.wrap() }).to_stmt(pos),
+		let var = |name: &str| ExprType::Variable(Identifier::new(name.to_owned(), pos)).to_expr(pos).wrap();
+
+		// each item is bound to the loop variable, or destructured into the names of a pattern
+		let next = self.peek();
+		let bind_item = match next.typ {
+			Identifier(name) => {
+				self.next();
+				StmtType::Declaration(DeclarationData { constant: false, name: Identifier::new(name, next.pos), typ: None, expr: var("$next") })
+			}
+			Symbol(OpenSqr) | Symbol(OpenBracket) => match self.pattern() {
+				Ok(pattern) => StmtType::Destructure(DestructureData { constant: false, pattern, expr: var("$next") }),
+				Err(err) => {
+					self.synchronize_with(Symbol(CloseBracket));
+					return Err(err);
+				}
+			}
+			_ => {
+				self.next();
+				self.synchronize_with(Symbol(CloseBracket));
+				return ErrorList::comp(format!("Expected identifier, found {}", next.typ), next.pos).err();
+			}
+		};
+
+		let in_pos = self.peek().pos;
+		errors.try_append(self.expect(Keyword(In)));
+		let list = append!(self.expression(); to errors; with {
+			self.synchronize_until(Symbol(OpenBracket));
+			append!(self.block(); to errors);
+		});
+		let body = append!(self.block(); to errors);
+
+		let list_pos = list.pos;
+
+		// the iterable is stepped through with next until it returns the done sentinel
+		// each part sits where it comes from in the header, so hooks don't count the whole loop at the for keyword
+		StmtType::Scoped(vec![
+			StmtType::Declaration(DeclarationData {
+				constant: true, name: Identifier::new("$it".to_owned(), list_pos), typ: None,
+				expr: ExprType::Call(CallData {
+					calee: ExprType::Variable(Identifier::new("$iter".to_owned(), list_pos)).to_expr(list_pos).wrap(),
+					args: vec![list],
+					named: vec![],
+					optional: false,
+				}).to_expr(list_pos).wrap(),
+			}).to_stmt(list_pos),
+			StmtType::Loop(vec![
 				StmtType::Declaration(DeclarationData {
-					constant: true, name: Identifier::new("$len".to_owned()),
+					constant: true, name: Identifier::new("$next".to_owned(), in_pos), typ: None,
 					expr: ExprType::Call(CallData {
-						calee: ExprType::FieldGet(FieldData {
-							head: ExprType::Variable(Identifier::new("$list".to_owned())).to_expr(pos).wrap(),
-							field: "size".to_owned(),
-						}).to_expr(pos).wrap(), 
-						args: vec![]
-					}).to_expr(pos).wrap()
-				}).to_stmt(pos),
-				StmtType::Loop(vec![
-					StmtType::Assignment(AssignData {
-						head: ExprType::Variable(Identifier::new("$i".to_owned())).to_expr(pos).wrap(),
-						l_pos: pos,
-						expr: ExprType::Binary(BinaryData {
-							lhs: ExprType::Variable(Identifier::new("$i".to_owned())).to_expr(pos).wrap(),
-							op: BinaryOperator::Add,
-							rhs: ExprType::Literal(LiteralData::Num(1.0)).to_expr(pos).wrap(),
-						}).to_expr(pos).wrap(),
-					}).to_stmt(pos),
-					StmtType::If(IfData { 
-						cond: Box::new(ExprType::Binary(BinaryData {
-							lhs: ExprType::Variable(Identifier::new("$i".to_owned())).to_expr(pos).wrap(),
-							op: BinaryOperator::Gre,
-							rhs: ExprType::Variable(Identifier::new("$len".to_owned())).to_expr(pos).wrap(),
-						}).to_expr(pos)),
-						then_block: vec![StmtType::Break.to_stmt(pos)],
-						else_block: vec![],
-					}).to_stmt(pos),
-					StmtType::Declaration(DeclarationData {
-						constant: false, name: Identifier::new(name),
-						expr: ExprType::Index(IndexData {
-							head: ExprType::Variable(Identifier::new("$list".to_owned())).to_expr(pos).wrap(),
-							index: ExprType::Variable(Identifier::new("$i".to_owned())).to_expr(pos).wrap(),
-						}).to_expr(pos).wrap(),
-					}).to_stmt(pos),
-					StmtType::Scoped(body).to_stmt(pos),
-				]).to_stmt(pos),
-			]).to_stmt(pos).wrap()
-
-		} else {
-			self.synchronize_with(Symbol(CloseBracket));
-			ErrorList::comp(format!("Expected identifier, found {}", next.typ), next.pos).err()
-		}
+						calee: ExprType::FieldGet(FieldData { head: var("$it"), field: "next".to_owned(), optional: false }).to_expr(in_pos).wrap(),
+						args: vec![],
+						named: vec![],
+						optional: false,
+					}).to_expr(in_pos).wrap(),
+				}).to_stmt(in_pos),
+				StmtType::If(IfData {
+					cond: ExprType::Binary(BinaryData { lhs: var("$next"), op: BinaryOperator::Equ, rhs: var("$done") }).to_expr(in_pos).wrap(),
+					then_block: vec![StmtType::Break.to_stmt(in_pos)],
+					else_block: vec![],
+				}).to_stmt(in_pos),
+				bind_item.to_stmt(next.pos),
+				StmtType::Scoped(body).to_stmt(pos),
+			]).to_stmt(pos),
+		]).to_stmt(pos).wrap()
 	}
 
 	fn break_stmt(&mut self) -> Result<Statement> {
@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests;
+
+use std::{collections::{BTreeMap, HashMap}, fmt::Write, path::Path};
+
+use crate::{ast::{expression::{ExprType, Expression, LiteralData, Param}, statement::{Statement, StmtType}}, interpreter::{Branch, is_loop_step}, utils::source_pos::SourcePos};
+
+use super::file_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind { If, Loop }
+
+#[derive(Default)]
+struct File {
+	// the times every statement ran, by the line and column it starts at
+	statements: BTreeMap<(u32, u32), u64>,
+	// the two ways out of every if and loop: then and else, or another iteration and the exit
+	branches: BTreeMap<(u32, u32, Kind), [u64; 2]>,
+}
+
+impl File {
+	// a line ran as many times as the statement on it that ran the most, like the condition of an if holding a break
+	fn lines(&self) -> BTreeMap<u32, u64> {
+		let mut lines = BTreeMap::new();
+		for ((lin, _), hits) in &self.statements {
+			let line: &mut u64 = lines.entry(*lin).or_default();
+			*line = (*line).max(*hits);
+		}
+		lines
+	}
+}
+
+#[derive(Default)]
+pub struct Coverage {
+	files: HashMap<usize, File>,
+}
+
+// lists lines as ranges, like 3-5, 9
+fn ranges(lines: &[u32]) -> String {
+	let mut out = String::new();
+	let mut i = 0;
+	while i < lines.len() {
+		let start = lines[i];
+		while i + 1 < lines.len() && lines[i + 1] == lines[i] + 1 { i += 1; }
+		if !out.is_empty() { out.push_str(", "); }
+		if lines[i] == start { write!(out, "{}", start) } else { write!(out, "{}-{}", start, lines[i]) }.unwrap();
+		i += 1;
+	}
+	out
+}
+
+fn percent(hit: usize, total: usize) -> f64 {
+	if total == 0 { 100.0 } else { hit as f64 * 100.0 / total as f64 }
+}
+
+impl Coverage {
+
+	// every statement that can run is known before it does, so lines that never ran show up too
+	pub fn load(&mut self, decls: &[Statement], block: &[Statement]) {
+		for decl in decls {
+			match &decl.typ {
+				StmtType::FuncDeclaration(data) => self.function(&data.params, &data.body),
+				StmtType::AttrDeclaration(data) => {
+					data.fields.values().for_each(|expr| self.expr(expr));
+					data.methods.iter().for_each(|method| self.function(&method.params, &method.body));
+				}
+				StmtType::Declaration(data) => self.expr(&data.expr),
+				_ => (),
+			}
+		}
+		self.block(block);
+	}
+
+	fn function(&mut self, params: &[Param], body: &[Statement]) {
+		params.iter().filter_map(|param| param.default.as_ref()).for_each(|default| self.expr(default));
+		self.block(body);
+	}
+
+	fn block(&mut self, block: &[Statement]) {
+		for stmt in block { self.stmt(stmt); }
+	}
+
+	fn stmt(&mut self, stmt: &Statement) {
+		let pos = stmt.pos;
+		let file = self.files.entry(pos.file).or_default();
+		// the steps a for loop adds never reach the hooks, only what they hold does
+		let step = is_loop_step(stmt);
+		if !step { file.statements.entry((pos.lin, pos.col)).or_default(); }
+
+		match &stmt.typ {
+			StmtType::Expr(expr) | StmtType::Return(expr) => self.expr(expr),
+			StmtType::Declaration(data) => self.expr(&data.expr),
+			StmtType::Destructure(data) => self.expr(&data.expr),
+			StmtType::FuncDeclaration(data) => self.function(&data.params, &data.body),
+			StmtType::AttrDeclaration(data) => {
+				data.fields.values().for_each(|expr| self.expr(expr));
+				data.methods.iter().for_each(|method| self.function(&method.params, &method.body));
+			}
+			StmtType::Assignment(data) => {
+				self.expr(&data.head);
+				self.expr(&data.expr);
+			}
+			StmtType::If(data) => {
+				if !step { file.branches.entry((pos.lin, pos.col, Kind::If)).or_default(); }
+				self.expr(&data.cond);
+				self.block(&data.then_block);
+				self.block(&data.else_block);
+			}
+			StmtType::Loop(block) => {
+				file.branches.entry((pos.lin, pos.col, Kind::Loop)).or_default();
+				self.block(block);
+			}
+			StmtType::Scoped(block) => self.block(block),
+			StmtType::Import(_) | StmtType::Break | StmtType::Continue => (),
+		}
+	}
+
+	fn expr(&mut self, expr: &Expression) {
+		match &expr.typ {
+			ExprType::Binding(data) => {
+				self.expr(&data.expr);
+				self.expr(&data.method);
+			}
+			ExprType::Logic(data) => {
+				self.expr(&data.lhs);
+				self.expr(&data.rhs);
+			}
+			ExprType::Binary(data) => {
+				self.expr(&data.lhs);
+				self.expr(&data.rhs);
+			}
+			ExprType::Unary(data) => self.expr(&data.expr),
+			ExprType::Call(data) => {
+				self.expr(&data.calee);
+				data.args.iter().for_each(|arg| self.expr(arg));
+				data.named.iter().for_each(|arg| self.expr(&arg.expr));
+			}
+			ExprType::Index(data) => {
+				self.expr(&data.head);
+				self.expr(&data.index);
+			}
+			ExprType::FieldGet(data) => self.expr(&data.head),
+			ExprType::Literal(data) => match data {
+				LiteralData::Template(exprs) | LiteralData::List(exprs) => exprs.iter().for_each(|expr| self.expr(expr)),
+				LiteralData::Map(entries) => entries.iter().for_each(|(key, value)| {
+					self.expr(key);
+					self.expr(value);
+				}),
+				LiteralData::Object(fields, _, spreads) => fields.values().chain(spreads).for_each(|expr| self.expr(expr)),
+				LiteralData::Error(expr) => self.expr(expr),
+				_ => (),
+			},
+			ExprType::Grouping(expr) | ExprType::Spread(expr) => self.expr(expr),
+			ExprType::Range(data) => data.start.iter().chain(&data.end).chain(&data.step).for_each(|bound| self.expr(bound)),
+			ExprType::Lambda(data) => self.function(&data.params, &data.body),
+			ExprType::DoExpr(block) => self.block(block),
+			ExprType::Match(data) => {
+				self.expr(&data.value);
+				for arm in &data.arms {
+					if let Some(ref guard) = arm.guard { self.expr(guard); }
+					self.expr(&arm.body);
+				}
+			}
+			ExprType::Variable(_) | ExprType::SelfRef => (),
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.files.is_empty()
+	}
+
+	pub fn hit(&mut self, pos: SourcePos) {
+		*self.files.entry(pos.file).or_default().statements.entry((pos.lin, pos.col)).or_default() += 1;
+	}
+
+	pub fn branch(&mut self, pos: SourcePos, branch: Branch) {
+		let (kind, taken) = match branch {
+			Branch::Then => (Kind::If, 0),
+			Branch::Else => (Kind::If, 1),
+			Branch::Iteration => (Kind::Loop, 0),
+			Branch::Exit => (Kind::Loop, 1),
+		};
+		// only the branches found while loading count
+		let file = self.files.entry(pos.file).or_default();
+		if let Some(counts) = file.branches.get_mut(&(pos.lin, pos.col, kind)) { counts[taken] += 1; }
+	}
+
+	fn sorted(&self) -> Vec<(String, &File)> {
+		let mut files = self.files.iter().filter(|(id, _)| **id != 0).map(|(id, file)| (file_name(*id), file)).collect::<Vec<_>>();
+		files.sort_by(|(a, _), (b, _)| a.cmp(b));
+		files
+	}
+
+	fn lcov(&self) -> String {
+		let mut out = String::new();
+		for (name, file) in self.sorted() {
+			let path = std::fs::canonicalize(&name).map_or(name, |path| path.to_string_lossy().to_string());
+			writeln!(out, "TN:\nSF:{}", path).unwrap();
+			let lines = file.lines();
+			for (lin, hits) in &lines {
+				writeln!(out, "DA:{},{}", lin, hits).unwrap();
+			}
+
+			// lcov numbers the blocks of a line, in the order they appear
+			let mut block = 0;
+			let mut last_lin = 0;
+			let mut branches_hit = 0;
+			for ((lin, _, _), counts) in &file.branches {
+				if *lin != last_lin { block = 0; last_lin = *lin; }
+				let ran = lines.get(lin).is_some_and(|hits| *hits > 0);
+				for (i, count) in counts.iter().enumerate() {
+					let taken = if ran { count.to_string() } else { "-".to_owned() };
+					writeln!(out, "BRDA:{},{},{},{}", lin, block, i, taken).unwrap();
+					if *count > 0 { branches_hit += 1; }
+				}
+				block += 1;
+			}
+			writeln!(out, "BRF:{}\nBRH:{}", file.branches.len() * 2, branches_hit).unwrap();
+
+			let lines_hit = lines.values().filter(|hits| **hits > 0).count();
+			writeln!(out, "LF:{}\nLH:{}\nend_of_record", lines.len(), lines_hit).unwrap();
+		}
+		out
+	}
+
+	pub fn report(&self, lcov_path: &Path) {
+		eprintln!("{:>14} {:>14}  file", "lines", "branches");
+		for (name, file) in self.sorted() {
+			let lines = file.lines();
+			let lines_hit = lines.values().filter(|hits| **hits > 0).count();
+			let branches = file.branches.len() * 2;
+			let branches_hit = file.branches.values().flatten().filter(|count| **count > 0).count();
+			eprintln!("{:>6}/{:<7} {:>6}/{:<7}  {} ({:.1}% lines, {:.1}% branches)",
+				lines_hit, lines.len(), branches_hit, branches, name,
+				percent(lines_hit, lines.len()), percent(branches_hit, branches),
+			);
+
+			let missed = lines.iter().filter(|(_, hits)| **hits == 0).map(|(lin, _)| *lin).collect::<Vec<_>>();
+			if !missed.is_empty() { eprintln!("{:>30}missed lines {}", "", ranges(&missed)); }
+		}
+
+		match std::fs::write(lcov_path, self.lcov()) {
+			Ok(()) => eprintln!("\ncoverage written to {}", lcov_path.display()),
+			Err(err) => eprintln!("\n{}: could not write {}: {}", ansi_term::Color::Red.paint("error"), lcov_path.display(), err),
+		}
+	}
+
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use crate::{interpreter::Interpreter, lexer::{Lexer, LexerResult}, parser::Parser, profiler::Recorder, semantics::{optimizer::Optimizer, resolver::Resolver}};
+
+use super::{Coverage, File, Kind};
+
+const PROGRAM: &str = "#!script
+let n = 0
+for i in [1, 2, 3] {
+	n = n + i
+}
+if n > 100 {
+	loop { break }
+}
+";
+
+// runs a script with coverage on, and gives back what was recorded
+fn run(name: &str, text: &str) -> Coverage {
+	let LexerResult { tokens, .. } = Lexer::from_text(name, text).scan_tokens();
+	let (module, block) = Parser::new(tokens).script().unwrap();
+	let mut resolver = Resolver::new();
+	resolver.resolve(&module).unwrap();
+	resolver.resolve_block(&block).unwrap();
+	let block = Optimizer.optimize_block(block).unwrap();
+
+	let recorder = Recorder::new(false, Some(PathBuf::new()));
+	let mut interpreter = Interpreter::new(PathBuf::new());
+	interpreter.modules.reserve(resolver.max_id());
+	interpreter.hook = Some(Box::new(recorder.clone()));
+	interpreter.interpret_script(module, block).unwrap();
+
+	let recording = std::mem::take(&mut *recorder.0.borrow_mut());
+	recording.coverage.unwrap().0
+}
+
+fn only_file(coverage: &Coverage) -> &File {
+	let files = coverage.sorted();
+	assert_eq!(files.len(), 1);
+	files[0].1
+}
+
+#[test]
+fn lines_take_the_most_run_statement() {
+	let coverage = run("lines.mars", PROGRAM);
+	let lines = only_file(&coverage).lines().into_iter().collect::<Vec<_>>();
+	assert_eq!(lines, vec![(2, 1), (3, 3), (4, 3), (6, 1), (7, 0)]);
+}
+
+#[test]
+fn branches_count_each_way_taken() {
+	let coverage = run("branches.mars", PROGRAM);
+	let branches = only_file(&coverage).branches.iter().map(|(key, counts)| (*key, *counts)).collect::<Vec<_>>();
+	assert_eq!(branches, vec![
+		// three passes through the for loop and the exit once the list ran out
+		((3, 1, Kind::Loop), [3, 1]),
+		((6, 1, Kind::If), [0, 1]),
+		((7, 2, Kind::Loop), [0, 0]),
+	]);
+}
+
+#[test]
+fn loops_count_passes_that_finished() {
+	let coverage = run("passes.mars", "#!script
+let n = 0
+loop {
+	n = n + 1
+	if n == 4 { break }
+}
+for i in 'ab' { continue }
+");
+	let branches = only_file(&coverage).branches.iter().map(|(key, counts)| (*key, *counts)).collect::<Vec<_>>();
+	assert_eq!(branches, vec![
+		((3, 1, Kind::Loop), [3, 1]),
+		((5, 2, Kind::If), [1, 3]),
+		((7, 1, Kind::Loop), [2, 1]),
+	]);
+}
+
+#[test]
+fn lcov_lists_lines_and_branches() {
+	let coverage = run("lcov.mars", PROGRAM);
+	assert_eq!(coverage.lcov(), "\
+TN:
+SF:lcov.mars
+DA:2,1
+DA:3,3
+DA:4,3
+DA:6,1
+DA:7,0
+BRDA:3,0,0,3
+BRDA:3,0,1,1
+BRDA:6,0,0,0
+BRDA:6,0,1,1
+BRDA:7,0,0,-
+BRDA:7,0,1,-
+BRF:6
+BRH:3
+LF:5
+LH:4
+end_of_record
+");
+}
@@ -0,0 +1,167 @@
+mod coverage;
+
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc, time::{Duration, Instant}};
+
+use crate::{ast::statement::Statement, interpreter::{Branch, Hook, Interpreter}, utils::{source_pos::SourcePos, sources::get_source}};
+
+use self::coverage::Coverage;
+
+// how many of the slowest lines the profile shows
+const TOP_LINES: usize = 20;
+
+fn file_name(file: usize) -> String {
+	get_source(file).map_or_else(|| "?".to_owned(), |src| src.name.clone())
+}
+
+#[derive(Default)]
+struct Stats {
+	count: u64,
+	total: Duration,
+	own: Duration,
+}
+
+// functions are told apart by where their body starts, since lambdas all share a name
+type FunctionKey = (String, Option<(usize, u32)>);
+type StatementKey = (usize, u32, u32);
+
+struct Frame {
+	key: FunctionKey,
+	start: Instant,
+	children: Duration,
+}
+
+#[derive(Default)]
+struct Profile {
+	functions: HashMap<FunctionKey, Stats>,
+	statements: HashMap<StatementKey, Stats>,
+	calls: Vec<Frame>,
+	running: Vec<(StatementKey, Instant)>,
+	// recursive calls are already timed by their outermost call, so only that one adds to the total
+	active_functions: HashMap<FunctionKey, usize>,
+	active_statements: HashMap<StatementKey, usize>,
+}
+
+impl Profile {
+
+	fn statement(&mut self, pos: SourcePos) {
+		let key = (pos.file, pos.lin, pos.col);
+		*self.active_statements.entry(key).or_default() += 1;
+		self.running.push((key, Instant::now()));
+	}
+
+	fn finished(&mut self) {
+		let Some((key, start)) = self.running.pop() else { return };
+		let active = self.active_statements.entry(key).or_default();
+		*active -= 1;
+		let outermost = *active == 0;
+
+		let stats = self.statements.entry(key).or_default();
+		stats.count += 1;
+		if outermost { stats.total += start.elapsed(); }
+	}
+
+	fn enter(&mut self, name: &str, body: Option<SourcePos>) {
+		let key = (name.to_owned(), body.map(|pos| (pos.file, pos.lin)));
+		*self.active_functions.entry(key.clone()).or_default() += 1;
+		self.calls.push(Frame { key, start: Instant::now(), children: Duration::ZERO });
+	}
+
+	fn leave(&mut self) {
+		let Some(frame) = self.calls.pop() else { return };
+		let elapsed = frame.start.elapsed();
+		if let Some(parent) = self.calls.last_mut() { parent.children += elapsed; }
+
+		let active = self.active_functions.entry(frame.key.clone()).or_default();
+		*active -= 1;
+		let outermost = *active == 0;
+
+		let stats = self.functions.entry(frame.key).or_default();
+		stats.count += 1;
+		stats.own += elapsed.saturating_sub(frame.children);
+		if outermost { stats.total += elapsed; }
+	}
+
+	fn report(&self) {
+		let mut functions = self.functions.iter().collect::<Vec<_>>();
+		functions.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.own));
+
+		eprintln!("{:>10} {:>12} {:>12}  function", "calls", "total", "self");
+		for ((name, body), stats) in functions {
+			let location = body.map(|(file, lin)| format!(" [{}:{}]", file_name(file), lin)).unwrap_or_default();
+			eprintln!("{:>10} {:>12.2?} {:>12.2?}  {}{}", stats.count, stats.total, stats.own, name, location);
+		}
+
+		let mut statements = self.statements.iter().collect::<Vec<_>>();
+		statements.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.total));
+
+		eprintln!();
+		eprintln!("{:>10} {:>12}  line", "runs", "total");
+		for ((file, lin, col), stats) in statements.into_iter().take(TOP_LINES) {
+			eprintln!("{:>10} {:>12.2?}  {}:{}:{}", stats.count, stats.total, file_name(*file), lin, col);
+		}
+	}
+
+}
+
+#[derive(Default)]
+struct Recording {
+	profile: Option<Profile>,
+	// the coverage of the run, and where its lcov report goes
+	coverage: Option<(Coverage, PathBuf)>,
+}
+
+// records a run for --profile and --coverage, cloning it shares the same recording
+#[derive(Clone, Default)]
+pub struct Recorder(Rc<RefCell<Recording>>);
+
+impl Recorder {
+
+	pub fn new(profile: bool, lcov_path: Option<PathBuf>) -> Self {
+		let recording = Recording {
+			profile: profile.then(Profile::default),
+			coverage: lcov_path.map(|path| (Coverage::default(), path)),
+		};
+		Self(Rc::new(RefCell::new(recording)))
+	}
+
+	// a program that failed to compile never ran, so there is nothing to show
+	pub fn report(&self) {
+		let recording = self.0.borrow();
+		if recording.profile.as_ref().is_some_and(|profile| profile.statements.is_empty()) || recording.coverage.as_ref().is_some_and(|(coverage, _)| coverage.is_empty()) { return; }
+		if let Some(ref profile) = recording.profile { profile.report(); }
+		if let Some((ref coverage, ref path)) = recording.coverage {
+			if recording.profile.is_some() { eprintln!(); }
+			coverage.report(path);
+		}
+	}
+
+}
+
+impl Hook for Recorder {
+	fn statement(&mut self, _interpreter: &mut Interpreter, stmt: &Statement) -> bool {
+		let mut recording = self.0.borrow_mut();
+		if let Some(ref mut profile) = recording.profile { profile.statement(stmt.pos); }
+		if let Some((ref mut coverage, _)) = recording.coverage { coverage.hit(stmt.pos); }
+		true
+	}
+
+	fn finished(&mut self, _pos: SourcePos) {
+		if let Some(ref mut profile) = self.0.borrow_mut().profile { profile.finished(); }
+	}
+
+	fn enter(&mut self, name: &str, body: Option<SourcePos>) {
+		if let Some(ref mut profile) = self.0.borrow_mut().profile { profile.enter(name, body); }
+	}
+
+	fn leave(&mut self) {
+		if let Some(ref mut profile) = self.0.borrow_mut().profile { profile.leave(); }
+	}
+
+	fn branch(&mut self, pos: SourcePos, branch: Branch) {
+		if let Some((ref mut coverage, _)) = self.0.borrow_mut().coverage { coverage.branch(pos, branch); }
+	}
+
+	fn loaded(&mut self, decls: &[Statement], block: &[Statement]) {
+		if let Some((ref mut coverage, _)) = self.0.borrow_mut().coverage { coverage.load(decls, block); }
+	}
+}
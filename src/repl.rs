@@ -0,0 +1,104 @@
+use std::io::Write;
+
+use crate::{interpreter::{Interpreter, Message}, lexer::{Lexer, LexerResult, token::{Keyword, Symbol::*, Token, TokenType}}, parser::Parser, semantics::{resolver::Resolver, optimizer::Optimizer}, utils::{result::Result, source_pos::SourcePos, sources::add_source}};
+
+const PROMPT: &str = "> ";
+const CONTINUE_PROMPT: &str = ". ";
+
+pub struct Repl {
+	interpreter: Interpreter,
+	resolver: Resolver,
+	// lines are lexed into the same source entry, replacing the one before
+	file: usize,
+}
+
+fn bracket_depth(tokens: &[Token]) -> i32 {
+	tokens.iter().fold(0, |depth, token| match token.typ {
+		TokenType::Symbol(OpenPar) | TokenType::Symbol(OpenSqr) | TokenType::Symbol(OpenBracket) => depth + 1,
+		TokenType::Symbol(ClosePar) | TokenType::Symbol(CloseSqr) | TokenType::Symbol(CloseBracket) => depth - 1,
+		_ => depth,
+	})
+}
+
+impl Default for Repl {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Repl {
+
+	pub fn new() -> Self {
+		Self {
+			interpreter: Interpreter::new(std::env::current_dir().unwrap_or_default()),
+			resolver: Resolver::repl(),
+			file: add_source("repl", ""),
+		}
+	}
+
+	pub fn run(mut self) {
+		let mut source = String::new();
+
+		loop {
+			print!("{}", if source.is_empty() { PROMPT } else { CONTINUE_PROMPT });
+			let _ = std::io::stdout().flush();
+
+			let mut line = String::new();
+			match std::io::stdin().read_line(&mut line) {
+				Ok(0) | Err(_) => break,
+				Ok(_) => source.push_str(&line),
+			}
+
+			if source.trim().is_empty() {
+				source.clear();
+				continue;
+			}
+
+			let lexer_res = Lexer::reuse_file(self.file, &source).scan_tokens();
+
+			// keep reading lines until every bracket is closed
+			if lexer_res.errors.is_empty() && bracket_depth(&lexer_res.tokens) > 0 { continue; }
+
+			// functions can still fail after their line was run, so their errors need its text to stay around
+			let keeps_code = lexer_res.tokens.iter().any(|token| matches!(token.typ, TokenType::Keyword(Keyword::Function) | TokenType::Keyword(Keyword::Attr)));
+
+			match self.eval(lexer_res) {
+				Ok(Message::Eval(val)) => match val.to_string(&mut self.interpreter, SourcePos::new(1, 1)) {
+					Ok(str) if str != "none" => println!("{}", str),
+					Ok(_) => (),
+					Err(err) => err.report_source("repl", &source),
+				},
+				Ok(Message::Halt) => break,
+				Ok(_) => (),
+				Err(err) => err.report_source("repl", &source),
+			}
+
+			if keeps_code { self.file = add_source("repl", ""); }
+			source.clear();
+		}
+	}
+
+	fn eval(&mut self, lexer_res: LexerResult) -> Result<Message> {
+		let LexerResult { tokens, mut errors, .. } = lexer_res;
+
+		let (mut module, block) = Parser::new(tokens).script()?;
+
+		// a failed resolution shouldn't leave half-declared names behind
+		let snapshot = self.resolver.clone();
+		self.resolver.reserve(self.interpreter.modules.next_id());
+		errors.try_append(self.resolver.resolve_repl(&module, &block));
+
+		if !errors.is_empty() {
+			self.resolver = snapshot;
+			return errors.err();
+		}
+
+		self.interpreter.modules.reserve(self.resolver.max_id());
+
+		Optimizer.optimize(&mut module)?;
+		let block = Optimizer.optimize_block(block)?;
+
+		self.interpreter.interpret_repl(module, block)
+	}
+
+}
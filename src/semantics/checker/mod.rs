@@ -0,0 +1,613 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{ast::{identifier::Identifier, expression::*, pattern::{Pattern, PatternType}, statement::*, types::Type, Block, module::Module}, interpreter::value::primitives::callable::arity_message, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+#[derive(Clone, Debug)]
+struct Signature {
+	params: Vec<(String, Type)>,
+	labels: Vec<String>,
+	optional: usize,
+	// the type of each argument the rest parameter collects
+	rest: Option<Type>,
+	ret: Type,
+}
+
+impl Signature {
+	fn new(params: &[Param], ret: &Option<Type>) -> Self {
+		let typ = |param: &Param| param.typ.clone().unwrap_or(Type::Any);
+		Self {
+			params: params.iter().filter(|param| !param.rest).map(|param| (param.name.get_name(), typ(param))).collect(),
+			labels: params.iter().map(Param::label).collect(),
+			optional: params.iter().filter(|param| param.default.is_some()).count(),
+			rest: params.iter().find(|param| param.rest).map(typ),
+			ret: ret.clone().unwrap_or(Type::Any),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Var {
+	typ: Type,
+	sig: Option<Signature>,
+}
+
+impl Var {
+	fn new(typ: Type) -> Self {
+		Self { typ, sig: None }
+	}
+}
+
+// names that are assigned to somewhere in a block, their type can change so it is never inferred
+fn assigned_block(block: &Block, names: &mut HashSet<String>) {
+	for stmt in block {
+		match &stmt.typ {
+			StmtType::Expr(expr) | StmtType::Return(expr) => assigned_expr(expr, names),
+			StmtType::Declaration(data) => assigned_expr(&data.expr, names),
+			StmtType::Destructure(data) => assigned_expr(&data.expr, names),
+			StmtType::Assignment(data) => {
+				if let ExprType::Variable(name) = &data.head.typ {
+					names.insert(name.get_name());
+				}
+				assigned_expr(&data.head, names);
+				assigned_expr(&data.expr, names);
+			}
+			StmtType::If(data) => {
+				assigned_expr(&data.cond, names);
+				assigned_block(&data.then_block, names);
+				assigned_block(&data.else_block, names);
+			}
+			StmtType::Loop(block) | StmtType::Scoped(block) => assigned_block(block, names),
+			_ => (),
+		}
+	}
+}
+
+fn assigned_expr(expr: &Expression, names: &mut HashSet<String>) {
+	match &expr.typ {
+		ExprType::Binding(data) => { assigned_expr(&data.expr, names); assigned_expr(&data.method, names); }
+		ExprType::Logic(data) => { assigned_expr(&data.lhs, names); assigned_expr(&data.rhs, names); }
+		ExprType::Binary(data) => { assigned_expr(&data.lhs, names); assigned_expr(&data.rhs, names); }
+		ExprType::Unary(data) => assigned_expr(&data.expr, names),
+		ExprType::Call(data) => {
+			assigned_expr(&data.calee, names);
+			data.args.iter().for_each(|arg| assigned_expr(arg, names));
+		}
+		ExprType::Index(data) => { assigned_expr(&data.head, names); assigned_expr(&data.index, names); }
+		ExprType::FieldGet(data) => assigned_expr(&data.head, names),
+		ExprType::Grouping(expr) => assigned_expr(expr, names),
+		ExprType::Lambda(data) => assigned_block(&data.body, names),
+		ExprType::DoExpr(block) => assigned_block(block, names),
+		ExprType::Match(data) => {
+			assigned_expr(&data.value, names);
+			for arm in &data.arms {
+				if let Some(guard) = &arm.guard { assigned_expr(guard, names); }
+				assigned_expr(&arm.body, names);
+			}
+		}
+		ExprType::Literal(data) => match data {
+			LiteralData::Template(exprs) | LiteralData::List(exprs) => exprs.iter().for_each(|expr| assigned_expr(expr, names)),
+			LiteralData::Map(entries) => entries.iter().for_each(|(key, val)| { assigned_expr(key, names); assigned_expr(val, names); }),
+			LiteralData::Object(fields, _, spreads) => fields.values().chain(spreads).for_each(|expr| assigned_expr(expr, names)),
+			LiteralData::Error(expr) => assigned_expr(expr, names),
+			_ => (),
+		}
+		ExprType::Spread(expr) => assigned_expr(expr, names),
+		ExprType::Range(data) => data.start.iter().chain(&data.end).chain(&data.step).for_each(|bound| assigned_expr(bound, names)),
+		ExprType::Variable(_) | ExprType::SelfRef => (),
+	}
+}
+
+// checks type annotations and the operands of operators, only values that can never be valid are reported
+#[derive(Debug, Clone)]
+pub struct Checker {
+	scopes: Vec<HashMap<String, Var>>,
+	// the declared return type of every enclosing function
+	returns: Vec<Type>,
+	// the names assigned to in every enclosing function
+	assigned: Vec<HashSet<String>>,
+	errors: ErrorList,
+}
+
+impl Default for Checker {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Checker {
+
+	pub fn new() -> Self {
+		Self {
+			scopes: vec![HashMap::new()],
+			returns: Vec::new(),
+			assigned: vec![HashSet::new()],
+			errors: ErrorList::new(),
+		}
+	}
+
+	pub fn check(&mut self, module: &Module) -> Result<()> {
+		self.push_scope();
+
+		for (id, stmt) in module.env.iter() {
+			let var = match &stmt.typ {
+				StmtType::FuncDeclaration(data) => Var { typ: Type::Function, sig: Some(Signature::new(&data.params, &data.ret)) },
+				StmtType::AttrDeclaration(_) => Var::new(Type::Attribute),
+				_ => Var::new(Type::Any),
+			};
+			self.add(id, var);
+		}
+
+		for stmt in module.env.values() {
+			let _ = stmt.clone().accept(self);
+		}
+
+		self.take_errors()
+	}
+
+	pub fn check_block(&mut self, block: &Block) -> Result<()> {
+		let mut names = HashSet::new();
+		assigned_block(block, &mut names);
+		self.assigned.push(names);
+		self.block(block);
+		self.assigned.pop();
+
+		self.take_errors()
+	}
+
+	fn take_errors(&mut self) -> Result<()> {
+		std::mem::take(&mut self.errors).if_empty(())
+	}
+
+	fn push_scope(&mut self) {
+		self.scopes.push(HashMap::new());
+	}
+
+	fn pop_scope(&mut self) {
+		self.scopes.pop();
+	}
+
+	fn add(&mut self, name: &Identifier, var: Var) {
+		self.scopes.last_mut().unwrap().insert(name.get_name(), var);
+	}
+
+	fn get_var(&self, name: &str) -> Option<&Var> {
+		self.scopes.iter().rev().find_map(|scope| scope.get(name))
+	}
+
+	fn block(&mut self, block: &Block) {
+		self.push_scope();
+		for stmt in block.clone() {
+			let _ = stmt.accept(self);
+		}
+		self.pop_scope();
+	}
+
+	fn infer(&mut self, expr: Expression) -> Type {
+		expr.accept(self).unwrap_or(Type::Any)
+	}
+
+	fn expect(&mut self, expected: &Type, found: &Type, pos: SourcePos) {
+		if !expected.compatible(found) {
+			self.errors.add_comp(format!("Expected {}, found {}", expected, found), pos);
+		}
+	}
+
+	// an unknown type is reported once and then treated as any
+	fn annotation(&mut self, typ: &Option<Type>, pos: SourcePos) -> Type {
+		match typ {
+			Some(typ) if self.known_type(typ, pos) => typ.clone(),
+			_ => Type::Any,
+		}
+	}
+
+	// named types have to be attributes declared in the module
+	fn known_type(&mut self, typ: &Type, pos: SourcePos) -> bool {
+		match typ {
+			Type::Named(name) if !matches!(self.get_var(name), Some(Var { typ: Type::Attribute, .. })) => {
+				self.errors.add_comp(format!("Unknown type '{}'", name), pos);
+				false
+			}
+			Type::Optional(typ) => self.known_type(typ, pos),
+			_ => true,
+		}
+	}
+
+	fn function(&mut self, params: Vec<Param>, ret: Option<Type>, body: Block, pos: SourcePos) {
+		let ret = self.annotation(&ret, pos);
+
+		self.push_scope();
+		for param in params {
+			let typ = self.annotation(&param.typ, pos);
+			if let Some(default) = param.default {
+				let default_pos = default.pos;
+				let found = self.infer(default);
+				self.expect(&typ, &found, default_pos);
+			}
+			let typ = if param.rest { Type::List } else { typ };
+			if let Some(ref pattern) = param.pattern {
+				self.destructured(pattern, &typ);
+			}
+			self.add(&param.name, Var::new(typ));
+		}
+
+		let mut names = HashSet::new();
+		assigned_block(&body, &mut names);
+		self.assigned.push(names);
+		self.returns.push(ret);
+		self.block(&body);
+		self.returns.pop();
+		self.assigned.pop();
+
+		self.pop_scope();
+	}
+
+	// a pattern only rules out values of another kind, the names it binds can be anything
+	fn destructured(&mut self, pattern: &Pattern, found: &Type) {
+		match pattern.typ {
+			PatternType::List(_) => self.expect(&Type::List, found, pattern.pos),
+			PatternType::Object(_, _) => self.expect(&Type::Object, found, pattern.pos),
+			_ => (),
+		}
+		for name in pattern.bindings() {
+			self.add(&name, Var::new(Type::Any));
+		}
+	}
+
+	fn number(&mut self, typ: &Type, pos: SourcePos) {
+		self.expect(&Type::Num, typ, pos);
+	}
+
+	fn arithmetic(&mut self, op: &BinaryOperator, lhs: Type, rhs: Type, l_pos: SourcePos, r_pos: SourcePos, pos: SourcePos) -> Type {
+		let lhs = match lhs {
+			Type::Optional(typ) => *typ,
+			typ => typ,
+		};
+		match lhs {
+			Type::Num => { self.number(&rhs, r_pos); Type::Num }
+			Type::Str if matches!(op, BinaryOperator::Add) => Type::Str,
+			// objects implement operators through their attributes
+			Type::Any | Type::Object | Type::Named(_) => Type::Any,
+			typ => {
+				let name = match op {
+					BinaryOperator::Add => "ADD",
+					BinaryOperator::Sub => "SUB",
+					BinaryOperator::Mul => "MUL",
+					_ => "DIV",
+				};
+				self.errors.add_comp(format!("Operation {} is not defined for {}", name, typ), l_pos.to(pos));
+				Type::Any
+			}
+		}
+	}
+
+}
+
+impl ExprVisitor<Type> for Checker {
+
+	fn literal(&mut self, data: LiteralData, _pos: SourcePos) -> Result<Type> {
+		match data {
+			LiteralData::None => Type::None,
+			LiteralData::Str(_) => Type::Str,
+			LiteralData::Num(_) => Type::Num,
+			LiteralData::Bool(_) => Type::Bool,
+			LiteralData::Template(exprs) => {
+				for expr in exprs { self.infer(expr); }
+				Type::Str
+			}
+			LiteralData::List(exprs) => {
+				for expr in exprs { self.infer(expr); }
+				Type::List
+			}
+			LiteralData::Map(entries) => {
+				for (key, val) in entries { self.infer(key); self.infer(val); }
+				Type::Map
+			}
+			LiteralData::Object(fields, _, spreads) => {
+				for expr in fields.into_values() { self.infer(expr); }
+				// only the fields of objects can be spread into another one
+				for spread in spreads {
+					let ExprType::Spread(expr) = spread.typ else { continue };
+					let expr_pos = expr.pos;
+					let found = self.infer(*expr);
+					self.expect(&Type::Object, &found, expr_pos);
+				}
+				Type::Object
+			}
+			LiteralData::Error(expr) => {
+				self.infer(*expr);
+				Type::Error
+			}
+		}.wrap()
+	}
+
+	fn binary(&mut self, data: BinaryData, pos: SourcePos) -> Result<Type> {
+		let (l_pos, r_pos) = (data.lhs.pos, data.rhs.pos);
+		let lhs = self.infer(*data.lhs);
+		let rhs = self.infer(*data.rhs);
+		match data.op {
+			BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul | BinaryOperator::Div => self.arithmetic(&data.op, lhs, rhs, l_pos, r_pos, pos),
+			BinaryOperator::Rem => {
+				self.number(&lhs, l_pos);
+				self.number(&rhs, r_pos);
+				Type::Num
+			}
+			BinaryOperator::Lst | BinaryOperator::Lse | BinaryOperator::Grt | BinaryOperator::Gre => {
+				self.number(&lhs, l_pos);
+				self.number(&rhs, r_pos);
+				Type::Bool
+			}
+			BinaryOperator::Typ => {
+				self.expect(&Type::Attribute, &rhs, r_pos);
+				Type::Bool
+			}
+			BinaryOperator::Equ | BinaryOperator::Neq => Type::Bool,
+		}.wrap()
+	}
+
+	fn unary(&mut self, data: UnaryData, _pos: SourcePos) -> Result<Type> {
+		let pos = data.expr.pos;
+		let typ = self.infer(*data.expr);
+		match data.op {
+			UnaryOperator::Not => Type::Bool,
+			UnaryOperator::Pos | UnaryOperator::Neg => { self.number(&typ, pos); Type::Num }
+		}.wrap()
+	}
+
+	fn logic(&mut self, data: LogicData, _pos: SourcePos) -> Result<Type> {
+		let lhs = self.infer(*data.lhs);
+		let rhs = self.infer(*data.rhs);
+		match data.op {
+			LogicOperator::Coalesce => Ok(match (lhs, rhs) {
+				(Type::None | Type::Error, rhs) => rhs,
+				(Type::Optional(inner), rhs) if *inner == rhs => rhs,
+				(Type::Any | Type::Optional(_), _) => Type::Any,
+				(lhs, _) => lhs,
+			}),
+			_ => Ok(Type::Bool),
+		}
+	}
+
+	fn grouping(&mut self, data: Box<Expression>, _pos: SourcePos) -> Result<Type> {
+		data.accept(self)
+	}
+
+	fn variable(&mut self, data: Identifier, _pos: SourcePos) -> Result<Type> {
+		Ok(self.get_var(&data.name).map(|var| var.typ.clone()).unwrap_or(Type::Any))
+	}
+
+	fn lambda(&mut self, data: LambdaData, pos: SourcePos) -> Result<Type> {
+		self.function(data.params, data.ret, data.body, pos);
+		Ok(Type::Function)
+	}
+
+	fn call(&mut self, data: CallData, pos: SourcePos) -> Result<Type> {
+		let sig = match &data.calee.typ {
+			ExprType::Variable(name) => self.get_var(&name.name).and_then(|var| var.sig.clone()),
+			_ => None,
+		};
+		let calee_pos = data.calee.pos;
+		let calee = self.infer(*data.calee);
+
+		// nothing is known about the arguments from the first spread one on
+		let spread = data.args.iter().position(|arg| matches!(arg.typ, ExprType::Spread(_)));
+		let mut args: Vec<_> = data.args.into_iter().map(|arg| (arg.pos, self.infer(arg))).collect();
+		if let Some(spread) = spread { args.truncate(spread); }
+		let named: Vec<_> = data.named.into_iter().map(|arg| (arg.name, arg.pos, self.infer(arg.expr))).collect();
+
+		match calee {
+			Type::None if data.optional => (),
+			Type::Num | Type::Str | Type::Bool | Type::None | Type::List | Type::Map | Type::Error | Type::Iterator => {
+				self.errors.add_comp(format!("Cannot call a value of type {}", calee), calee_pos);
+			}
+			_ => (),
+		}
+
+		let Some(sig) = sig else { return Ok(Type::Any) };
+
+		let (min, max) = (sig.params.len() - sig.optional, sig.params.len());
+		if (args.len() > max && sig.rest.is_none()) || (args.len() < min && named.is_empty() && spread.is_none()) {
+			self.errors.add_comp(arity_message(min, max, sig.rest.is_some(), args.len()), pos);
+		}
+		for ((_, expected), (arg_pos, found)) in sig.params.iter().zip(args.iter()) {
+			self.expect(expected, found, *arg_pos);
+		}
+		if let Some(ref expected) = sig.rest {
+			for (arg_pos, found) in args.iter().skip(max) {
+				self.expect(expected, found, *arg_pos);
+			}
+		}
+
+		for (name, arg_pos, found) in &named {
+			match sig.params.iter().position(|(param, _)| param == name) {
+				Some(i) if i < args.len() => self.errors.add_comp(format!("Argument '{}' was already passed", name), *arg_pos),
+				Some(i) => self.expect(&sig.params[i].1, found, *arg_pos),
+				None => self.errors.add_comp(format!("Function has no parameter named '{}'", name), *arg_pos),
+			}
+		}
+		if !named.is_empty() && spread.is_none() {
+			let missing = (args.len()..min).find(|i| !named.iter().any(|(name, _, _)| *name == sig.params[*i].0));
+			if let Some(i) = missing {
+				self.errors.add_comp(format!("Missing argument for parameter {}", sig.labels[i]), pos);
+			}
+		}
+
+		Ok(sig.ret)
+	}
+
+	fn spread(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<Type> {
+		let expr_pos = expr.pos;
+		if let typ @ (Type::Num | Type::Bool | Type::None | Type::Error | Type::Function | Type::Attribute) = self.infer(*expr) {
+			self.errors.add_comp(format!("Cannot spread a value of type {}", typ), expr_pos);
+		}
+		Ok(Type::Any)
+	}
+
+	fn range(&mut self, data: RangeData, _pos: SourcePos) -> Result<Type> {
+		for bound in data.start.into_iter().chain(data.end).chain(data.step) {
+			let bound_pos = bound.pos;
+			let typ = self.infer(*bound);
+			self.number(&typ, bound_pos);
+		}
+		Ok(Type::Range)
+	}
+
+	fn index(&mut self, data: IndexData, _pos: SourcePos) -> Result<Type> {
+		let head = self.infer(*data.head);
+		let index = self.infer(*data.index);
+		// slicing gives back the same kind of value
+		Ok(match (head, index) {
+			(typ @ (Type::List | Type::Str), Type::Range) => typ,
+			_ => Type::Any,
+		})
+	}
+
+	fn field(&mut self, data: FieldData, _pos: SourcePos) -> Result<Type> {
+		self.infer(*data.head);
+		Ok(Type::Any)
+	}
+
+	fn self_ref(&mut self, _pos: SourcePos) -> Result<Type> {
+		Ok(Type::Object)
+	}
+
+	fn do_expr(&mut self, block: Block, _pos: SourcePos) -> Result<Type> {
+		self.block(&block);
+		Ok(Type::Any)
+	}
+
+	fn bind_expr(&mut self, data: BindData, _pos: SourcePos) -> Result<Type> {
+		self.infer(*data.expr);
+		self.infer(*data.method);
+		Ok(Type::Function)
+	}
+
+	fn match_expr(&mut self, data: MatchData, _pos: SourcePos) -> Result<Type> {
+		self.infer(*data.value);
+		let mut typ = None;
+		for arm in data.arms {
+			self.push_scope();
+			for name in arm.pattern.bindings() {
+				self.add(&name, Var::new(Type::Any));
+			}
+			if let Some(guard) = arm.guard {
+				self.infer(guard);
+			}
+			let arm_typ = self.infer(arm.body);
+			typ = Some(match typ {
+				Some(prev) => Type::join(prev, arm_typ),
+				None => arm_typ,
+			});
+			self.pop_scope();
+		}
+		Ok(typ.unwrap_or(Type::None))
+	}
+
+}
+
+impl StmtVisitor<()> for Checker {
+
+	fn func_declaration(&mut self, data: FunctionData, pos: SourcePos) -> Result<()> {
+		self.function(data.params, data.ret, data.body, pos);
+		Ok(())
+	}
+
+	fn attr_declaration(&mut self, data: AttrDeclarationData, pos: SourcePos) -> Result<()> {
+		for method in data.methods {
+			self.function(method.params, method.ret, method.body, pos);
+		}
+		for expr in data.fields.into_values() {
+			self.infer(expr);
+		}
+		Ok(())
+	}
+
+	fn import(&mut self, _data: ImportData, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn expr(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		self.infer(*expr);
+		Ok(())
+	}
+
+	fn declaration(&mut self, data: DeclarationData, pos: SourcePos) -> Result<()> {
+		let declared = data.typ.as_ref().map(|typ| self.annotation(&Some(typ.clone()), pos));
+		let reassigned = self.assigned.last().is_some_and(|names| names.contains(&data.name.name));
+
+		// lambdas can call themselves, so their name is visible while checking them
+		let sig = match &data.expr.typ {
+			ExprType::Lambda(lambda) if !reassigned => {
+				let sig = Signature::new(&lambda.params, &lambda.ret);
+				self.add(&data.name, Var { typ: Type::Function, sig: Some(sig.clone()) });
+				Some(sig)
+			}
+			_ => None,
+		};
+
+		let expr_pos = data.expr.pos;
+		let found = self.infer(*data.expr);
+
+		let typ = match declared {
+			Some(declared) => { self.expect(&declared, &found, expr_pos); declared }
+			None if reassigned => Type::Any,
+			None => found,
+		};
+		self.add(&data.name, Var { typ, sig });
+		Ok(())
+	}
+
+	fn destructure(&mut self, data: DestructureData, _pos: SourcePos) -> Result<()> {
+		let found = self.infer(*data.expr);
+		self.destructured(&data.pattern, &found);
+		Ok(())
+	}
+
+	fn assignment(&mut self, data: AssignData, _pos: SourcePos) -> Result<()> {
+		let declared = match &data.head.typ {
+			ExprType::Variable(name) => self.get_var(&name.name).map(|var| var.typ.clone()),
+			_ => None,
+		};
+		self.infer(*data.head);
+		let expr_pos = data.expr.pos;
+		let found = self.infer(*data.expr);
+		if let Some(declared) = declared {
+			self.expect(&declared, &found, expr_pos);
+		}
+		Ok(())
+	}
+
+	fn if_stmt(&mut self, data: IfData, _pos: SourcePos) -> Result<()> {
+		self.infer(*data.cond);
+		self.block(&data.then_block);
+		self.block(&data.else_block);
+		Ok(())
+	}
+
+	fn loop_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(&block);
+		Ok(())
+	}
+
+	fn break_stmt(&mut self, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn continue_stmt(&mut self, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
+	fn return_stmt(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		let expr_pos = expr.pos;
+		let found = self.infer(*expr);
+		if let Some(expected) = self.returns.last().cloned() {
+			self.expect(&expected, &found, expr_pos);
+		}
+		Ok(())
+	}
+
+	fn scoped_stmt(&mut self, block: Block, _pos: SourcePos) -> Result<()> {
+		self.block(&block);
+		Ok(())
+	}
+
+}
@@ -1,3 +1,3 @@
 
 pub mod resolver;
-pub mod optimizer;
\ No newline at end of file
+pub mod optimizer;pub mod checker;
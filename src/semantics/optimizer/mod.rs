@@ -21,6 +21,15 @@ impl Optimizer {
 		}
 		statements.wrap()
 	}
+
+	fn params(&mut self, params: Vec<Param>) -> Result<Vec<Param>> {
+		let mut optimized = Vec::new();
+		for mut param in params {
+			param.default = param.default.map(|default| default.accept(self)).transpose()?;
+			optimized.push(param);
+		}
+		optimized.wrap()
+	}
 	
 }
 
@@ -42,7 +51,12 @@ impl ExprVisitor<Expression> for Optimizer {
 				for expr in exprs { oexprs.push(expr.accept(self)?); }
 				data = LiteralData::List(oexprs);
 			},
-			LiteralData::Object(ref fields, _) => {
+			LiteralData::Map(entries) => {
+				let mut oentries = Vec::new();
+				for (key, val) in entries { oentries.push((key.accept(self)?, val.accept(self)?)); }
+				data = LiteralData::Map(oentries);
+			},
+			LiteralData::Object(ref fields, _, _) => {
 				let mut ofields = HashMap::new();
 				for (key, expr) in fields.iter() { ofields.insert(key, expr.clone().accept(self)?); }
 			},
@@ -117,6 +131,7 @@ impl ExprVisitor<Expression> for Optimizer {
 	}
 	
 	fn lambda(&mut self, mut data: LambdaData, pos: SourcePos) -> Result<Expression> {
+		data.params = self.params(data.params)?;
 		data.body = self.optimize_block(data.body)?;
 		ExprType::Lambda(data).to_expr(pos).wrap()
 	}
@@ -126,6 +141,12 @@ impl ExprVisitor<Expression> for Optimizer {
 		let mut args = Vec::new();
 		for arg in data.args { args.push(arg.accept(self)?); }
 		data.args = args;
+		let mut named = Vec::new();
+		for mut arg in data.named {
+			arg.expr = arg.expr.accept(self)?;
+			named.push(arg);
+		}
+		data.named = named;
 		ExprType::Call(data).to_expr(pos).wrap()
 	}
 	
@@ -155,6 +176,29 @@ impl ExprVisitor<Expression> for Optimizer {
 		ExprType::Binding(data).to_expr(pos).wrap()
 	}
 	
+	fn match_expr(&mut self, mut data: MatchData, pos: SourcePos) -> Result<Expression> {
+		data.value = data.value.accept(self)?.wrap();
+		for arm in data.arms.iter_mut() {
+			arm.guard = match arm.guard.take() {
+				Some(guard) => Some(guard.accept(self)?),
+				None => None,
+			};
+			arm.body = arm.body.clone().accept(self)?;
+		}
+		ExprType::Match(data).to_expr(pos).wrap()
+	}
+
+	fn spread(&mut self, expr: Box<Expression>, pos: SourcePos) -> Result<Expression> {
+		ExprType::Spread(expr.accept(self)?.wrap()).to_expr(pos).wrap()
+	}
+
+	fn range(&mut self, mut data: RangeData, pos: SourcePos) -> Result<Expression> {
+		if let Some(start) = data.start { data.start = Some(start.accept(self)?.wrap()); }
+		if let Some(end) = data.end { data.end = Some(end.accept(self)?.wrap()); }
+		if let Some(step) = data.step { data.step = Some(step.accept(self)?.wrap()); }
+		ExprType::Range(data).to_expr(pos).wrap()
+	}
+	
 }
 
 impl StmtVisitor<Statement> for Optimizer {
@@ -169,7 +213,13 @@ impl StmtVisitor<Statement> for Optimizer {
 		StmtType::Declaration(data).to_stmt(pos).wrap()
 	}
 	
+	fn destructure(&mut self, mut data: DestructureData, pos: SourcePos) -> Result<Statement> {
+		data.expr = data.expr.accept(self)?.wrap();
+		StmtType::Destructure(data).to_stmt(pos).wrap()
+	}
+	
 	fn func_declaration(&mut self, mut data: FunctionData, pos: SourcePos) -> Result<Statement> {
+		data.params = self.params(data.params)?;
 		data.body = self.optimize_block(data.body)?;
 		StmtType::FuncDeclaration(data).to_stmt(pos).wrap()
 	}
@@ -181,6 +231,7 @@ impl StmtVisitor<Statement> for Optimizer {
 
 		let mut methods = Vec::new();
 		for mut method in data.methods.clone() {
+			method.params = self.params(method.params)?;
 			method.body = self.optimize_block(method.body)?;
 			methods.push(method);
 		}
@@ -189,6 +240,10 @@ impl StmtVisitor<Statement> for Optimizer {
 		StmtType::AttrDeclaration(data).to_stmt(pos).wrap()
 	}
 	
+	fn import(&mut self, data: ImportData, pos: SourcePos) -> Result<Statement> {
+		StmtType::Import(data).to_stmt(pos).wrap()
+	}
+	
 	fn assignment(&mut self, mut data: AssignData, pos: SourcePos) -> Result<Statement> {
 		data.expr = data.expr.accept(self)?.wrap();
 		StmtType::Assignment(data).to_stmt(pos).wrap()
@@ -211,7 +266,7 @@ impl StmtVisitor<Statement> for Optimizer {
 	}
 	
 	fn continue_stmt(&mut self, pos: SourcePos) -> Result<Statement> {
-		StmtType::Break.to_stmt(pos).wrap()
+		StmtType::Continue.to_stmt(pos).wrap()
 	}
 	
 	fn return_stmt(&mut self, mut expr: Box<Expression>, pos: SourcePos) -> Result<Statement> {
@@ -1,7 +1,7 @@
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::{ast::{identifier::Identifier, expression::*, statement::*, Block, module::Module}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, global_ids::get_global_identifiers}};
+use crate::{ast::{identifier::{Identifier, Location}, expression::*, pattern::{Pattern, PatternType}, statement::*, Block, module::Module}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, global_ids::get_global_identifiers}};
 
 macro_rules! with_ctx {
 	($self:ident, $block:expr, $ctx:ident: $val:expr) => {{
@@ -23,16 +23,27 @@ macro_rules! with_ctx {
 pub struct IdentifierData {
 	id: usize,
 	constant: bool,
+	slot: usize,
 }
 
 impl IdentifierData {
 	pub fn new(id: usize, constant: bool) -> Self {
-		Self { id, constant }
+		Self { id, constant, slot: 0 }
 	}
 }
 
 type SymbolTable = HashMap<String, IdentifierData>;
 
+// the global and module scopes hold their names by id, every scope after them is a local one
+const MODULE_SCOPE: usize = 1;
+
+#[derive(Clone, Debug, Default)]
+struct Scope {
+	symbols: SymbolTable,
+	// shadowed names still keep their slot, so this can be larger than symbols.len()
+	slots: usize,
+}
+
 fn allowed(cond: bool, msg: &str, pos: SourcePos) -> Result<()> {
 	if cond { Ok(()) }
 	else { ErrorList::comp(msg.to_owned(), pos).err() }
@@ -47,14 +58,21 @@ struct Context {
 	overwriting: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Resolver {
 	last_id: usize,
-	tables: Vec<SymbolTable>,
+	max_id: usize,
+	tables: Vec<Scope>,
 	globals: SymbolTable,
 	ctx: Context,
 }
 
+impl Default for Resolver {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl Resolver {
 
 	pub fn new() -> Self {
@@ -62,8 +80,9 @@ impl Resolver {
 
 		Resolver {
 			last_id: globals.len() + 1,
+			max_id: globals.len(),
 			globals: globals.clone(),
-			tables: vec![globals],
+			tables: vec![Scope { symbols: globals, slots: 0 }],
 			ctx: Context::default(),
 		}
 	}
@@ -87,6 +106,75 @@ impl Resolver {
 		errors.if_empty(())
 	}
 
+	// imported modules share the interpreter's globals, so each one gets its own range of ids
+	pub fn with_base(base: usize) -> Self {
+		let mut resolver = Self::new();
+		resolver.reserve(base);
+		resolver
+	}
+
+	pub fn reserve(&mut self, next_id: usize) {
+		self.last_id = self.last_id.max(next_id);
+	}
+
+	pub fn max_id(&self) -> usize {
+		self.max_id
+	}
+
+	pub fn repl() -> Self {
+		let mut resolver = Self::new();
+		resolver.push_scope();
+		resolver
+	}
+
+	// a name defined from outside of any code, like the values handed to an embedded interpreter
+	pub fn declare(&mut self, name: &str) -> Result<usize> {
+		let iden = Identifier::new(name.to_owned(), SourcePos::new(0, 0));
+		self.add(iden.clone(), false, iden.pos)?;
+		Ok(iden.get_id())
+	}
+
+	pub fn lookup(&self, name: &str) -> Option<usize> {
+		self.get_var(name).map(|var| var.id)
+	}
+
+	// sees the names of a running program where it stopped, so the debugger can evaluate code in there
+	pub fn frame(module: Vec<(String, usize)>, locals: Vec<Vec<String>>, next_id: usize) -> Self {
+		let mut resolver = Self::new();
+		resolver.reserve(next_id);
+		resolver.ctx.in_function = true;
+
+		resolver.push_scope();
+		for (name, id) in module {
+			resolver.tables[MODULE_SCOPE].symbols.insert(name, IdentifierData::new(id, false));
+		}
+
+		for names in locals {
+			resolver.push_scope();
+			let scope = resolver.tables.last_mut().unwrap();
+			scope.slots = names.len();
+			for (slot, name) in names.into_iter().enumerate().filter(|(_, name)| !name.is_empty()) {
+				scope.symbols.insert(name, IdentifierData { id: resolver.last_id, constant: false, slot });
+				resolver.last_id += 1;
+			}
+		}
+		resolver
+	}
+
+	pub fn resolve_repl(&mut self, module: &Module, block: &Block) -> Result<()> {
+		let mut errors = ErrorList::new();
+
+		for id in module.env.keys().cloned() {
+			errors.try_append(self.add(id, true, SourcePos::new(1, 1)));
+		}
+
+		for stmt in module.env.values().cloned().chain(block.clone()) {
+			errors.try_append(stmt.accept(self));
+		}
+
+		errors.if_empty(())
+	}
+
 	// pub fn resolve_script(&mut self, module: &Module, block: &Block) -> Result<()> {
 	// 	self.resolv
 	// }
@@ -108,32 +196,134 @@ impl Resolver {
 			return ErrorList::comp(format!("Cannot redefine global constant '{}'", iden), pos).err();
 		}
 
+		let depth = self.tables.len() - 1;
+		let scope = self.tables.last_mut().unwrap();
+		let slot = scope.slots;
+		scope.slots += 1;
+
 		*iden.id.borrow_mut() = self.last_id;
-		self.tables.last_mut().unwrap().insert(iden.get_name(), IdentifierData::new(iden.get_id(), constant));
+		*iden.location.borrow_mut() = if depth > MODULE_SCOPE { Location::Local(0, slot) } else { Location::Global };
+		scope.symbols.insert(iden.get_name(), IdentifierData { id: iden.get_id(), constant, slot });
+		self.max_id = self.max_id.max(self.last_id);
 		self.last_id += 1;
 		Ok(())
 	}
 
 	fn push_scope(&mut self) {
-		self.tables.push(SymbolTable::new());
+		self.tables.push(Scope::default());
 	}
 
 	fn pop_scope(&mut self) {
-		self.last_id -= self.tables.last().unwrap().len();
+		self.last_id -= self.tables.last().unwrap().symbols.len();
 		self.tables.pop();
 	}
 
 	fn get_var(&self, name: &str) -> Option<IdentifierData> {
+		self.find_var(name).map(|(var, _)| var)
+	}
+
+	fn find_var(&self, name: &str) -> Option<(IdentifierData, Location)> {
 		let mut cur = self.tables.as_slice();
 		while let [rest @ .., table] = cur {
-			match table.get(name) {
-				Some(id) => return Some(id.clone()),
+			match table.symbols.get(name) {
+				Some(var) => {
+					let location = if rest.len() > MODULE_SCOPE {
+						Location::Local(self.tables.len() - 1 - rest.len(), var.slot)
+					} else {
+						Location::Global
+					};
+					return Some((var.clone(), location));
+				}
 				None => cur = rest,
 			}
 		}
 		None
 	}
 
+	// match bindings live in the scope of their arm, so they can be used by its guard and body
+	fn pattern(&mut self, pattern: &Pattern, constant: bool) -> Result<()> {
+		let mut errors = ErrorList::new();
+		errors.try_append(self.pattern_attributes(pattern));
+
+		let mut names = HashSet::new();
+		for name in pattern.bindings() {
+			if !names.insert(name.get_name()) {
+				errors.add_comp(format!("Name '{}' is bound more than once in the same pattern", name), pattern.pos);
+				continue;
+			}
+			errors.try_append(self.add(name, constant, pattern.pos));
+		}
+
+		errors.if_empty(())
+	}
+
+	fn pattern_attributes(&mut self, pattern: &Pattern) -> Result<()> {
+		let mut errors = ErrorList::new();
+		let attrs = match &pattern.typ {
+			PatternType::Attribute(attr) => vec![attr.clone()],
+			PatternType::Object(fields, attrs) => {
+				for (_, field) in fields { errors.try_append(self.pattern_attributes(field)); }
+				attrs.clone()
+			}
+			PatternType::List(patterns) => {
+				for item in patterns { errors.try_append(self.pattern_attributes(item)); }
+				vec![]
+			}
+			PatternType::Rest(pattern) => return self.pattern_attributes(pattern),
+			_ => vec![],
+		};
+		for attr in attrs {
+			if let Some(var) = self.get_var(&attr.get_name()) {
+				*attr.id.borrow_mut() = var.id;
+			} else {
+				errors.add_comp(format!("Use of undefined attribute {}", attr.get_name()), pattern.pos);
+			}
+		}
+		errors.if_empty(())
+	}
+
+	// a default is resolved before its own parameter is declared, so it only sees the ones before it
+	fn params(&mut self, params: &[Param]) -> Result<()> {
+		let mut errors = ErrorList::new();
+		let mut names = HashSet::new();
+		let mut optional = false;
+		for (i, param) in params.iter().enumerate() {
+			let pos = param.name.pos;
+			if !names.insert(param.name.get_name()) {
+				errors.add_comp(format!("Parameter '{}' is declared more than once", param.name), pos);
+			}
+			if param.rest {
+				if i + 1 != params.len() {
+					errors.add_comp(format!("Rest parameter {} must be the last one", param.label()), pos);
+				}
+				if param.default.is_some() {
+					errors.add_comp(format!("Rest parameter {} can't have a default value", param.label()), pos);
+				}
+			} else if param.default.is_some() {
+				optional = true;
+			} else if optional {
+				errors.add_comp(format!("Parameter {} needs a default value, like the ones before it", param.label()), pos);
+			}
+			if let Some(ref default) = param.default {
+				with_ctx!(self, errors.try_append(default.clone().accept(self)), in_function: true);
+			}
+			errors.try_append(self.add(param.name.clone(), false, pos));
+
+			// the names a destructured parameter binds can't clash with any other parameter either
+			if let Some(ref pattern) = param.pattern {
+				errors.try_append(self.pattern_attributes(pattern));
+				for name in pattern.bindings() {
+					if !names.insert(name.get_name()) {
+						errors.add_comp(format!("Parameter '{}' is declared more than once", name), name.pos);
+						continue;
+					}
+					errors.try_append(self.add(name.clone(), false, name.pos));
+				}
+			}
+		}
+		errors.if_empty(())
+	}
+
 }
 
 impl ExprVisitor<()> for Resolver {
@@ -144,10 +334,11 @@ impl ExprVisitor<()> for Resolver {
 		let exprs = match data {
 			LiteralData::List(exprs) => exprs,
 			LiteralData::Template(exprs) => exprs,
-			LiteralData::Object(map, attrs) => {
+			LiteralData::Map(entries) => entries.into_iter().flat_map(|(key, val)| [key, val]).collect(),
+			LiteralData::Object(map, attrs, spreads) => {
 				let exprs = map.clone().into_values();
 				// with_ctx!(self, for expr in exprs { errors.try_append(expr.accept(self)); }, in_obj: true);
-				for expr in exprs { errors.try_append(expr.accept(self)); };
+				for expr in exprs.chain(spreads) { errors.try_append(expr.accept(self)); };
 				for attr in attrs {
 					if let Some(var) = self.get_var(&attr.get_name()) {
 						*attr.id.borrow_mut() = var.id;
@@ -191,7 +382,7 @@ impl ExprVisitor<()> for Resolver {
 	}
 
 	fn variable(&mut self, data: Identifier, pos: SourcePos) -> Result<()> {
-		if let Some(var) = self.get_var(&data.name) {
+		if let Some((var, location)) = self.find_var(&data.name) {
 			if self.ctx.overwriting {
 				if var.id < self.globals.len() {
 					return ErrorList::comp(format!("Cannot assign to global constant '{}'", data), pos).err();
@@ -201,18 +392,17 @@ impl ExprVisitor<()> for Resolver {
 			}
 
 			*data.id.borrow_mut() = var.id;
+			*data.location.borrow_mut() = location;
 			Ok(())
 		} else {
 			ErrorList::comp(format!("Use of undefined variable '{}'", data), pos).err()
 		}
 	}
 
-	fn lambda(&mut self, data: LambdaData, pos: SourcePos) -> Result<()> {
+	fn lambda(&mut self, data: LambdaData, _pos: SourcePos) -> Result<()> {
 		let mut errors = ErrorList::new();
 		self.push_scope();
-		for param in data.params {
-			errors.try_append(self.add(param, false, pos));
-		}
+		errors.try_append(self.params(&data.params));
 		with_ctx!(self, errors.try_append(self.resolve_block(&data.body)), in_function: true);
 		self.pop_scope();
 		errors.if_empty(())
@@ -224,6 +414,13 @@ impl ExprVisitor<()> for Resolver {
 		for arg in data.args {
 			errors.try_append(arg.accept(self));
 		}
+		let mut names = HashSet::new();
+		for arg in data.named {
+			if !names.insert(arg.name.clone()) {
+				errors.add_comp(format!("Argument '{}' is passed more than once", arg.name), arg.pos);
+			}
+			errors.try_append(arg.expr.accept(self));
+		}
 		errors.if_empty(())
 	}
 
@@ -253,17 +450,42 @@ impl ExprVisitor<()> for Resolver {
 		errors.if_empty(())
 	}
 
+	fn match_expr(&mut self, data: MatchData, _pos: SourcePos) -> Result<()> {
+		let mut errors = ErrorList::new();
+		errors.try_append(data.value.accept(self));
+		for arm in data.arms {
+			self.push_scope();
+			errors.try_append(self.pattern(&arm.pattern, false));
+			if let Some(guard) = arm.guard {
+				errors.try_append(guard.accept(self));
+			}
+			errors.try_append(arm.body.accept(self));
+			self.pop_scope();
+		}
+		errors.if_empty(())
+	}
+
+	fn spread(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
+		expr.accept(self)
+	}
+
+	fn range(&mut self, data: RangeData, _pos: SourcePos) -> Result<()> {
+		let mut errors = ErrorList::new();
+		for bound in data.start.into_iter().chain(data.end).chain(data.step) {
+			errors.try_append(bound.accept(self));
+		}
+		errors.if_empty(())
+	}
+
 }
 
 impl StmtVisitor<()> for Resolver {
 
-	fn func_declaration(&mut self, data: FunctionData, pos: SourcePos) -> Result<()> {
+	fn func_declaration(&mut self, data: FunctionData, _pos: SourcePos) -> Result<()> {
 		let mut errors = ErrorList::new();
 		// errors.try_append(self.add(data.name, true, pos));
 		self.push_scope();
-		for param in data.params {
-			errors.try_append(self.add(param, false, pos));
-		}
+		errors.try_append(self.params(&data.params));
 		with_ctx!(self, errors.try_append(self.resolve_block(&data.body)), in_function: true);
 		self.pop_scope();
 		errors.if_empty(())
@@ -274,9 +496,7 @@ impl StmtVisitor<()> for Resolver {
 		// errors.try_append(self.add(data.name, true, pos));
 		for method in data.methods {
 			self.push_scope();
-			for param in method.params {
-				errors.try_append(self.add(param, false, pos));
-			}
+			errors.try_append(self.params(&method.params));
 			// with_ctx!(self, errors.try_append(self.resolve(&method.body)), in_method: true);
 			with_ctx!(self, errors.try_append(self.resolve_block(&method.body)), in_function: true);
 			self.pop_scope();
@@ -294,6 +514,10 @@ impl StmtVisitor<()> for Resolver {
 		errors.if_empty(())
 	}
 
+	fn import(&mut self, _data: ImportData, _pos: SourcePos) -> Result<()> {
+		Ok(())
+	}
+
 	fn expr(&mut self, expr: Box<Expression>, _pos: SourcePos) -> Result<()> {
 		expr.accept(self)
 	}
@@ -301,7 +525,7 @@ impl StmtVisitor<()> for Resolver {
 	fn declaration(&mut self, data: DeclarationData, pos: SourcePos) -> Result<()> {
 		let mut errors = ErrorList::new();
 		match data.expr.typ.clone() {
-			ExprType::Lambda(_) | ExprType::Literal(LiteralData::Object(_, _)) => {
+			ExprType::Lambda(_) | ExprType::Literal(LiteralData::Object(..)) => {
 				errors.try_append(self.add(data.name, data.constant, pos));
 				errors.try_append(data.expr.accept(self));
 			},
@@ -313,6 +537,13 @@ impl StmtVisitor<()> for Resolver {
 		errors.if_empty(())
 	}
 
+	fn destructure(&mut self, data: DestructureData, _pos: SourcePos) -> Result<()> {
+		let mut errors = ErrorList::new();
+		errors.try_append(data.expr.accept(self));
+		errors.try_append(self.pattern(&data.pattern, data.constant));
+		errors.if_empty(())
+	}
+
 	fn assignment(&mut self, data: AssignData, _pos: SourcePos) -> Result<()> {
 		let mut errors = ErrorList::new();
 		with_ctx!(self, errors.try_append(data.head.accept(self)), overwriting: true);
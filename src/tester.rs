@@ -0,0 +1,156 @@
+use std::{path::{Path, PathBuf}, time::Instant};
+
+use ansi_term::Color;
+
+use crate::{ast::{module::Module, statement::StmtType}, interpreter::{Backend, Interpreter, value::macros::castf}, lexer::{Lexer, LexerResult}, parser::Parser, semantics::{checker::Checker, optimizer::Optimizer, resolver::Resolver}, utils::{result::{ErrorList, Result}, source_pos::SourcePos}};
+
+// tests are module functions named test_something, or any function right below a `#test` comment
+struct Test {
+	name: String,
+	id: usize,
+	pos: SourcePos,
+}
+
+struct TestFile {
+	module: Module,
+	tests: Vec<Test>,
+	max_id: usize,
+}
+
+#[derive(Default)]
+struct Summary {
+	passed: usize,
+	failed: usize,
+}
+
+// scripts run as soon as they are loaded, so they can't hold tests and give back None
+fn load(path: &str) -> Result<Option<TestFile>> {
+	let lexer = Lexer::from_file(path).map_err(|err| ErrorList::sys(err.to_string()))?;
+	let LexerResult { tokens, directives, comments, mut errors } = lexer.scan_tokens();
+
+	if directives.contains("script") { return Ok(None); }
+
+	let mut module = Parser::new(tokens).module()?;
+
+	let mut resolver = Resolver::new();
+	errors.try_append(resolver.resolve(&module));
+	errors.try_append(Checker::new().check(&module));
+	errors.if_empty(())?;
+
+	Optimizer.optimize(&mut module)?;
+
+	let marked = comments.iter().filter(|comment| comment.text.trim() == "#test").map(|comment| comment.pos.lin + 1).collect::<Vec<_>>();
+	let mut tests = module.env.iter().filter_map(|(id, stmt)| match stmt.typ {
+		StmtType::FuncDeclaration(_) if id.name.starts_with("test_") || marked.contains(&stmt.pos.lin) => Some(Test { name: id.get_name(), id: id.get_id(), pos: stmt.pos }),
+		_ => None,
+	}).collect::<Vec<_>>();
+	tests.sort_by_key(|test| (test.pos.lin, test.pos.col));
+
+	Ok(Some(TestFile { module, tests, max_id: resolver.max_id() }))
+}
+
+// every test gets its own interpreter, so state left behind by one can't leak into the next
+fn run_test(path: &str, file: &TestFile, test: &Test, backend: Backend) -> Result<()> {
+	let mut root = Path::new(path).to_path_buf();
+	root.pop();
+
+	let mut interpreter = Interpreter::new(root);
+	interpreter.backend = backend;
+	interpreter.modules.reserve(file.max_id);
+	interpreter.modules.enter(Path::new(path));
+	interpreter.interpret(file.module.clone())?;
+
+	let fun = interpreter.env.get_id(test.id);
+	let ret = castf!(fun fun).call(test.pos, &mut interpreter, vec![])?;
+
+	// like main, a test can also fail by returning an error
+	if ret.to_error(test.pos).is_ok() {
+		let msg = format!("Test returned {}", ret.to_string(&mut interpreter, test.pos)?);
+		return ErrorList::run(msg, test.pos).err();
+	}
+	Ok(())
+}
+
+fn run_file(path: &str, file: TestFile, backend: Backend, summary: &mut Summary) {
+	println!("running {} test{} from {}", file.tests.len(), if file.tests.len() == 1 { "" } else { "s" }, path);
+
+	let mut failures = Vec::new();
+	for test in &file.tests {
+		let start = Instant::now();
+		let res = run_test(path, &file, test, backend);
+		let elapsed = start.elapsed();
+		match res {
+			Ok(()) => {
+				summary.passed += 1;
+				println!("test {} ... {} ({:.2?})", test.name, Color::Green.paint("ok"), elapsed);
+			}
+			Err(errors) => {
+				summary.failed += 1;
+				println!("test {} ... {} ({:.2?})", test.name, Color::Red.paint("FAILED"), elapsed);
+				failures.push((&test.name, errors));
+			}
+		}
+	}
+
+	for (name, errors) in failures {
+		println!("\n---- {} ----", name);
+		errors.report(path);
+	}
+	println!();
+}
+
+fn collect(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+	if path.is_dir() {
+		let mut entries = std::fs::read_dir(path)?.map(|entry| entry.map(|entry| entry.path())).collect::<std::io::Result<Vec<_>>>()?;
+		entries.sort();
+		for entry in entries { collect(&entry, files)?; }
+	} else if path.extension().is_some_and(|ext| ext == "mars") {
+		files.push(path.to_path_buf());
+	}
+	Ok(())
+}
+
+// runs the tests in every given file, and in every mars file inside the given directories
+pub fn run(paths: Vec<String>, backend: Backend) -> bool {
+	if paths.is_empty() {
+		eprintln!("{}: expected the paths of the files to test", Color::Red.paint("error"));
+		return false;
+	}
+
+	let start = Instant::now();
+	let mut summary = Summary::default();
+	let mut ok = true;
+
+	for path in paths {
+		let explicit = !Path::new(&path).is_dir();
+		let mut files = Vec::new();
+		if explicit {
+			files.push(PathBuf::from(&path));
+		} else if let Err(err) = collect(Path::new(&path), &mut files) {
+			ErrorList::sys(err.to_string()).report(&path);
+			ok = false;
+		}
+		for file in files {
+			let file = file.to_string_lossy().to_string();
+			match load(&file) {
+				Ok(Some(tests)) => run_file(&file, tests, backend, &mut summary),
+				// scripts found while walking a directory are just skipped
+				Ok(None) if !explicit => (),
+				Ok(None) => {
+					ErrorList::mod_comp("Cannot run tests from a script".to_owned()).report(&file);
+					ok = false;
+				}
+				Err(errors) => {
+					errors.report(&file);
+					ok = false;
+				}
+			}
+		}
+	}
+
+	let elapsed = start.elapsed();
+	let result = if ok && summary.failed == 0 { Color::Green.paint("ok") } else { Color::Red.paint("FAILED") };
+	println!("test result: {}. {} passed; {} failed; finished in {:.2?}", result, summary.passed, summary.failed, elapsed);
+
+	ok && summary.failed == 0
+}
@@ -20,12 +20,21 @@ pub static GLOBAL_IDS: &[&str] = &[
 	"random", "rand",
 	"char",
 	"paint",
+
+	// testing
+	"assert", "assert_eq", "assert_error",
 	
 	// std lib	
-	"math", "fs",
+	"math", "fs", "gc",
 
 	// attributes
-	"String", "List", "Error",
+	"String", "List", "Map", "Error", "Iter",
+
+	// error kinds
+	"IoError", "ParseError", "ValueError",
+
+	// used by desugared for loops, can't be named from source
+	"$iter", "$done",
 ];
 
 pub fn global_id(global: &str) -> usize {
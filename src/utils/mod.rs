@@ -1,5 +1,6 @@
 
 pub mod wrap;
 pub mod source_pos;
+pub mod sources;
 pub mod result;
 pub mod global_ids;
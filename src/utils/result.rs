@@ -1,7 +1,11 @@
 
 use std::fmt::{Debug, Display};
 
-use super::source_pos::SourcePos;
+use super::{source_pos::SourcePos, sources::get_source};
+
+fn file_name(pos: SourcePos, default: &str) -> String {
+	get_source(pos.file).map_or_else(|| default.to_owned(), |src| src.name.clone())
+}
 
 macro_rules! append {
 	(ret comp $str:expr, $pos:expr; to $errors:expr) => {{
@@ -89,40 +93,80 @@ impl Display for Stage {
 	}
 }
 
+// the name of a called function and the position it was called from
+pub type CallFrame = (String, SourcePos);
+
 #[derive(Clone, Debug)]
 struct Error {
 	msg: String,
 	pos: Option<SourcePos>,
 	stage: Stage,
+	trace: Vec<CallFrame>,
 }
 
 impl Error {
 	
 	fn new(msg: String, pos: Option<SourcePos>, stage: Stage) -> Error {
-		Error { msg, pos, stage }
+		Error { msg, pos, stage, trace: Vec::new() }
+	}
+
+	fn report_trace(&self, name: &str) {
+		let location = |pos: Option<SourcePos>| match pos {
+			Some(pos) => format!("[{}:{}:{}]", file_name(pos, name), pos.lin, pos.col),
+			None => format!("[{}]", name),
+		};
+
+		let mut lines = Vec::new();
+		let mut at = self.pos;
+		for (function, call_pos) in self.trace.iter().rev() {
+			lines.push(format!("at {} {}", function, location(at)));
+			at = Some(*call_pos);
+		}
+		lines.push(format!("at <top level> {}", location(at)));
+
+		// recursive calls would otherwise flood the output with the same lines
+		let mut i = 0;
+		while i < lines.len() {
+			let repeats = lines[i..].iter().take_while(|line| **line == lines[i]).count();
+			eprintln!("  {}", lines[i]);
+			if repeats > 1 { eprintln!("  ... repeated {} more times", repeats - 1); }
+			i += repeats;
+		}
 	}
 
-	fn report(&self, path: &str) {
+	fn report(&self, default_name: &str, source: &str) {
+		// positions coming from imported modules are reported against their own file
+		let registered = self.pos.and_then(|pos| get_source(pos.file));
+		let (name, source) = match registered {
+			Some(ref src) => (src.name.as_str(), src.text.as_str()),
+			None => (default_name, source),
+		};
+
 		eprintln!("{} {}: {}",
 			ansi_term::Color::Red.bold().paint(format!("{} error", self.stage)),
-			if let Some(pos) = self.pos { format!("[{}:{}:{}]", path, pos.lin, pos.col) } else { format!("[{}]", path) },
+			if let Some(pos) = self.pos { format!("[{}:{}:{}]", name, pos.lin, pos.col) } else { format!("[{}]", name) },
 			self.msg
 		);
 
-		if let Some(pos) = self.pos {
-			let data = std::fs::read_to_string(path).unwrap();
-			let line = data.lines().skip(pos.lin as usize - 1).next().unwrap().replace("\t", " ");
+		let line = self.pos.and_then(|pos| source.lines().nth((pos.lin as usize).checked_sub(1)?));
+		if let (Some(pos), Some(line)) = (self.pos, line) {
+			let line = line.replace("\t", " ");
+			let start = (pos.col as usize).max(1);
+			let end = if pos.end_lin == pos.lin { pos.end_col as usize } else { line.chars().count() };
 	
 			let bar_offset = " ".repeat(pos.lin.to_string().len());
 	
 			eprintln!(" {} |", bar_offset);
 			eprintln!(" {} | {}", pos.lin, line);
-			eprintln!(" {} | {}^",
+			eprintln!(" {} | {}{}",
 				bar_offset,
-				" ".repeat(pos.col as usize - 1),
+				" ".repeat(start - 1),
+				"^".repeat(end.saturating_sub(start) + 1),
 			);
 		}
 
+		if !self.trace.is_empty() { self.report_trace(default_name); }
+
 		eprintln!();
 	}
 
@@ -131,6 +175,12 @@ impl Error {
 #[derive(Debug, Clone)]
 pub struct ErrorList(Vec<Error>);
 
+impl Default for ErrorList {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl ErrorList {
 	pub fn new() -> Self { Self(Vec::new()) }
 	pub fn from(msg: String, pos: SourcePos, stage: Stage) -> Self { Self(vec![Error::new(msg, Some(pos), stage)]) }
@@ -146,8 +196,18 @@ impl ErrorList {
 	pub fn add_run(&mut self, msg: String, pos: SourcePos) { self.0.push(Error::new(msg, Some(pos), Stage::Run)) }
 	pub fn append(&mut self, mut err: ErrorList) { self.0.append(&mut err.0) }
 	pub fn try_append<T>(&mut self, res: Result<T>) { if let Err(err) = res { self.append(err) } }
-	pub fn report(&self, path: &str) { self.0.iter().for_each(|err| err.report(path)) }
+	pub fn report(&self, path: &str) { self.report_source(path, &std::fs::read_to_string(path).unwrap_or_default()) }
+	pub fn report_source(&self, name: &str, source: &str) { self.0.iter().for_each(|err| err.report(name, source)) }
 	pub fn if_empty<T>(self, ret: T) -> Result<T> { if self.is_empty() { Ok(ret) } else { self.err() } }
+	pub fn entries(&self) -> impl Iterator<Item = (&str, Option<SourcePos>)> { self.0.iter().map(|err| (err.msg.as_str(), err.pos)) }
+
+	// errors keep the call stack of the innermost function they left
+	pub fn with_trace(mut self, trace: &[CallFrame]) -> Self {
+		for err in self.0.iter_mut().filter(|err| err.trace.is_empty()) {
+			err.trace = trace.to_vec();
+		}
+		self
+	}
 }
 
 impl Display for ErrorList {
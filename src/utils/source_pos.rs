@@ -1,15 +1,30 @@
 
 use std::fmt::Display;
 
+// positions span from (lin, col) to (end_lin, end_col), both inclusive
+// file ids come from the sources registry, 0 means the position isn't tied to any file
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SourcePos {
 	pub lin: u32,
 	pub col: u32,
+	pub end_lin: u32,
+	pub end_col: u32,
+	pub file: usize,
 }
 
 impl SourcePos {
 	pub fn new(lin: u32, col: u32) -> Self {
-		Self { lin, col }
+		Self { lin, col, end_lin: lin, end_col: col, file: 0 }
+	}
+
+	pub fn in_file(lin: u32, col: u32, file: usize) -> Self {
+		Self { file, ..Self::new(lin, col) }
+	}
+
+	// a span from the start of this position to the end of the other
+	pub fn to(self, other: SourcePos) -> Self {
+		if self.file != other.file { return self; }
+		Self { end_lin: other.end_lin, end_col: other.end_col, ..self }
 	}
 }
 
@@ -0,0 +1,46 @@
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Debug)]
+pub struct Source {
+	pub name: String,
+	pub text: String,
+}
+
+thread_local! {
+	static SOURCES: RefCell<Vec<Rc<Source>>> = const { RefCell::new(Vec::new()) };
+}
+
+// every lexed text is registered here, so errors can be reported against the file they came from
+pub fn add_source(name: &str, text: &str) -> usize {
+	SOURCES.with(|sources| {
+		let mut sources = sources.borrow_mut();
+		sources.push(Rc::new(Source { name: name.to_owned(), text: text.to_owned() }));
+		sources.len()
+	})
+}
+
+// files that are edited in place, like the ones open in the language server, keep a single entry
+pub fn replace_source(name: &str, text: &str) -> usize {
+	SOURCES.with(|sources| {
+		let mut sources = sources.borrow_mut();
+		let source = Rc::new(Source { name: name.to_owned(), text: text.to_owned() });
+		match sources.iter().position(|src| src.name == name) {
+			Some(i) => { sources[i] = source; i + 1 }
+			None => { sources.push(source); sources.len() }
+		}
+	})
+}
+
+// code that is run piece by piece, like the lines typed in the repl, keeps reusing the entry it was given
+pub fn set_source(file: usize, text: &str) {
+	SOURCES.with(|sources| {
+		let mut sources = sources.borrow_mut();
+		let source = &mut sources[file - 1];
+		*source = Rc::new(Source { name: source.name.clone(), text: text.to_owned() });
+	})
+}
+
+pub fn get_source(file: usize) -> Option<Rc<Source>> {
+	if file == 0 { return None; }
+	SOURCES.with(|sources| sources.borrow().get(file - 1).cloned())
+}
@@ -0,0 +1,63 @@
+use std::rc::Rc;
+
+use crate::{compiler::chunk::Proto, interpreter::{Interpreter, environment::Environment, value::{Value, ValueRef, gc::{Node, Tracer}}}, interpreter::value::primitives::callable::{Callable, NamedValue}, utils::{result::Result, source_pos::SourcePos}};
+
+#[derive(Debug, Clone)]
+pub struct Closure {
+	pub proto: Rc<Proto>,
+	pub upvalues: Vec<ValueRef>,
+	pub receiver: Option<Box<dyn Value>>,
+	pub env: Environment,
+}
+
+impl Closure {
+	pub fn new(proto: Rc<Proto>, upvalues: Vec<ValueRef>, receiver: Option<Box<dyn Value>>, env: Environment) -> Self {
+		Self { proto, upvalues, receiver, env }
+	}
+}
+
+impl Callable for Closure {
+	fn cloned(&self) -> Box<dyn Callable> { Box::new(self.clone()) }
+
+	fn display(&self) -> String {
+		if let Some(ref name) = self.proto.name {
+			format!("<function {}>", name)
+		} else {
+			"<lambda>".to_owned()
+		}
+	}
+
+	fn arity(&self) -> usize { self.proto.params.arity() }
+
+	fn optional(&self) -> usize { self.proto.params.optional }
+
+	fn variadic(&self) -> bool { self.proto.params.rest }
+
+	fn bind(&mut self, binding: Box<dyn Value>) {
+		self.receiver = Some(binding);
+	}
+
+	fn closure(&self) -> Option<Closure> { Some(self.clone()) }
+
+	fn trace(&self, visit: &mut Tracer) {
+		self.upvalues.iter().for_each(|upvalue| visit(Node::Cell(upvalue.clone())));
+		if let Some(ref receiver) = self.receiver { receiver.trace(visit); }
+		self.env.trace(visit);
+	}
+
+	fn call(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>) -> Result<Box<dyn Value>> {
+		self.call_named(pos, interpreter, args, Vec::new())
+	}
+
+	fn call_named(&self, pos: SourcePos, interpreter: &mut Interpreter, args: Vec<(Box<dyn Value>, SourcePos)>, named: Vec<NamedValue>) -> Result<Box<dyn Value>> {
+		let args = if named.is_empty() && self.proto.params.is_plain(args.len()) {
+			args.into_iter().map(|(val, _)| Some(val)).collect()
+		} else {
+			self.proto.params.bind(&self.display(), args, named, pos)?
+		};
+		interpreter.call_stack.push((self.display(), pos));
+		let ret = super::run(interpreter, self.clone(), args);
+		interpreter.call_stack.pop();
+		ret
+	}
+}
@@ -0,0 +1,367 @@
+pub mod closure;
+
+use std::{cell::RefCell, collections::{HashMap, HashSet}, rc::Rc};
+
+use crate::{ast::{Block, expression::BinaryOperator, module::Module}, compiler::{Compiler, chunk::{Capture, Op}}, interpreter::{Interpreter, get_index, slice, assign_slice, spread_object, value::{Value, ValueRef, ValueType, gc, macros::castf, primitives::{attribute::Attribute, bool::Bool, callable::{Callable, ValCallable}, error::Error, list::List, map::{Map, MapData}, none::ValNone, number::Number, object::Object, range::Range, string::Str}}}, utils::{result::{ErrorList, Result}, source_pos::SourcePos, wrap::Wrap}};
+
+use self::closure::Closure;
+
+struct Frame {
+	closure: Closure,
+	ip: usize,
+	slots: Vec<ValueRef>,
+	// where this call's values start on the stack
+	base: usize,
+	// the stack height at the start of each loop, so break and continue can drop what's left of a statement
+	loops: Vec<usize>,
+	// which parameters were given a value by the call, only kept for functions with defaults
+	passed: Vec<bool>,
+}
+
+impl Frame {
+	fn new(closure: Closure, args: Vec<Option<Box<dyn Value>>>, base: usize) -> Self {
+		let passed = if closure.proto.params.optional > 0 { args.iter().map(Option::is_some).collect() } else { Vec::new() };
+		let mut slots = args.into_iter().map(|arg| Rc::new(RefCell::new(arg.unwrap_or_else(ValNone::new)))).collect::<Vec<_>>();
+		slots.resize_with(closure.proto.slots.max(slots.len()), || Rc::new(RefCell::new(ValNone::new())));
+		Self { closure, ip: 0, slots, base, loops: Vec::new(), passed }
+	}
+}
+
+pub fn run_module(interpreter: &mut Interpreter, module: Module) -> Result<()> {
+	let proto = Compiler::new().compile_module(module)?;
+	let closure = Closure::new(Rc::new(proto), Vec::new(), None, interpreter.env.clone());
+	run(interpreter, closure, Vec::new())?;
+	Ok(())
+}
+
+pub fn run_block(interpreter: &mut Interpreter, block: Block) -> Result<()> {
+	let proto = Compiler::new().compile_block(block)?;
+	let closure = Closure::new(Rc::new(proto), Vec::new(), None, interpreter.env.clone());
+	run(interpreter, closure, Vec::new())?;
+	Ok(())
+}
+
+pub fn run(interpreter: &mut Interpreter, closure: Closure, args: Vec<Option<Box<dyn Value>>>) -> Result<Box<dyn Value>> {
+	let prev = interpreter.env.clone();
+	let depth = interpreter.call_stack.len();
+	let ret = execute(interpreter, closure, args);
+	let ret = interpreter.traced(ret);
+	interpreter.env = prev;
+	interpreter.call_stack.truncate(depth);
+	ret
+}
+
+fn binary(interpreter: &mut Interpreter, op: &BinaryOperator, lhs: Box<dyn Value>, rhs: Box<dyn Value>, l_pos: SourcePos, r_pos: SourcePos, pos: SourcePos) -> Result<Box<dyn Value>> {
+	match op {
+		BinaryOperator::Add => lhs.add(rhs, r_pos, interpreter, pos),
+		BinaryOperator::Sub => lhs.sub(rhs, r_pos, interpreter, pos),
+		BinaryOperator::Mul => lhs.mul(rhs, r_pos, interpreter, pos),
+		BinaryOperator::Div => lhs.div(rhs, r_pos, interpreter, pos),
+		BinaryOperator::Rem => Number::new(lhs.to_num(l_pos)? % rhs.to_num(r_pos)?).wrap(),
+		BinaryOperator::Lst => Bool::new(lhs.to_num(l_pos)? < rhs.to_num(r_pos)?).wrap(),
+		BinaryOperator::Lse => Bool::new(lhs.to_num(l_pos)? <= rhs.to_num(r_pos)?).wrap(),
+		BinaryOperator::Grt => Bool::new(lhs.to_num(l_pos)? > rhs.to_num(r_pos)?).wrap(),
+		BinaryOperator::Gre => Bool::new(lhs.to_num(l_pos)? >= rhs.to_num(r_pos)?).wrap(),
+		BinaryOperator::Equ => Bool::new(lhs.equals(rhs, r_pos, interpreter, pos)?).wrap(),
+		BinaryOperator::Neq => Bool::new(!lhs.equals(rhs, r_pos, interpreter, pos)?).wrap(),
+		BinaryOperator::Typ => Bool::new(lhs.has_attr(rhs.to_attr(r_pos)?.get_id(), interpreter)).wrap(),
+	}
+}
+
+// expands the spread items of a list or a call, each value keeps the index of the item it came from
+fn spread(interpreter: &mut Interpreter, values: Vec<Box<dyn Value>>, spreads: &[Option<SourcePos>]) -> Result<Vec<(Box<dyn Value>, usize)>> {
+	let mut items = Vec::new();
+	for (i, (val, spread)) in values.into_iter().zip(spreads).enumerate() {
+		match spread {
+			Some(pos) => items.extend(interpreter.spread_items(val, *pos)?.into_iter().map(|val| (val, i))),
+			None => items.push((val, i)),
+		}
+	}
+	Ok(items)
+}
+
+fn execute(interpreter: &mut Interpreter, closure: Closure, args: Vec<Option<Box<dyn Value>>>) -> Result<Box<dyn Value>> {
+	let mut stack: Vec<Box<dyn Value>> = Vec::new();
+	interpreter.env = closure.env.clone();
+	let mut frames = vec![Frame::new(closure, args, 0)];
+
+	macro_rules! pop { () => { stack.pop().expect("VM stack underflow") }; }
+	macro_rules! pop_n { ($n:expr) => { stack.split_off(stack.len() - $n) }; }
+
+	loop {
+		let frame = frames.last_mut().unwrap();
+		let proto = frame.closure.proto.clone();
+		let chunk = &proto.chunk;
+		let pos = chunk.positions[frame.ip];
+		let op = &chunk.code[frame.ip];
+		frame.ip += 1;
+
+		match op {
+			Op::Constant(i) => stack.push(chunk.constants[*i].clone()),
+			Op::None => stack.push(ValNone::new()),
+			Op::True => stack.push(Bool::new(true)),
+			Op::False => stack.push(Bool::new(false)),
+			Op::Pop => { pop!(); },
+
+			Op::DefineLocal(slot) => frame.slots[*slot] = Rc::new(RefCell::new(pop!())),
+			Op::GetLocal(slot) => stack.push(frame.slots[*slot].borrow().clone()),
+			Op::SetLocal(slot) => *frame.slots[*slot].borrow_mut() = pop!(),
+			Op::GetUpvalue(i) => stack.push(frame.closure.upvalues[*i].borrow().clone()),
+			Op::SetUpvalue(i) => *frame.closure.upvalues[*i].borrow_mut() = pop!(),
+			Op::DefineGlobal(id) => interpreter.env.define_id(*id, pop!()),
+			Op::GetGlobal(id) => stack.push(interpreter.env.get_id(*id)),
+			Op::SetGlobal(id) => interpreter.env.assign_id(*id, pop!()),
+			Op::GetSelf => match frame.closure.receiver {
+				Some(ref receiver) => stack.push(receiver.clone()),
+				None => return ErrorList::run("Unbound self".to_owned(), pos).err(),
+			},
+
+			Op::Binary(op, l_pos, r_pos) => {
+				let rhs = pop!();
+				let lhs = pop!();
+				stack.push(binary(interpreter, op, lhs, rhs, *l_pos, *r_pos, pos)?);
+			}
+			Op::Not => { let val = pop!(); stack.push(Bool::new(!val.is_truthy())) },
+			Op::Neg(expr_pos) => { let val = pop!(); stack.push(Number::new(-val.to_num(*expr_pos)?)) },
+			Op::Pos(expr_pos) => { let val = pop!(); stack.push(Number::new(val.to_num(*expr_pos)?)) },
+			Op::ToBool => { let val = pop!(); stack.push(Bool::new(val.is_truthy())) },
+
+			Op::Jump(target) => {
+				// jumping back closes a loop iteration
+				if *target < frame.ip { gc::collect_if_needed(); }
+				frame.ip = *target
+			}
+			Op::JumpIfFalse(target) => if !pop!().is_truthy() { frame.ip = *target },
+			Op::JumpIfTrue(target) => if pop!().is_truthy() { frame.ip = *target },
+			Op::JumpIfPassed(param, target) => if frame.passed[*param] { frame.ip = *target },
+			Op::JumpIfNone(target) => if stack.last().unwrap().get_type() == ValueType::None { frame.ip = *target },
+			Op::JumpIfPresent(target) => match stack.last().unwrap().get_type() {
+				ValueType::None | ValueType::Error => { pop!(); }
+				_ => frame.ip = *target,
+			},
+			Op::EnterLoop => frame.loops.push(stack.len()),
+			Op::ExitLoop => { frame.loops.pop(); },
+			Op::Unwind => stack.truncate(*frame.loops.last().expect("Unwind outside of a loop")),
+
+			Op::Call(i) => {
+				let site = &chunk.calls[*i];
+				let named = pop_n!(site.named.len());
+				let args = pop_n!(site.argc);
+				let callable = pop!().to_callable(site.calee_pos)?;
+				// a spread arg takes the position of the spread for each value it gives
+				let (args, spread_pos) = if site.spreads.iter().any(Option::is_some) {
+					let (args, from): (Vec<_>, Vec<_>) = spread(interpreter, args, &site.spreads)?.into_iter().unzip();
+					(args, Some(from.into_iter().map(|i| site.arg_pos[i]).collect::<Vec<_>>()))
+				} else {
+					(args, None)
+				};
+				let arg_pos = spread_pos.as_deref().unwrap_or(&site.arg_pos);
+				gc::collect_if_needed();
+				if named.is_empty() { callable.check_arity(args.len(), pos)?; }
+				let named = site.named.iter().zip(named).map(|((name, arg_pos), val)| (name.clone(), val, *arg_pos)).collect::<Vec<_>>();
+				match callable.closure() {
+					Some(closure) => {
+						let args = if named.is_empty() && closure.proto.params.is_plain(args.len()) {
+							args.into_iter().map(Some).collect()
+						} else {
+							let args = args.into_iter().zip(arg_pos.iter().cloned()).collect();
+							closure.proto.params.bind(&closure.display(), args, named, pos)?
+						};
+						interpreter.env = closure.env.clone();
+						interpreter.call_stack.push((closure.display(), site.calee_pos));
+						frames.push(Frame::new(closure, args, stack.len()));
+					}
+					None => {
+						let args = args.into_iter().zip(arg_pos.iter().cloned()).collect();
+						let ret = if named.is_empty() {
+							callable.call(site.calee_pos, interpreter, args)?
+						} else {
+							callable.call_named(site.calee_pos, interpreter, args, named)?
+						};
+						if ret.get_type() == ValueType::Messenger { return ret.wrap(); }
+						stack.push(ret);
+					}
+				}
+			}
+			Op::Index(head_pos, index_pos) => {
+				let index = pop!();
+				let head = pop!();
+				if head.get_type() == ValueType::Map {
+					match head.to_map(*head_pos)?.borrow().get(&index.to_key(*index_pos)?) {
+						Some(val) => stack.push(val),
+						None => return ErrorList::run("Key not found in map".to_owned(), *index_pos).err(),
+					}
+					continue;
+				}
+				if index.get_type() == ValueType::Range {
+					stack.push(slice(head.as_ref(), &index.to_range(*index_pos)?, *head_pos, *index_pos)?);
+					continue;
+				}
+				let list = match head.get_type() {
+					ValueType::Vector => head.to_list(*head_pos)?.borrow().clone(),
+					ValueType::Str => head.to_str(*head_pos)?.chars().map(|c| Str::new(c.to_string())).collect(),
+					typ => return ErrorList::run(format!("Cannot index {}", typ), *head_pos).err(),
+				};
+				let index = get_index(index.to_num(*index_pos)?, list.len(), *index_pos)?;
+				stack.push(list[index].clone());
+			}
+			Op::SetIndex(head_pos, index_pos) => {
+				let index = pop!();
+				let head = pop!();
+				let val = pop!();
+				if head.get_type() == ValueType::Map {
+					head.to_map(*head_pos)?.borrow_mut().insert(index.to_key(*index_pos)?, val);
+					continue;
+				}
+				let list = if let ValueType::Vector = head.get_type() {
+					castf!(vec head)
+				} else {
+					return ErrorList::run("Invalid assignment target".to_owned(), *head_pos).err()
+				};
+				if index.get_type() == ValueType::Range {
+					let items = interpreter.spread_items(val, *index_pos)?;
+					assign_slice(&list, &index.to_range(*index_pos)?, items, *index_pos)?;
+					continue;
+				}
+				let index = get_index(index.to_num(*index_pos)?, list.borrow().len(), *index_pos)?;
+				list.borrow_mut()[index] = val;
+			}
+			Op::GetField(name) => {
+				let head = pop!();
+				let field = head.get_field(&chunk.names[*name], interpreter, pos)?;
+				let field = field.borrow().clone();
+				if field.get_type() == ValueType::Callable && head.get_type() != ValueType::Attribute {
+					let mut bound_method = field.to_callable(pos)?.cloned();
+					bound_method.bind(head);
+					stack.push(ValCallable::new(bound_method.wrap()));
+				} else {
+					stack.push(field);
+				}
+			}
+			Op::SetField(name, head_pos) => {
+				let head = pop!();
+				let val = pop!();
+				let map = head.to_obj(*head_pos)?;
+				match map.get(&chunk.names[*name]) {
+					Some(cur) => *cur.borrow_mut() = val,
+					None => return ErrorList::run(format!("Property {} is undefined for object", chunk.names[*name]), *head_pos).err(),
+				}
+			}
+			Op::Bind(method_pos) => {
+				let method = pop!().to_callable(*method_pos)?;
+				let head = pop!();
+				let mut bound_method = method.cloned();
+				bound_method.bind(head);
+				stack.push(ValCallable::new(bound_method.wrap()));
+			}
+
+			Op::Match(i) => {
+				let shape = &chunk.matches[*i];
+				let value = pop!();
+				let slots = &mut frame.slots;
+				let matched = interpreter.match_pattern(&shape.pattern, value, &mut |_, name, value| {
+					if let Some((_, slot)) = shape.slots.iter().find(|(id, _)| *id == name.get_id()) {
+						slots[*slot] = Rc::new(RefCell::new(value));
+					}
+				})?;
+				stack.push(Bool::new(matched));
+			}
+			Op::Destructure(i) => {
+				let shape = &chunk.matches[*i];
+				let value = pop!();
+				let slots = &mut frame.slots;
+				interpreter.destructure(&shape.pattern, value, &mut |_, name, value| {
+					if let Some((_, slot)) = shape.slots.iter().find(|(id, _)| *id == name.get_id()) {
+						slots[*slot] = Rc::new(RefCell::new(value));
+					}
+				})?;
+			}
+
+			Op::Template(i) => {
+				let positions = &chunk.templates[*i];
+				let values = pop_n!(positions.len());
+				let mut strs = Vec::new();
+				for (val, pos) in values.into_iter().zip(positions) { strs.push(val.to_string(interpreter, *pos)?); }
+				stack.push(Str::new(strs.join("")));
+			}
+			Op::List(len) => {
+				let values = pop_n!(*len);
+				stack.push(List::new(values));
+			}
+			Op::SpreadList(i) => {
+				let spreads = &chunk.spreads[*i];
+				let values = pop_n!(spreads.len());
+				let values = spread(interpreter, values, spreads)?.into_iter().map(|(val, _)| val).collect();
+				stack.push(List::new(values));
+			}
+			Op::Map(i) => {
+				let positions = &chunk.maps[*i];
+				let values = pop_n!(positions.len() * 2);
+				let mut map = MapData::default();
+				for (pair, pos) in values.chunks(2).zip(positions) {
+					map.insert(pair[0].to_key(*pos)?, pair[1].clone());
+				}
+				stack.push(Map::new(map));
+			}
+			Op::Object(i) => {
+				let shape = &chunk.objects[*i];
+				let values = pop_n!(shape.members.len());
+				let mut map = HashMap::new();
+				let mut attributes = shape.attributes.iter().cloned().collect();
+				for ((key, val_pos), val) in shape.members.iter().zip(values) {
+					match key {
+						Some(key) => { map.insert(key.clone(), Rc::new(RefCell::new(val))); }
+						None => spread_object(val.as_ref(), *val_pos, &mut map, &mut attributes)?,
+					}
+				}
+				stack.push(Object::new(map, attributes));
+			}
+			Op::Range(i) => {
+				let shape = &chunk.ranges[*i];
+				let step = shape.step.map(|pos| (pop!(), pos));
+				let end = shape.end.map(|pos| (pop!(), pos));
+				let start = shape.start.map(|pos| (pop!(), pos));
+				stack.push(Range::from_bounds(start, end, shape.inclusive, step)?);
+			}
+			Op::Error => { let val = pop!(); stack.push(Error::at(val, pos)) },
+			Op::Closure(i) => {
+				let proto = chunk.protos[*i].clone();
+				let upvalues = proto.captures.iter().map(|capture| match capture {
+					Capture::Local(slot) => frame.slots[*slot].clone(),
+					Capture::Upvalue(i) => frame.closure.upvalues[*i].clone(),
+				}).collect();
+				// lambdas declared inside methods still see the method's self
+				let closure = Closure::new(proto, upvalues, frame.closure.receiver.clone(), interpreter.env.clone());
+				stack.push(ValCallable::new(closure.wrap()));
+			}
+			Op::Attribute(i) => {
+				let shape = &chunk.attributes[*i];
+				let fields = pop_n!(shape.fields.len());
+				let methods = pop_n!(shape.methods.len());
+				let fields = shape.fields.iter().cloned().zip(fields.into_iter().map(|val| Rc::new(RefCell::new(val)))).collect::<HashMap<_, _>>();
+				let methods = shape.methods.iter().cloned().zip(methods.into_iter().map(|val| Rc::new(RefCell::new(val)))).collect::<HashMap<_, _>>();
+				let attrs = shape.attributes.iter().cloned().collect::<HashSet<_>>();
+				stack.push(Attribute::new(shape.name.clone(), methods, fields, attrs));
+			}
+			Op::Import(path) => {
+				let namespace = interpreter.import_module(&chunk.names[*path], pos)?;
+				stack.push(namespace);
+			}
+
+			Op::Return => {
+				let ret = pop!();
+				let frame = frames.pop().unwrap();
+				stack.truncate(frame.base);
+				match frames.last() {
+					Some(caller) => {
+						interpreter.env = caller.closure.env.clone();
+						interpreter.call_stack.pop();
+						stack.push(ret);
+					}
+					None => return ret.wrap(),
+				}
+			}
+		}
+	}
+}
+
@@ -0,0 +1,124 @@
+use std::{cell::RefCell, collections::VecDeque, path::PathBuf, rc::Rc};
+
+use rover::{debugger::{Frontend, Session, Step, Stop, launch}, interpreter::Interpreter, utils::source_pos::SourcePos};
+
+const PROGRAM: &str = "\
+function double(n) {
+	let a = n * 2
+	return a
+}
+
+function main(args) {
+	let a = 4
+	let b = double(a)
+	writeline(b)
+	for i in [1, 2] {
+		b = b + i
+	}
+	writeline(b)
+}
+";
+
+#[derive(Debug)]
+struct Seen {
+	line: u32,
+	reason: Stop,
+	locals: Vec<(String, String)>,
+	eval: Option<String>,
+}
+
+// answers every stop with the next scripted step, and writes down where it stopped and what it saw there
+struct Script {
+	steps: VecDeque<Step>,
+	seen: Rc<RefCell<Vec<Seen>>>,
+}
+
+impl Frontend for Script {
+	fn stopped(&mut self, session: &mut Session, interpreter: &mut Interpreter, pos: SourcePos, reason: Stop) -> Step {
+		let locals = session.locals(interpreter);
+		let eval = session.evaluate(interpreter, "a * 10").ok();
+		self.seen.borrow_mut().push(Seen { line: pos.lin, reason, locals, eval });
+		self.steps.pop_front().unwrap_or(Step::Continue)
+	}
+}
+
+fn program(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("rover-debug-{}-{}", name, std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	let path = dir.join("prog.mars");
+	std::fs::write(&path, PROGRAM).unwrap();
+	path
+}
+
+// runs the program under the debugger, giving back every stop and the program output
+fn debug(path: &PathBuf, session: Session, steps: Vec<Step>, stop_on_entry: bool) -> (Vec<Seen>, String) {
+	let seen = Rc::new(RefCell::new(Vec::new()));
+	let output = Rc::new(RefCell::new(String::new()));
+	let sink = output.clone();
+	let frontend = Script { steps: steps.into(), seen: seen.clone() };
+	launch(frontend, session, vec![], stop_on_entry, Some(Box::new(move |text: &str| sink.borrow_mut().push_str(text)))).unwrap();
+	std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+	let seen = seen.take();
+	let output = output.take();
+	(seen, output)
+}
+
+fn lines(seen: &[Seen]) -> Vec<u32> {
+	seen.iter().map(|seen| seen.line).collect()
+}
+
+#[test]
+fn stepping_over_stops_once_per_line() {
+	let path = program("over");
+	let session = Session::new(path.to_str().unwrap());
+	let (seen, output) = debug(&path, session, vec![Step::Over; 20], true);
+
+	assert_eq!(seen[0].reason, Stop::Entry);
+	assert!(seen[1..].iter().all(|seen| seen.reason == Stop::Step));
+	// the for line is stopped at before every item and once more when the list ran out
+	assert_eq!(lines(&seen), vec![7, 8, 9, 10, 11, 10, 11, 10, 13]);
+	assert_eq!(output, "8\n11\n");
+}
+
+#[test]
+fn stepping_in_and_out_follows_calls() {
+	let path = program("in-out");
+	let session = Session::new(path.to_str().unwrap());
+	let (seen, output) = debug(&path, session, vec![Step::Over, Step::In, Step::Over, Step::Out, Step::Continue], true);
+
+	assert_eq!(lines(&seen), vec![7, 8, 2, 3, 9]);
+	assert_eq!(output, "8\n11\n");
+}
+
+#[test]
+fn breakpoints_match_any_path_to_the_file() {
+	let path = program("breakpoints");
+	let mut session = Session::new(path.to_str().unwrap());
+	let dir = path.parent().unwrap();
+	// the same file through a longer path, and a line of a file that isn't part of the program
+	session.add_breakpoint(dir.join(".").join("prog.mars").to_str().unwrap(), 3);
+	session.add_breakpoint(dir.join("other.mars").to_str().unwrap(), 11);
+	let (seen, output) = debug(&path, session, vec![], false);
+
+	assert_eq!(lines(&seen), vec![3]);
+	assert_eq!(seen[0].reason, Stop::Breakpoint);
+	assert_eq!(output, "8\n11\n");
+}
+
+#[test]
+fn locals_and_evaluate_see_the_current_frame() {
+	let path = program("locals");
+	let mut session = Session::new(path.to_str().unwrap());
+	session.add_breakpoint(path.to_str().unwrap(), 3);
+	session.add_breakpoint(path.to_str().unwrap(), 9);
+	let (seen, _) = debug(&path, session, vec![], false);
+
+	let pairs = |list: &[(&str, &str)]| list.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>();
+	assert_eq!(lines(&seen), vec![3, 9]);
+	// the latest declarations come first, then the parameters
+	assert_eq!(seen[0].locals, pairs(&[("a", "8"), ("n", "4")]));
+	assert_eq!(seen[0].eval.as_deref(), Some("80"));
+	assert_eq!(seen[1].locals, pairs(&[("b", "8"), ("a", "4"), ("args", "[]")]));
+	assert_eq!(seen[1].eval.as_deref(), Some("40"));
+}
@@ -0,0 +1,141 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use rover::{Engine, IntoValue, Value};
+
+fn error_file(engine: &mut Engine, source: &str) -> usize {
+	let err = engine.eval::<()>(source).unwrap_err();
+	let (_, pos) = err.entries().next().unwrap();
+	pos.unwrap().file
+}
+
+#[test]
+fn eval_returns_the_last_expression() {
+	let mut engine = Engine::new();
+	assert_eq!(engine.eval::<f64>("1 + 2 * 3").unwrap(), 7.0);
+	assert_eq!(engine.eval::<String>("'ab' + 'cd'").unwrap(), "abcd");
+	assert_eq!(engine.eval::<Vec<i64>>("[1, 2, 3]").unwrap(), vec![1, 2, 3]);
+	assert_eq!(engine.eval::<Option<bool>>("none").unwrap(), None);
+	engine.eval::<()>("let x = 5").unwrap();
+}
+
+#[test]
+fn eval_keeps_names_between_calls() {
+	let mut engine = Engine::new();
+	engine.eval::<()>("let count = 1").unwrap();
+	engine.eval::<()>("function bump() { count = count + 1; }").unwrap();
+	engine.eval::<()>("bump(); bump()").unwrap();
+	assert_eq!(engine.eval::<f64>("count").unwrap(), 3.0);
+}
+
+#[test]
+fn eval_reports_errors() {
+	let mut engine = Engine::new();
+	assert!(engine.eval::<f64>("missing + 1").is_err());
+	assert!(engine.eval::<f64>("'text'").is_err());
+	assert!(engine.eval::<()>("let = 2").is_err());
+	assert!(engine.eval::<u32>("1.5").is_err());
+
+	// a failed evaluation doesn't declare anything
+	assert!(engine.eval::<()>("let y = 1; missing").is_err());
+	assert!(engine.get::<f64>("y").is_err());
+}
+
+#[test]
+fn eval_checks_types() {
+	let mut engine = Engine::new();
+	assert!(engine.eval::<()>("let n: number = 'text'").is_err());
+	assert!(engine.get::<f64>("n").is_err());
+	assert!(engine.eval::<f64>("1 + true").is_err());
+
+	// names declared by earlier calls are still known to the checker
+	engine.eval::<()>("attr Shape { area() => 0 }").unwrap();
+	engine.eval::<()>("function half(n: number) => n / 2").unwrap();
+	assert_eq!(engine.eval::<f64>("let s: Shape = { is Shape }; s.area() + half(4)").unwrap(), 2.0);
+	assert!(engine.eval::<f64>("half('four')").is_err());
+}
+
+#[test]
+fn integers_must_fit_their_type() {
+	let mut engine = Engine::new();
+	assert_eq!(engine.eval::<u32>("4294967295").unwrap(), u32::MAX);
+	assert!(engine.eval::<u32>("4294967296").is_err());
+	assert_eq!(engine.eval::<i32>("-2147483648").unwrap(), i32::MIN);
+	assert!(engine.eval::<i32>("2147483648").is_err());
+	assert!(engine.eval::<i64>("9223372036854775808").is_err());
+	assert!(engine.eval::<u64>("18446744073709551616").is_err());
+	assert!(engine.eval::<u64>("-1").is_err());
+}
+
+#[test]
+fn eval_reuses_its_source() {
+	let mut engine = Engine::new();
+	let file = error_file(&mut engine, "missing");
+	for _ in 0..10 {
+		assert_eq!(error_file(&mut engine, "1 + missing"), file);
+	}
+}
+
+#[test]
+fn get_and_set() {
+	let mut engine = Engine::new();
+	engine.set("limit", 10).unwrap();
+	engine.set("name", "mars").unwrap();
+	assert_eq!(engine.eval::<f64>("limit * 2").unwrap(), 20.0);
+	assert_eq!(engine.get::<String>("name").unwrap(), "mars");
+
+	engine.eval::<()>("let point = { x = 1, y = 2 }").unwrap();
+	let point: HashMap<String, f64> = engine.get("point").unwrap();
+	assert_eq!(point["x"], 1.0);
+	assert_eq!(point["y"], 2.0);
+
+	// setting a name again replaces it
+	engine.set("limit", "none").unwrap();
+	assert_eq!(engine.get::<String>("limit").unwrap(), "none");
+	assert!(engine.get::<f64>("limit").is_err());
+	assert!(engine.get::<f64>("undefined").is_err());
+}
+
+#[test]
+fn register_converts_arguments_and_results() {
+	let mut engine = Engine::new();
+	engine.register("hypot", |a: f64, b: f64| (a * a + b * b).sqrt()).unwrap();
+	engine.register("parse", |text: String| text.parse::<f64>().map_err(|err| err.to_string())).unwrap();
+	assert_eq!(engine.eval::<f64>("hypot(3, 4)").unwrap(), 5.0);
+	assert_eq!(engine.eval::<f64>("parse('12')").unwrap(), 12.0);
+	assert!(engine.eval::<f64>("parse('twelve')").is_err());
+	assert!(engine.eval::<f64>("hypot('3', 4)").is_err());
+	assert!(engine.eval::<f64>("hypot(3)").is_err());
+}
+
+#[test]
+fn register_keeps_rust_state() {
+	let mut engine = Engine::new();
+	let seen = Rc::new(RefCell::new(Vec::new()));
+	let log = seen.clone();
+	engine.register("log", move |text: String| log.borrow_mut().push(text)).unwrap();
+	engine.eval::<()>("for x in [1, 2] { log('item ' + x) }").unwrap();
+	assert_eq!(*seen.borrow(), vec!["item 1", "item 2"]);
+}
+
+#[test]
+fn call_and_apply() {
+	let mut engine = Engine::new();
+	engine.eval::<()>("function add(a, b) { return a + b; }").unwrap();
+	assert_eq!(engine.call::<f64>("add", vec![1.into_value(), 2.into_value()]).unwrap(), 3.0);
+	assert!(engine.call::<f64>("add", vec![1.into_value()]).is_err());
+	assert!(engine.call::<f64>("missing", vec![]).is_err());
+
+	engine.eval::<()>("let square = function(n) => n * n").unwrap();
+	let square: Box<dyn Value> = engine.get("square").unwrap();
+	assert_eq!(engine.apply::<f64>(square.as_ref(), vec![7.into_value()]).unwrap(), 49.0);
+}
+
+#[test]
+fn output_can_be_captured() {
+	let mut engine = Engine::new();
+	let output = Rc::new(RefCell::new(String::new()));
+	let sink = output.clone();
+	engine.set_output(move |text| sink.borrow_mut().push_str(text));
+	engine.eval::<()>("writeline('hello'); write(1 + 1)").unwrap();
+	assert_eq!(*output.borrow(), "hello\n2");
+}
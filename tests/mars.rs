@@ -0,0 +1,12 @@
+use rover::{interpreter::Backend, tester};
+
+// the mars tests next to this file must pass on both backends
+#[test]
+fn mars_tests_pass_on_the_tree_walker() {
+	assert!(tester::run(vec!["tests".into()], Backend::TreeWalk));
+}
+
+#[test]
+fn mars_tests_pass_on_the_vm() {
+	assert!(tester::run(vec!["tests".into()], Backend::Bytecode));
+}
@@ -0,0 +1,47 @@
+use std::{path::PathBuf, process::{Command, Output}};
+
+// writes the files of a program to a fresh directory
+fn program(name: &str, files: &[(&str, &str)]) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("rover-{}-{}", name, std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+	for (path, text) in files {
+		let path = dir.join(path);
+		std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+		std::fs::write(path, text).unwrap();
+	}
+	dir
+}
+
+fn run(dir: &PathBuf, main: &str, backend: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_rover")).args(backend).arg(dir.join(main)).output().unwrap()
+}
+
+#[test]
+fn import_cycles_start_at_the_entry_file() {
+	let dir = program("cycle", &[
+		("c1.mars", "import \"c2\"\n\nfunction main(args) {\n\twriteline(c2.value())\n}\n"),
+		("c2.mars", "import \"c1\"\n\nfunction value() => 1\n"),
+	]);
+	for backend in [&[][..], &["--vm"]] {
+		let output = run(&dir, "c1.mars", backend);
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		assert!(stderr.contains("Import cycle detected: c1.mars -> c2.mars -> c1.mars"), "{}", stderr);
+		assert!(output.stdout.is_empty());
+	}
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn imports_are_relative_to_the_importing_module() {
+	let dir = program("nested", &[
+		("main.mars", "import \"lib/shapes\"\nimport \"units\"\n\nfunction main(args) {\n\twriteline('area ' + shapes.area(2) + ' from ' + units.name())\n}\n"),
+		("units.mars", "function name() => 'top'\n"),
+		("lib/shapes.mars", "import \"units\"\n\nfunction area(side) => side * side * units.scale()\n"),
+		("lib/units.mars", "function scale() => 10\n"),
+	]);
+	for backend in [&[][..], &["--vm"]] {
+		let output = run(&dir, "main.mars", backend);
+		assert_eq!(String::from_utf8_lossy(&output.stdout), "area 40 from top\n", "{}", String::from_utf8_lossy(&output.stderr));
+	}
+	std::fs::remove_dir_all(dir).unwrap();
+}